@@ -0,0 +1,35 @@
+use std::process::Command;
+
+// Exposes the Git commit and build timestamp to the crate as the GIT_SHA and BUILD_TIMESTAMP
+// environment variables. The Git commit can be overridden by setting GIT_SHA at build time, which
+// is useful when building in an environment without Git.
+fn main() {
+    println!("cargo:rerun-if-env-changed=GIT_SHA");
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/refs");
+
+    let git_sha = std::env::var("GIT_SHA")
+        .ok()
+        .filter(|sha| !sha.is_empty())
+        .or_else(git_sha_from_repository)
+        .unwrap_or_else(|| String::from("unknown"));
+    println!("cargo:rustc-env=GIT_SHA={}", git_sha);
+
+    let build_timestamp = chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+    println!("cargo:rustc-env=BUILD_TIMESTAMP={}", build_timestamp);
+}
+
+fn git_sha_from_repository() -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    String::from_utf8(output.stdout)
+        .ok()
+        .map(|sha| sha.trim().to_string())
+}
@@ -0,0 +1,205 @@
+use k8s_openapi::api::authentication::v1::{TokenReview, TokenReviewSpec};
+use k8s_openapi::api::authorization::v1::{
+    ResourceAttributes, SubjectAccessReview, SubjectAccessReviewSpec,
+};
+use kube::api::PostParams;
+use kube::{Api, Client};
+
+// Authorizes the callers of Tainter's admin endpoints through the cluster's RBAC. Tainter writes
+// nodes with its own service account, so without the check anyone who can reach the server would
+// gain Tainter's permissions. The API server reviews the caller's bearer token, and the caller
+// must be allowed to update nodes themselves.
+pub struct Authorizer {
+    client: Client,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Decision {
+    Allowed,
+    // The request has no bearer token, or the API server does not authenticate it.
+    Unauthenticated,
+    // The caller, identified by their username, is not allowed to update nodes.
+    Forbidden(String),
+}
+
+impl Authorizer {
+    pub fn new(client: Client) -> Self {
+        Authorizer { client }
+    }
+
+    // Decides whether the owner of the token may update the node, or all nodes if node_name is
+    // not set.
+    pub async fn authorize(
+        &self,
+        token: Option<&str>,
+        node_name: Option<&str>,
+    ) -> Result<Decision, kube::Error> {
+        let Some(token) = token.filter(|token| !token.is_empty()) else {
+            return Ok(Decision::Unauthenticated);
+        };
+
+        let review = TokenReview {
+            spec: TokenReviewSpec {
+                token: Some(token.to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let review = Api::<TokenReview>::all(self.client.clone())
+            .create(&PostParams::default(), &review)
+            .await?;
+        let Some(status) = review
+            .status
+            .filter(|status| status.authenticated == Some(true))
+        else {
+            return Ok(Decision::Unauthenticated);
+        };
+        let user = status.user.unwrap_or_default();
+
+        let review = SubjectAccessReview {
+            spec: SubjectAccessReviewSpec {
+                user: user.username.clone(),
+                uid: user.uid,
+                groups: user.groups,
+                extra: user.extra,
+                resource_attributes: Some(ResourceAttributes {
+                    group: Some(String::new()),
+                    resource: Some("nodes".to_string()),
+                    verb: Some("update".to_string()),
+                    name: node_name.map(String::from),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let review = Api::<SubjectAccessReview>::all(self.client.clone())
+            .create(&PostParams::default(), &review)
+            .await?;
+        if review.status.is_some_and(|status| status.allowed) {
+            Ok(Decision::Allowed)
+        } else {
+            Ok(Decision::Forbidden(user.username.unwrap_or_default()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http::{Request, Response};
+    use k8s_openapi::serde_json;
+    use kube::client::Body;
+    use test_case::test_case;
+
+    use super::*;
+
+    fn response(body: serde_json::Value) -> Response<Body> {
+        Response::builder()
+            .body(Body::from(serde_json::to_vec(&body).unwrap()))
+            .unwrap()
+    }
+
+    async fn body_of(request: Request<Body>) -> serde_json::Value {
+        let bytes = request.into_body().collect_bytes().await.unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_authorize_without_token() {
+        let (mock_service, _) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+        let authorizer = Authorizer::new(Client::new(mock_service, "default"));
+
+        let decision = authorizer.authorize(None, None).await.unwrap();
+
+        assert_eq!(Decision::Unauthenticated, decision);
+    }
+
+    #[tokio::test]
+    async fn test_authorize_unauthenticated_token() {
+        let (mock_service, mut handle) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+        let authorizer = Authorizer::new(Client::new(mock_service, "default"));
+
+        tokio::spawn(async move {
+            let (request, send) = handle
+                .next_request()
+                .await
+                .expect("token review not created");
+            assert_eq!(
+                request.uri().path(),
+                "/apis/authentication.k8s.io/v1/tokenreviews"
+            );
+            assert_eq!("forged", body_of(request).await["spec"]["token"]);
+            send.send_response(response(serde_json::json!({
+                "apiVersion": "authentication.k8s.io/v1",
+                "kind": "TokenReview",
+                "spec": {},
+                "status": {"authenticated": false}
+            })));
+        });
+
+        let decision = authorizer.authorize(Some("forged"), None).await.unwrap();
+
+        assert_eq!(Decision::Unauthenticated, decision);
+    }
+
+    #[test_case(true, Decision::Allowed ; "allowed")]
+    #[test_case(false, Decision::Forbidden("jane".to_string()) ; "forbidden")]
+    #[tokio::test]
+    async fn test_authorize_reviews_access_of_caller(allowed: bool, expected: Decision) {
+        let (mock_service, mut handle) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+        let authorizer = Authorizer::new(Client::new(mock_service, "default"));
+
+        tokio::spawn(async move {
+            let (_, send) = handle
+                .next_request()
+                .await
+                .expect("token review not created");
+            send.send_response(response(serde_json::json!({
+                "apiVersion": "authentication.k8s.io/v1",
+                "kind": "TokenReview",
+                "spec": {},
+                "status": {
+                    "authenticated": true,
+                    "user": {"username": "jane", "groups": ["system:authenticated"]}
+                }
+            })));
+
+            let (request, send) = handle
+                .next_request()
+                .await
+                .expect("subject access review not created");
+            assert_eq!(
+                request.uri().path(),
+                "/apis/authorization.k8s.io/v1/subjectaccessreviews"
+            );
+            let body = body_of(request).await;
+            assert_eq!("jane", body["spec"]["user"]);
+            assert_eq!(
+                serde_json::json!(["system:authenticated"]),
+                body["spec"]["groups"]
+            );
+            assert_eq!(
+                serde_json::json!({
+                    "group": "",
+                    "name": "aks-zeus1-41950716-vmss000082",
+                    "resource": "nodes",
+                    "verb": "update"
+                }),
+                body["spec"]["resourceAttributes"]
+            );
+            send.send_response(response(serde_json::json!({
+                "apiVersion": "authorization.k8s.io/v1",
+                "kind": "SubjectAccessReview",
+                "spec": {},
+                "status": {"allowed": allowed}
+            })));
+        });
+
+        let decision = authorizer
+            .authorize(Some("token"), Some("aks-zeus1-41950716-vmss000082"))
+            .await
+            .unwrap();
+
+        assert_eq!(expected, decision);
+    }
+}
@@ -0,0 +1,439 @@
+use std::env;
+use std::fmt::Display;
+use std::fs;
+use std::future::Future;
+use std::io;
+use std::time::Duration;
+
+use http::uri::InvalidUri;
+use kube::config::InferConfigError;
+use kube::Config;
+use thiserror::Error;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+use crate::settings;
+
+#[derive(Error, Debug)]
+pub enum NewClientError {
+    #[error("error inferring Kubernetes configuration {0}")]
+    InferConfig(#[from] InferConfigError),
+    #[error("error creating Kubernetes client {0}")]
+    Create(#[from] kube::Error),
+    #[error("error parsing proxy URL {0}")]
+    ProxyUrl(#[from] InvalidUri),
+    #[error("error reading {0}: {1}")]
+    ReadFile(String, io::Error),
+    #[error("error parsing certificate authority {0}: {1}")]
+    ParseCaCert(String, pem::PemError),
+    #[error("error reaching API server {0}")]
+    Connect(kube::Error),
+}
+
+impl NewClientError {
+    // Whether the error may resolve by itself, e.g. because the API server is still starting
+    // during cluster bootstrap, as opposed to errors of Tainter's configuration.
+    fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            NewClientError::InferConfig(_) | NewClientError::Connect(_)
+        )
+    }
+}
+
+const DEFAULT_INIT_RETRIES: u32 = 5;
+const DEFAULT_INIT_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+// Creates a Kubernetes client from the inferred configuration, e.g. the in-cluster configuration,
+// with Tainter's client settings applied. Transient errors are retried with backoff.
+pub async fn new(settings: &settings::Client) -> Result<kube::Client, NewClientError> {
+    retry(
+        settings.init_retries.unwrap_or(DEFAULT_INIT_RETRIES),
+        settings
+            .init_retry_delay
+            .unwrap_or(DEFAULT_INIT_RETRY_DELAY),
+        NewClientError::is_transient,
+        || try_new(settings),
+    )
+    .await
+}
+
+async fn try_new(settings: &settings::Client) -> Result<kube::Client, NewClientError> {
+    let config = Config::infer().await?;
+    let config = configure(config, settings, env::var("HTTPS_PROXY").ok())?;
+    let client = kube::Client::try_from(config)?;
+
+    // Creating the client doesn't connect to the API server, so its reachability is verified with
+    // a cheap request.
+    client
+        .apiserver_version()
+        .await
+        .map_err(NewClientError::Connect)?;
+
+    Ok(client)
+}
+
+// Calls attempt until it succeeds or fails with an error that is not retryable, retrying up to
+// retries times. The delay before the first retry doubles with each further retry.
+async fn retry<T, E, F, Fut>(
+    retries: u32,
+    delay: Duration,
+    retryable: impl Fn(&E) -> bool,
+    mut attempt: F,
+) -> Result<T, E>
+where
+    E: Display,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let mut delay = delay;
+    for retry in 1..=retries {
+        match attempt().await {
+            Ok(value) => return Ok(value),
+            Err(error) if retryable(&error) => {
+                tracing::warn!(
+                    error = error.to_string(),
+                    retry,
+                    retries,
+                    delay = format!("{:?}", delay),
+                    "Error initializing Kubernetes client, retrying"
+                );
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+            }
+            Err(error) => return Err(error),
+        }
+    }
+
+    attempt().await
+}
+
+// Applies Tainter's client settings to the configuration. Settings that are not set leave the
+// configuration's defaults in place. https_proxy is the value of the HTTPS_PROXY environment
+// variable, which is used if no proxy URL is configured.
+fn configure(
+    mut config: Config,
+    settings: &settings::Client,
+    https_proxy: Option<String>,
+) -> Result<Config, NewClientError> {
+    if let Some(connect_timeout) = settings.connect_timeout {
+        config.connect_timeout = Some(connect_timeout);
+    }
+
+    if let Some(read_timeout) = settings.read_timeout {
+        config.read_timeout = Some(read_timeout);
+    }
+
+    if let Some(proxy_url) = settings.proxy_url.clone().or(https_proxy) {
+        config.proxy_url = Some(proxy_url.parse()?);
+    }
+
+    if let Some(token_file) = &settings.token_file {
+        // The token is read by the client whenever it is needed, which picks up rotated tokens.
+        // Reading it once here makes an unreadable file fail startup rather than the first request.
+        read(token_file)?;
+        config.auth_info.token = None;
+        config.auth_info.token_file = Some(token_file.clone());
+    }
+
+    if let Some(ca_cert_file) = &settings.ca_cert_file {
+        config.root_cert = Some(ca_certs(ca_cert_file)?);
+    }
+
+    Ok(config)
+}
+
+fn read(path: &str) -> Result<Vec<u8>, NewClientError> {
+    fs::read(path).map_err(|error| NewClientError::ReadFile(path.to_string(), error))
+}
+
+// Returns the DER-encoded certificates of the PEM-encoded certificate authority file.
+fn ca_certs(path: &str) -> Result<Vec<Vec<u8>>, NewClientError> {
+    let pems = pem::parse_many(read(path)?)
+        .map_err(|error| NewClientError::ParseCaCert(path.to_string(), error))?;
+    if pems.is_empty() {
+        return Err(NewClientError::ParseCaCert(
+            path.to_string(),
+            pem::PemError::MissingData,
+        ));
+    }
+
+    Ok(pems.into_iter().map(|pem| pem.into_contents()).collect())
+}
+
+// Limits the rate of requests to qps per second on average, while allowing bursts of up to burst
+// requests. Requests beyond the limit wait rather than fail.
+pub struct RateLimiter {
+    qps: f64,
+    burst: f64,
+    bucket: Mutex<Bucket>,
+}
+
+// A token bucket that holds up to burst tokens and is refilled at qps tokens per second.
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl RateLimiter {
+    pub fn new(qps: f64, burst: u32) -> Self {
+        RateLimiter {
+            qps,
+            burst: burst as f64,
+            bucket: Mutex::new(Bucket {
+                tokens: burst as f64,
+                refilled_at: Instant::now(),
+            }),
+        }
+    }
+
+    // Waits until a request may be issued. Waiting requests are let through in order, since the
+    // bucket stays locked while waiting.
+    pub async fn acquire(&self) {
+        let mut bucket = self.bucket.lock().await;
+
+        let now = Instant::now();
+        let refill = now.duration_since(bucket.refilled_at).as_secs_f64() * self.qps;
+        bucket.tokens = (bucket.tokens + refill).min(self.burst);
+        bucket.refilled_at = now;
+
+        if bucket.tokens < 1.0 {
+            let wait = Duration::from_secs_f64((1.0 - bucket.tokens) / self.qps);
+            tracing::debug!(
+                wait = format!("{:?}", wait),
+                "Rate limit exceeded, delaying request"
+            );
+            tokio::time::sleep(wait).await;
+            bucket.tokens = 1.0;
+            bucket.refilled_at = Instant::now();
+        }
+
+        bucket.tokens -= 1.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use test_case::test_case;
+    use tracing_test::traced_test;
+
+    use super::*;
+
+    fn config() -> Config {
+        Config::new("https://kubernetes.default.svc".parse().unwrap())
+    }
+
+    #[test]
+    fn test_configure_applies_timeouts() {
+        let settings = settings::Client {
+            connect_timeout: Some(Duration::from_secs(5)),
+            read_timeout: Some(Duration::from_secs(30)),
+            ..Default::default()
+        };
+
+        let config = configure(config(), &settings, None).unwrap();
+
+        assert_eq!(Some(Duration::from_secs(5)), config.connect_timeout);
+        assert_eq!(Some(Duration::from_secs(30)), config.read_timeout);
+    }
+
+    #[test]
+    fn test_configure_keeps_defaults_when_unset() {
+        let defaults = config();
+
+        let config = configure(config(), &settings::Client::default(), None).unwrap();
+
+        assert_eq!(defaults.connect_timeout, config.connect_timeout);
+        assert_eq!(defaults.read_timeout, config.read_timeout);
+        assert_eq!(None, config.proxy_url);
+    }
+
+    #[test]
+    fn test_configure_applies_proxy_url() {
+        let settings = settings::Client {
+            proxy_url: Some("http://proxy.internal:3128".to_string()),
+            ..Default::default()
+        };
+
+        let config = configure(
+            config(),
+            &settings,
+            Some("http://ignored.internal:3128".to_string()),
+        )
+        .unwrap();
+
+        assert_eq!(
+            Some("http://proxy.internal:3128".parse().unwrap()),
+            config.proxy_url
+        );
+    }
+
+    #[test]
+    fn test_configure_falls_back_to_https_proxy() {
+        let config = configure(
+            config(),
+            &settings::Client::default(),
+            Some("http://fallback.internal:3128".to_string()),
+        )
+        .unwrap();
+
+        assert_eq!(
+            Some("http://fallback.internal:3128".parse().unwrap()),
+            config.proxy_url
+        );
+    }
+
+    #[test]
+    fn test_configure_returns_error_on_invalid_https_proxy() {
+        let res = configure(
+            config(),
+            &settings::Client::default(),
+            Some("http://bad proxy".to_string()),
+        );
+
+        assert!(res.is_err());
+        assert!(res
+            .err()
+            .unwrap()
+            .to_string()
+            .starts_with("error parsing proxy URL"));
+    }
+
+    #[test]
+    fn test_configure_applies_token_and_ca_cert_files() {
+        let settings = settings::Client {
+            token_file: Some("src/client/testfiles/token".to_string()),
+            ca_cert_file: Some("src/client/testfiles/ca.crt".to_string()),
+            ..Default::default()
+        };
+
+        let config = configure(config(), &settings, None).unwrap();
+
+        assert_eq!(
+            Some("src/client/testfiles/token".to_string()),
+            config.auth_info.token_file
+        );
+        assert_eq!(Some(vec![(0..48).collect::<Vec<u8>>()]), config.root_cert);
+    }
+
+    #[test_case(Some("src/client/testfiles/does-not-exist"), None, "error reading src/client/testfiles/does-not-exist" ; "returns error on missing token file")]
+    #[test_case(None, Some("src/client/testfiles/does-not-exist"), "error reading src/client/testfiles/does-not-exist" ; "returns error on missing ca cert file")]
+    #[test_case(None, Some("src/client/testfiles/invalid-ca.crt"), "error parsing certificate authority src/client/testfiles/invalid-ca.crt" ; "returns error on invalid ca cert file")]
+    fn test_configure_returns_error_on_unusable_files(
+        token_file: Option<&str>,
+        ca_cert_file: Option<&str>,
+        expected_error: &str,
+    ) {
+        let settings = settings::Client {
+            token_file: token_file.map(String::from),
+            ca_cert_file: ca_cert_file.map(String::from),
+            ..Default::default()
+        };
+
+        let res = configure(config(), &settings, None);
+
+        assert!(res.is_err());
+        assert!(res.err().unwrap().to_string().starts_with(expected_error));
+    }
+
+    #[tokio::test(start_paused = true)]
+    #[traced_test]
+    async fn test_retry_succeeds_after_transient_failures() {
+        let attempts = AtomicU32::new(0);
+        let start = Instant::now();
+
+        let res = retry(
+            5,
+            Duration::from_secs(1),
+            |_: &String| true,
+            || async {
+                match attempts.fetch_add(1, Ordering::SeqCst) {
+                    0 | 1 => Err("connection refused".to_string()),
+                    _ => Ok("client"),
+                }
+            },
+        )
+        .await;
+
+        assert_eq!(Ok("client"), res);
+        assert_eq!(3, attempts.load(Ordering::SeqCst));
+        // The delay doubles after each retry.
+        assert_eq!(Duration::from_secs(3), start.elapsed());
+        assert!(logs_contain(
+            r#"Error initializing Kubernetes client, retrying error="connection refused" retry=2 retries=5 delay="2s""#
+        ));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_retry_gives_up_after_retries() {
+        let attempts = AtomicU32::new(0);
+
+        let res: Result<(), String> = retry(
+            2,
+            Duration::from_secs(1),
+            |_| true,
+            || async {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                Err("connection refused".to_string())
+            },
+        )
+        .await;
+
+        assert_eq!(Err("connection refused".to_string()), res);
+        assert_eq!(3, attempts.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_retry_does_not_retry_permanent_errors() {
+        let attempts = AtomicU32::new(0);
+
+        let res: Result<(), String> = retry(
+            5,
+            Duration::from_secs(1),
+            |error: &String| error != "invalid token file",
+            || async {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                Err("invalid token file".to_string())
+            },
+        )
+        .await;
+
+        assert_eq!(Err("invalid token file".to_string()), res);
+        assert_eq!(1, attempts.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_rate_limiter_throttles_requests_beyond_burst() {
+        let limiter = RateLimiter::new(2.0, 3);
+        let start = Instant::now();
+
+        for _ in 0..3 {
+            limiter.acquire().await;
+        }
+        assert_eq!(Duration::ZERO, start.elapsed());
+
+        // Tokens are refilled at 2 per second, so each further request waits half a second.
+        limiter.acquire().await;
+        assert_eq!(Duration::from_millis(500), start.elapsed());
+        limiter.acquire().await;
+        assert_eq!(Duration::from_millis(1000), start.elapsed());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_rate_limiter_refills_up_to_burst() {
+        let limiter = RateLimiter::new(1.0, 2);
+        limiter.acquire().await;
+        limiter.acquire().await;
+
+        // Idling for longer than it takes to refill the bucket must not allow more than burst
+        // requests at once.
+        tokio::time::sleep(Duration::from_secs(10)).await;
+        let start = Instant::now();
+        for _ in 0..3 {
+            limiter.acquire().await;
+        }
+
+        assert_eq!(Duration::from_secs(1), start.elapsed());
+    }
+}
@@ -0,0 +1,51 @@
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+
+// A source of the current time. Injecting the clock makes time-dependent behaviour, such as the
+// time_added field of NoExecute taints, deterministic in tests.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+// The clock used outside of tests.
+pub struct UtcClock;
+
+impl Clock for UtcClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+// Allows several reconcilers to share a clock.
+impl Clock for Arc<dyn Clock> {
+    fn now(&self) -> DateTime<Utc> {
+        self.as_ref().now()
+    }
+}
+
+// A clock that always returns the same time.
+#[cfg(test)]
+pub struct FixedClock(pub DateTime<Utc>);
+
+#[cfg(test)]
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixed_clock_returns_fixed_time() {
+        let time = DateTime::parse_from_rfc3339("2024-05-12T11:21:10Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let clock = FixedClock(time);
+
+        assert_eq!(time, clock.now());
+        assert_eq!(time, clock.now());
+    }
+}
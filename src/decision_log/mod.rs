@@ -0,0 +1,184 @@
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use k8s_openapi::api::core::v1::Taint;
+use k8s_openapi::serde_json;
+use serde::Serialize;
+
+use crate::webhook::Action;
+
+// The version of the schema of the records. The schema is independent of the format of the logs,
+// and is only changed in a backwards incompatible way along with the version.
+pub const SCHEMA_VERSION: u32 = 1;
+
+// Writes a decision record for each taint that Tainter adds to or removes from a node as a line of
+// JSON (NDJSON), e.g. for ingestion by a log pipeline.
+#[derive(Clone)]
+pub struct DecisionLog {
+    writer: Arc<Mutex<Box<dyn Write + Send>>>,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Record {
+    pub version: u32,
+    pub node: String,
+    // The matcher that added the taint or whose conditions cleared, or None if no matcher adds the
+    // taint any more.
+    pub matcher: Option<String>,
+    pub action: Action,
+    pub taint: Taint,
+    // Why the taint was added or removed, e.g. the node conditions that triggered it.
+    pub reason: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl DecisionLog {
+    pub fn new(writer: impl Write + Send + 'static) -> Self {
+        DecisionLog {
+            writer: Arc::new(Mutex::new(Box::new(writer))),
+        }
+    }
+
+    pub fn stdout() -> Self {
+        Self::new(io::stdout())
+    }
+
+    // Appends the records to the file, which is created if it does not exist.
+    pub fn file(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self::new(file))
+    }
+
+    // Writes each record on a line of its own. Records are best effort: a record that can't be
+    // written is logged and dropped rather than failing reconciliation.
+    pub fn write(&self, records: &[Record]) {
+        if records.is_empty() {
+            return;
+        }
+
+        let mut writer = self
+            .writer
+            .lock()
+            .expect("decision log lock should not be poisoned");
+        for record in records {
+            // Each line is written at once, so that the lines of reconcilers that append to the
+            // same file don't interleave.
+            let result = serde_json::to_vec(record)
+                .map_err(io::Error::from)
+                .and_then(|mut line| {
+                    line.push(b'\n');
+                    writer.write_all(&line)
+                });
+            if let Err(error) = result {
+                tracing::warn!(
+                    error = error.to_string(),
+                    node = record.node,
+                    taint = record.taint.key,
+                    "Error writing decision record"
+                );
+            }
+        }
+        if let Err(error) = writer.flush() {
+            tracing::warn!(error = error.to_string(), "Error flushing decision log");
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    // A writer whose output can be inspected after it was moved into a decision log.
+    #[derive(Clone, Default)]
+    pub(crate) struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Buffer {
+        pub(crate) fn lines(&self) -> Vec<serde_json::Value> {
+            let bytes = self.0.lock().unwrap();
+            String::from_utf8(bytes.clone())
+                .unwrap()
+                .lines()
+                .map(|line| serde_json::from_str(line).unwrap())
+                .collect()
+        }
+    }
+
+    impl Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_write_writes_a_line_per_record() {
+        let buffer = Buffer::default();
+        let decision_log = DecisionLog::new(buffer.clone());
+        let timestamp = DateTime::parse_from_rfc3339("2024-05-12T11:21:10Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let record = |action, key: &str| Record {
+            version: SCHEMA_VERSION,
+            node: "aks-zeus1-41950716-vmss000082".to_string(),
+            matcher: None,
+            action,
+            taint: Taint {
+                effect: "NoSchedule".to_string(),
+                key: key.to_string(),
+                time_added: None,
+                value: None,
+            },
+            reason: "No matcher adds the taint any more".to_string(),
+            timestamp,
+        };
+
+        decision_log.write(&[
+            record(Action::Added, "not-ready"),
+            record(Action::Removed, "pressure"),
+        ]);
+
+        let lines = buffer.lines();
+        assert_eq!(2, lines.len());
+        assert_eq!("Added", lines[0]["action"]);
+        assert_eq!("not-ready", lines[0]["taint"]["key"]);
+        assert_eq!("Removed", lines[1]["action"]);
+        assert!(lines[1]["matcher"].is_null());
+    }
+
+    #[test]
+    fn test_file_appends_to_file() {
+        let path = std::env::temp_dir().join(format!(
+            "tainter-decision-log-{}.ndjson",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+
+        for _ in 0..2 {
+            DecisionLog::file(&path).unwrap().write(&[Record {
+                version: SCHEMA_VERSION,
+                node: "aks-zeus1-41950716-vmss000082".to_string(),
+                matcher: Some("not-ready".to_string()),
+                action: Action::Added,
+                taint: Taint {
+                    effect: "NoSchedule".to_string(),
+                    key: "not-ready".to_string(),
+                    time_added: None,
+                    value: None,
+                },
+                reason: "Ready=False".to_string(),
+                timestamp: Utc::now(),
+            }]);
+        }
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(2, contents.lines().count());
+    }
+}
@@ -0,0 +1,17 @@
+// Tainter is also a library so that the reconciler can be embedded in other binaries. The tainter
+// binary is a thin wrapper that reads settings from a configuration file.
+pub mod authorizer;
+pub mod client;
+pub mod clock;
+pub mod decision_log;
+pub mod logging;
+pub mod metrics;
+pub mod quantity;
+pub mod reconciler;
+pub mod settings;
+pub mod tainter;
+pub mod webhook;
+
+pub use reconciler::{Condition, Configuration, Reconciler};
+pub use settings::Settings;
+pub use tainter::{Tainter, TainterBuilder};
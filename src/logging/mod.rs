@@ -0,0 +1,125 @@
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::path::Path;
+use std::sync::Mutex;
+
+use thiserror::Error;
+use tracing::Subscriber;
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::{SubscriberInitExt, TryInitError};
+
+use crate::settings;
+
+#[derive(Error, Debug)]
+pub enum InitLogError {
+    #[error("log file directory {0} does not exist")]
+    MissingDirectory(String),
+    #[error("error opening log file {0}: {1}")]
+    OpenFile(String, io::Error),
+    #[error("error initializing logging {0}")]
+    Init(#[from] TryInitError),
+}
+
+// Installs the subscriber that writes logs to stdout and, if configured, to a file.
+pub fn init(settings: &settings::Log) -> Result<(), InitLogError> {
+    subscriber(settings)?.try_init()?;
+
+    Ok(())
+}
+
+// Returns a subscriber that writes JSON logs to stdout and, if a log file is configured, to the
+// file as well.
+fn subscriber(settings: &settings::Log) -> Result<impl Subscriber + Send + Sync, InitLogError> {
+    let stdout_layer = tracing_subscriber::fmt::layer()
+        .json()
+        .with_current_span(settings.with_current_span);
+
+    let file_layer = match settings.file.as_deref() {
+        None => None,
+        Some(path) => Some(
+            tracing_subscriber::fmt::layer()
+                .json()
+                .with_current_span(settings.with_current_span)
+                .with_writer(Mutex::new(open(path)?)),
+        ),
+    };
+
+    Ok(tracing_subscriber::registry()
+        .with(LevelFilter::from_level(settings.max_level))
+        .with(stdout_layer)
+        .with(file_layer))
+}
+
+// Opens the log file for appending, creating it if needed. The directory of the file must exist.
+fn open(path: &str) -> Result<File, InitLogError> {
+    let directory = Path::new(path)
+        .parent()
+        .filter(|directory| !directory.as_os_str().is_empty());
+    if let Some(directory) = directory {
+        if !directory.is_dir() {
+            return Err(InitLogError::MissingDirectory(
+                directory.display().to_string(),
+            ));
+        }
+    }
+
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|error| InitLogError::OpenFile(path.to_string(), error))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+
+    use super::*;
+
+    fn log_settings(file: Option<String>) -> settings::Log {
+        settings::Log {
+            max_level: tracing::Level::INFO,
+            with_current_span: true,
+            file,
+            aggregation: None,
+        }
+    }
+
+    #[test]
+    fn test_subscriber_writes_logs_to_file() {
+        let directory = env::temp_dir().join(format!("tainter-logging-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let path = directory.join("tainter.log");
+        let _ = fs::remove_file(&path);
+
+        let subscriber = subscriber(&log_settings(Some(path.display().to_string()))).unwrap();
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!(node = "aks-zeus1-41950716-vmss000082", "Processing node");
+            tracing::debug!("Filtered out by max_level");
+        });
+
+        let content = fs::read_to_string(&path).unwrap();
+        fs::remove_dir_all(&directory).unwrap();
+        assert!(content.contains(r#""message":"Processing node""#));
+        assert!(content.contains(r#""node":"aks-zeus1-41950716-vmss000082""#));
+        assert!(!content.contains("Filtered out by max_level"));
+    }
+
+    #[test]
+    fn test_subscriber_returns_error_on_missing_directory() {
+        let path = env::temp_dir()
+            .join("tainter-logging-does-not-exist")
+            .join("tainter.log");
+
+        let res = subscriber(&log_settings(Some(path.display().to_string())));
+
+        assert!(res.is_err());
+        assert!(res
+            .err()
+            .unwrap()
+            .to_string()
+            .ends_with("tainter-logging-does-not-exist does not exist"));
+    }
+}
@@ -1,12 +1,7 @@
 use clap::Parser;
-use kube::Client;
 use std::error::Error;
 
-use crate::settings::Settings;
-
-mod reconciler;
-mod settings;
-mod tainter;
+use tainter::{client, logging, Settings, Tainter};
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
@@ -14,6 +9,14 @@ struct Args {
     /// Path to TOML file from which configuration is read.
     #[arg(short, long)]
     config_file: String,
+    /// List nodes once, print how many are eligible for each matcher and exit without changing
+    /// anything.
+    #[arg(long)]
+    self_test: bool,
+    /// Print the effective configuration after defaults and environment overrides were applied
+    /// as JSON and exit.
+    #[arg(long)]
+    print_config: bool,
 }
 
 // Adding the actix_web::main attribute also implicitly adds tokio::main.
@@ -21,23 +24,33 @@ struct Args {
 #[actix_web::main]
 async fn main() -> Result<(), Box<dyn Error>> {
     let args = Args::parse();
-    println!(
-        "Reading configuration from file at path {}",
-        args.config_file
-    );
+    // The printed configuration is kept free of other output so that it can be piped to tools.
+    if !args.print_config {
+        println!(
+            "Reading configuration from file at path {}",
+            args.config_file
+        );
+    }
     let settings = Settings::new(args.config_file.as_str())?;
 
-    tracing_subscriber::fmt()
-        .json()
-        .with_max_level(settings.log.max_level)
-        .with_current_span(false)
-        .init();
+    if args.print_config {
+        println!("{}", settings.to_json()?);
+        return Ok(());
+    }
+
+    logging::init(&settings.log)?;
 
     tracing::info!("Initializing Kubernetes client");
 
-    let client = Client::try_default().await?;
+    let client = client::new(&settings.client).await?;
+
+    let tainter = Tainter::new(settings, client)?;
 
-    let tainter = tainter::Tainter::new(settings, client);
+    if args.self_test {
+        let summary = tainter.self_test().await?;
+        print!("{summary}");
+        return Ok(());
+    }
 
     tainter.start().await?;
 
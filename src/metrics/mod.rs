@@ -0,0 +1,218 @@
+use prometheus::core::Collector;
+use prometheus::{Encoder, IntCounter, IntCounterVec, IntGauge, Opts, Registry, TextEncoder};
+
+// Metrics are cheap to clone as all clones share the same underlying counters.
+#[derive(Clone)]
+pub struct Metrics {
+    registry: Registry,
+    pub watch_errors: IntCounterVec,
+    pub watch_restarts: IntCounter,
+    pub watch_relists: IntCounter,
+    pub shadow_divergences: IntCounter,
+    pub matcher_eligible: IntCounterVec,
+    pub matcher_ineligible: IntCounterVec,
+    pub taints_already_present: IntCounterVec,
+    pub malformed_nodes: IntCounterVec,
+    pub last_reconcile: IntGauge,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        let registry = Registry::new();
+
+        let watch_errors = IntCounterVec::new(
+            Opts::new(
+                "tainter_watch_errors_total",
+                "Number of errors returned by the node watch stream.",
+            ),
+            &["category"],
+        )
+        .expect("metric should be valid");
+        registry
+            .register(Box::new(watch_errors.clone()))
+            .expect("metric should only be registered once");
+
+        let watch_restarts = IntCounter::new(
+            "tainter_watch_restarts_total",
+            "Number of times the node watch stream successfully (re-)listed all nodes.",
+        )
+        .expect("metric should be valid");
+        registry
+            .register(Box::new(watch_restarts.clone()))
+            .expect("metric should only be registered once");
+
+        let watch_relists = IntCounter::new(
+            "tainter_watch_relists_total",
+            "Number of times the node watch fell too far behind and was forced to relist all nodes.",
+        )
+        .expect("metric should be valid");
+        registry
+            .register(Box::new(watch_relists.clone()))
+            .expect("metric should only be registered once");
+
+        let shadow_divergences = IntCounter::new(
+            "tainter_shadow_divergences_total",
+            "Number of times the shadow matchers would have tainted a node differently.",
+        )
+        .expect("metric should be valid");
+        registry
+            .register(Box::new(shadow_divergences.clone()))
+            .expect("metric should only be registered once");
+
+        let matcher_eligible = IntCounterVec::new(
+            Opts::new(
+                "tainter_matcher_eligible_total",
+                "Number of times a node was eligible for the taints of a matcher.",
+            ),
+            &["matcher"],
+        )
+        .expect("metric should be valid");
+        registry
+            .register(Box::new(matcher_eligible.clone()))
+            .expect("metric should only be registered once");
+
+        let matcher_ineligible = IntCounterVec::new(
+            Opts::new(
+                "tainter_matcher_ineligible_total",
+                "Number of times a node was not eligible for the taints of a matcher.",
+            ),
+            &["matcher"],
+        )
+        .expect("metric should be valid");
+        registry
+            .register(Box::new(matcher_ineligible.clone()))
+            .expect("metric should only be registered once");
+
+        let taints_already_present = IntCounterVec::new(
+            Opts::new(
+                "tainter_taints_already_present_total",
+                "Number of times a node was eligible for a taint of a matcher that it already had.",
+            ),
+            &["matcher"],
+        )
+        .expect("metric should be valid");
+        registry
+            .register(Box::new(taints_already_present.clone()))
+            .expect("metric should only be registered once");
+
+        let malformed_nodes = IntCounterVec::new(
+            Opts::new(
+                "tainter_malformed_nodes_total",
+                "Number of times a node was skipped because it had no name, spec or status.",
+            ),
+            &["reason"],
+        )
+        .expect("metric should be valid");
+        registry
+            .register(Box::new(malformed_nodes.clone()))
+            .expect("metric should only be registered once");
+
+        let last_reconcile = IntGauge::new(
+            "tainter_last_reconcile_timestamp_seconds",
+            "Unix timestamp of the last time the reconciler received a watch event or processed a node.",
+        )
+        .expect("metric should be valid");
+        registry
+            .register(Box::new(last_reconcile.clone()))
+            .expect("metric should only be registered once");
+
+        Metrics {
+            registry,
+            watch_errors,
+            watch_restarts,
+            watch_relists,
+            shadow_divergences,
+            matcher_eligible,
+            matcher_ineligible,
+            taints_already_present,
+            malformed_nodes,
+            last_reconcile,
+        }
+    }
+
+    // Returns all metrics in the Prometheus text exposition format.
+    // The number of errors of the watch stream across all categories.
+    pub fn watch_error_count(&self) -> u64 {
+        self.watch_errors
+            .collect()
+            .iter()
+            .flat_map(|family| family.get_metric())
+            .map(|metric| metric.get_counter().get_value() as u64)
+            .sum()
+    }
+
+    pub fn gather(&self) -> String {
+        let mut buffer = vec![];
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut buffer)
+            .expect("metrics should be encodable");
+
+        String::from_utf8(buffer).expect("metrics should be valid UTF-8")
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gather_includes_all_metrics() {
+        let metrics = Metrics::new();
+        metrics
+            .watch_errors
+            .with_label_values(&["initial_list"])
+            .inc();
+        metrics.watch_restarts.inc();
+        metrics.watch_relists.inc();
+        metrics.shadow_divergences.inc();
+        metrics
+            .matcher_eligible
+            .with_label_values(&["pressure"])
+            .inc();
+        metrics
+            .matcher_ineligible
+            .with_label_values(&["pressure"])
+            .inc();
+        metrics
+            .taints_already_present
+            .with_label_values(&["pressure"])
+            .inc();
+        metrics
+            .malformed_nodes
+            .with_label_values(&["no_name"])
+            .inc();
+        metrics.last_reconcile.set(1715512870);
+
+        let gathered = metrics.gather();
+
+        assert!(gathered.contains(r#"tainter_watch_errors_total{category="initial_list"} 1"#));
+        assert!(gathered.contains("tainter_watch_restarts_total 1"));
+        assert!(gathered.contains("tainter_watch_relists_total 1"));
+        assert!(gathered.contains("tainter_shadow_divergences_total 1"));
+        assert!(gathered.contains(r#"tainter_matcher_eligible_total{matcher="pressure"} 1"#));
+        assert!(gathered.contains(r#"tainter_matcher_ineligible_total{matcher="pressure"} 1"#));
+        assert!(gathered.contains(r#"tainter_taints_already_present_total{matcher="pressure"} 1"#));
+        assert!(gathered.contains(r#"tainter_malformed_nodes_total{reason="no_name"} 1"#));
+        assert!(gathered.contains("tainter_last_reconcile_timestamp_seconds 1715512870"));
+    }
+
+    #[test]
+    fn test_watch_error_count_sums_categories() {
+        let metrics = Metrics::new();
+        assert_eq!(0, metrics.watch_error_count());
+
+        metrics
+            .watch_errors
+            .with_label_values(&["initial_list"])
+            .inc();
+        metrics.watch_errors.with_label_values(&["watch"]).inc_by(2);
+
+        assert_eq!(3, metrics.watch_error_count());
+    }
+}
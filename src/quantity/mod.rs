@@ -0,0 +1,54 @@
+// Parses Kubernetes resource quantities such as "64Gi", "500m" and "1e3", which k8s_openapi
+// represents as plain strings.
+// See https://kubernetes.io/docs/reference/kubernetes-api/common-definitions/quantity/.
+pub fn parse(quantity: &str) -> Option<f64> {
+    let quantity = quantity.trim();
+    let suffix_start = quantity
+        .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '+' || c == '-'))
+        .unwrap_or(quantity.len());
+    let (number, suffix) = quantity.split_at(suffix_start);
+    let number: f64 = number.parse().ok()?;
+
+    let multiplier = match suffix {
+        "" => 1.0,
+        "Ki" => 1024_f64,
+        "Mi" => 1024_f64.powi(2),
+        "Gi" => 1024_f64.powi(3),
+        "Ti" => 1024_f64.powi(4),
+        "Pi" => 1024_f64.powi(5),
+        "Ei" => 1024_f64.powi(6),
+        "n" => 1e-9,
+        "u" => 1e-6,
+        "m" => 1e-3,
+        "k" => 1e3,
+        "M" => 1e6,
+        "G" => 1e9,
+        "T" => 1e12,
+        "P" => 1e15,
+        "E" => 1e18,
+        // Decimal exponents, e.g. 1e3 or 1E-3.
+        exponent if exponent.starts_with(['e', 'E']) => 10_f64.powi(exponent[1..].parse().ok()?),
+        _ => return None,
+    };
+
+    Some(number * multiplier)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case("1", Some(1.0) ; "plain number")]
+    #[test_case("1.5", Some(1.5) ; "decimal number")]
+    #[test_case("64Gi", Some(64.0 * 1024.0 * 1024.0 * 1024.0) ; "binary suffix")]
+    #[test_case("500m", Some(0.5) ; "decimal suffix")]
+    #[test_case("2k", Some(2000.0) ; "kilo suffix")]
+    #[test_case("1e3", Some(1000.0) ; "decimal exponent")]
+    #[test_case("", None ; "empty")]
+    #[test_case("Gi", None ; "suffix without number")]
+    #[test_case("64Gb", None ; "unknown suffix")]
+    fn test_parse(quantity: &str, expected: Option<f64>) {
+        assert_eq!(expected, parse(quantity));
+    }
+}
@@ -1,564 +1,9567 @@
-use chrono::Utc;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
 use std::pin::pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
 
-use futures::TryStreamExt;
-use k8s_openapi::api::core::v1::{Node, NodeCondition, Taint};
-use k8s_openapi::apimachinery::pkg::apis::meta::v1::Time;
-use kube::api::PostParams;
-use kube::runtime::reflector::Lookup;
+use futures::{Stream, TryStreamExt};
+use k8s_openapi::api::core::v1::{Node, NodeCondition, NodeStatus, Pod, Taint, Toleration};
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::{ObjectMeta, Time};
+use k8s_openapi::serde_json::{self, json};
+use kube::api::{EvictParams, ListParams, Patch, PatchParams, PostParams};
+use kube::runtime::reflector::{self, store::Writer, Lookup, Store};
 use kube::{
     api::Api,
     client::Client,
     runtime::{watcher, WatchStreamExt},
 };
-use regex::Regex;
+use rand::Rng;
+use regex::{Regex, RegexSet};
+use serde::Serialize;
 
+use crate::client::RateLimiter;
+use crate::clock::Clock;
+use crate::decision_log::{self, DecisionLog};
+use crate::metrics::Metrics;
+use crate::quantity;
+use crate::webhook::{self, Webhook};
+
+// A pattern of the type or status of a node condition.
 #[derive(Debug)]
-pub struct Condition {
-    pub type_: Regex,
-    pub status: Regex,
+pub enum Pattern {
+    // The value must equal the string, e.g. for patterns without regex metacharacters.
+    Literal(String),
+    // The value must match the regular expression.
+    Regex(Regex),
 }
 
-pub struct Configuration {
-    pub conditions: Vec<Condition>,
-    pub taint: Taint,
+impl Pattern {
+    pub fn is_match(&self, value: &str) -> bool {
+        match self {
+            Pattern::Literal(literal) => literal == value,
+            Pattern::Regex(regex) => regex.is_match(value),
+        }
+    }
+
+    // Literals have no capture groups, so only regular expressions capture values.
+    fn regex(&self) -> Option<&Regex> {
+        match self {
+            Pattern::Literal(_) => None,
+            Pattern::Regex(regex) => Some(regex),
+        }
+    }
+
+    // The pattern as a regular expression that matches the same values.
+    fn as_regex_string(&self) -> Cow<'_, str> {
+        match self {
+            Pattern::Literal(literal) => Cow::Owned(format!("^{}$", regex::escape(literal))),
+            Pattern::Regex(regex) => Cow::Borrowed(regex.as_str()),
+        }
+    }
 }
 
-pub struct Reconciler {
-    node_client: Api<Node>,
-    matchers: Vec<Configuration>,
+// Patterns are equal if they match the same values by construction, i.e. a literal equals the
+// regular expression that anchors it.
+impl PartialEq for Pattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_regex_string() == other.as_regex_string()
+    }
 }
 
-impl Reconciler {
-    pub fn new(client: Client, matchers: Vec<Configuration>) -> Reconciler {
-        Reconciler {
-            node_client: Api::all(client),
-            matchers,
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Pattern::Literal(literal) => write!(f, "{literal}"),
+            Pattern::Regex(regex) => write!(f, "{}", regex.as_str()),
         }
     }
+}
 
-    pub async fn start(&self) {
-        // https://github.com/kube-rs/kube/blob/dac48d96a7b72a88fdf60857e751b122b79a3cc4/examples/node_watcher.rs.
-        let wc = watcher::Config::default();
-        let obs = watcher(self.node_client.clone(), wc)
-            .default_backoff()
-            .applied_objects();
-        let mut obs = pin!(obs);
+#[derive(Debug, PartialEq)]
+pub enum Status {
+    // The status must match the pattern.
+    Pattern(Pattern),
+    // The status must not match the pattern.
+    NotPattern(Pattern),
+    // The status must be exactly one of the strings.
+    In(Vec<String>),
+}
 
-        loop {
-            let result = obs.try_next().await;
-
-            match result {
-                Ok(node) => {
-                    match node {
-                        Some(node) => self.process_node(node).await,
-                        None => {
-                            // I'm not sure if this can happen in practice.
-                            tracing::info!("Node is none")
-                        }
-                    }
-                }
-                Err(error) => {
-                    tracing::error!(error = error.to_string())
-                }
-            }
+impl Status {
+    fn is_match(&self, status: &str) -> bool {
+        match self {
+            Status::Pattern(pattern) => pattern.is_match(status),
+            Status::NotPattern(pattern) => !pattern.is_match(status),
+            Status::In(statuses) => statuses.iter().any(|candidate| candidate == status),
         }
     }
+}
+
+#[derive(Debug)]
+pub struct Condition {
+    pub type_: Pattern,
+    pub status: Status,
+    // If set, the reason of the node condition must also match.
+    pub reason: Option<Regex>,
+    // If set, the message of the node condition must also match.
+    pub message: Option<Regex>,
+    // If set, the node condition must have transitioned within this duration. Node conditions
+    // without a transition time never match.
+    pub max_age: Option<chrono::Duration>,
+    // If set, the node condition must have a fresh or, to detect silent kubelets, a stale
+    // heartbeat.
+    pub heartbeat: Option<Heartbeat>,
+    // How the type and status of the node condition are normalized before they are matched.
+    pub normalize: Normalize,
+}
+
+impl PartialEq for Condition {
+    fn eq(&self, other: &Self) -> bool {
+        let regex_str = |regex: &Option<Regex>| regex.as_ref().map(Regex::as_str).map(String::from);
+        self.type_ == other.type_
+            && self.status == other.status
+            && regex_str(&self.reason) == regex_str(&other.reason)
+            && regex_str(&self.message) == regex_str(&other.message)
+            && self.max_age == other.max_age
+            && self.heartbeat == other.heartbeat
+            && self.normalize == other.normalize
+    }
+}
 
-    async fn process_node(&self, node: Node) {
-        let node_name = node.name().expect("node should have a name");
-        tracing::info!(node_name = node_name.as_ref(), "Processing node");
+// The first field of a node condition that does not match a condition.
+#[derive(Debug, PartialEq)]
+enum Mismatch {
+    Type,
+    Status,
+    Reason,
+    Message,
+    Age,
+    Heartbeat,
+}
 
-        let status = node.status.as_ref().expect("node should have a status");
-        let conditions = status.conditions.as_ref();
+// The kubelet refreshes the heartbeats of the node's conditions periodically, so an old heartbeat
+// reveals a silent kubelet even if the condition's status still looks healthy, and well before the
+// node controller marks the node as not Ready. Node conditions without a heartbeat never match.
+#[derive(Debug, PartialEq)]
+pub enum Heartbeat {
+    // The node condition's last heartbeat must be at most this old.
+    Fresh(chrono::Duration),
+    // The node condition's last heartbeat must be older than this.
+    Stale(chrono::Duration),
+}
 
-        // If a node has no conditions, then we cannot determine whether it's eligible.
-        // I'm unsure if this can happen in practice.
-        if conditions.is_none() {
-            return;
+// Custom condition producers sometimes emit types and statuses with surrounding whitespace or in
+// unexpected case, e.g. "True " or "TRUE", which would otherwise silently not match.
+#[derive(Default, Debug, PartialEq)]
+pub enum Normalize {
+    // Match the type and status as they are.
+    #[default]
+    Off,
+    // Trim surrounding whitespace.
+    Trim,
+    // Trim surrounding whitespace and lowercase, so patterns must be lowercase to match.
+    TrimLowercase,
+}
+
+impl Normalize {
+    fn apply<'a>(&self, value: &'a str) -> Cow<'a, str> {
+        match self {
+            Normalize::Off => Cow::Borrowed(value),
+            Normalize::Trim => Cow::Borrowed(value.trim()),
+            Normalize::TrimLowercase => Cow::Owned(value.trim().to_lowercase()),
         }
+    }
+}
 
-        let mut taints_to_add: Vec<Taint> = vec![];
+// A taint that the node must already have, e.g. a taint of another controller such as
+// node.kubernetes.io/unreachable.
+#[derive(Debug)]
+pub struct TaintCondition {
+    pub key: Regex,
+    // If set, the effect of the taint must also match.
+    pub effect: Option<Regex>,
+    // If set, the value of the taint must also match. Taints without a value never match.
+    pub value: Option<Regex>,
+}
 
-        let mut node = node.clone();
+// Correlated failures, e.g. of a device that several node problem detectors watch, make several
+// node conditions report the same reason.
+#[derive(Debug)]
+pub struct ReasonQuorum {
+    pub reason: Regex,
+    // The minimum number of node conditions that must report the same reason that matches.
+    pub count: usize,
+}
 
-        let mut spec = node.spec.expect("node should have a spec");
-        // We deliberately unwrap_or_default to gracefully handle nodes with no taints.
-        let mut taints = spec.taints.unwrap_or_default();
+impl TaintCondition {
+    fn is_match(&self, taint: &Taint) -> bool {
+        self.key.is_match(&taint.key)
+            && self
+                .effect
+                .as_ref()
+                .is_none_or(|effect| effect.is_match(&taint.effect))
+            && self.value.as_ref().is_none_or(|value| {
+                taint
+                    .value
+                    .as_deref()
+                    .is_some_and(|taint| value.is_match(taint))
+            })
+    }
+}
 
-        for matcher in &self.matchers {
-            if !self.is_node_eligible(
-                node_name.as_ref(),
-                conditions.unwrap(),
-                matcher.conditions.as_ref(),
-            ) {
-                continue;
-            }
+// Recurring windows, e.g. approved maintenance windows, that start at the times of the cron
+// expression and last for the duration.
+pub struct Schedule {
+    pub cron: cron::Schedule,
+    pub duration: chrono::Duration,
+}
 
-            let taint = &matcher.taint;
+impl Schedule {
+    // Whether one of the windows started within the duration before the time.
+    pub fn contains(&self, time: chrono::DateTime<chrono::Utc>) -> bool {
+        self.cron
+            .after(&(time - self.duration))
+            .next()
+            .is_some_and(|start| start <= time)
+    }
+}
 
-            // Don't attempt to add the taint if the node already has it.
-            if self.node_has_taint(&taints, taint) {
-                tracing::info!(
-                    node = node_name.as_ref(),
-                    taint = self.taint_to_string(taint),
-                    "Node matches conditions but already has taint"
-                );
-                continue;
-            }
+pub struct Configuration {
+    // Human-friendly name that identifies the matcher in metrics and logs instead of its taint keys.
+    pub name: Option<String>,
+    pub enabled: bool,
+    pub mode: Mode,
+    pub conditions: Vec<Condition>,
+    // Taints that the node must already have in addition to the conditions.
+    pub taint_conditions: Vec<TaintCondition>,
+    pub taints: Vec<Taint>,
+    // Maps the value captured by a condition status' "severity" capture group to the effect of
+    // the taints. A taint's own effect is used if no severity is captured or mapped.
+    pub effect_mapping: HashMap<String, String>,
+    // Soft-preference weight that is published in the PREFER_WEIGHT_LABEL label when the matcher
+    // adds a PreferNoSchedule taint, for schedulers that read it.
+    pub weight: Option<u8>,
+    // What to do with a taint whose value has a ${label:<key>} placeholder for a label that the
+    // node does not have.
+    pub missing_label_policy: MissingLabelPolicy,
+    pub lifecycle: Lifecycle,
+    // The minimum quantities of resources that the node's capacity must have for the matcher to
+    // apply, e.g. nvidia.com/gpu: 1. A minimum of 0 only requires the resource to be present.
+    pub capacity_requires: BTreeMap<String, f64>,
+    // Whether to evict the node's pods that don't tolerate the matcher's NoExecute taints through
+    // the Eviction API before adding the taints.
+    pub graceful_evict: bool,
+    // If set, the matcher only adds taints while the current time is within one of the
+    // schedule's windows, and defers them otherwise.
+    pub schedule: Option<Schedule>,
+    // If set, the matcher only applies if enough node conditions report the same reason.
+    pub reason_quorum: Option<ReasonQuorum>,
+    // If set, the matcher only applies to nodes that are missing at least one of the labels, e.g.
+    // to taint nodes that don't comply with a labelling policy.
+    pub required_labels: Vec<String>,
+    // If set, the matcher only applies to nodes that were created at least this long ago, e.g. to
+    // leave nodes that are still bootstrapping alone. Nodes without a creation time never match.
+    pub node_age_min: Option<chrono::Duration>,
+    // If set, the matcher only applies to nodes that were created at most this long ago, e.g. to
+    // taint freshly joined nodes until they are bootstrapped.
+    pub node_age_max: Option<chrono::Duration>,
+    // If set, the matcher's taints are written with updates of their own under this field manager
+    // rather than Tainter's, so that the node's managed fields attribute them to the matcher.
+    pub field_manager: Option<String>,
+    // If set, the matcher only adds its taints when one of its matching node conditions changed
+    // its status since Tainter last observed the node, e.g. to act on nodes that just went bad
+    // while leaving nodes that have been bad for a long time to another system. Taints that the
+    // node already has are kept as long as the conditions match.
+    pub on_transition_only: bool,
+    // Matchers are evaluated in order of descending priority, and in the order in which they are
+    // configured if their priorities are equal.
+    pub priority: i32,
+    // The type patterns of the conditions compiled into a set when the matcher is first evaluated.
+    // Public only so that matchers can be built with ..Default::default().
+    pub type_set: TypeSet,
+}
 
-            let mut taint_to_add = taint.clone();
+// None if the set could not be compiled, e.g. because it exceeds the size limit. The set itself
+// is private so that it is only ever compiled from the matcher's own conditions.
+#[derive(Default)]
+pub struct TypeSet(OnceLock<Option<RegexSet>>);
 
-            // Only set time_added for NoExecute taints.
-            // See https://kubernetes.io/docs/reference/generated/kubernetes-api/v1.27/#taint-v1-core.
-            if &taint_to_add.effect == "NoExecute" {
-                let time_added = Time(Utc::now());
-                taint_to_add.time_added = Some(time_added)
-            }
+impl Configuration {
+    // Matching a node condition's type against the set matches it against the types of all of the
+    // matcher's conditions in a single pass, which prunes the node conditions that can't match a
+    // condition before its status, reason and message are matched.
+    fn type_set(&self) -> Option<&RegexSet> {
+        self.type_set
+            .0
+            .get_or_init(|| {
+                RegexSet::new(
+                    self.conditions
+                        .iter()
+                        .map(|condition| condition.type_.as_regex_string()),
+                )
+                .ok()
+            })
+            .as_ref()
+    }
+}
+
+#[derive(Default, Debug, PartialEq)]
+pub enum Lifecycle {
+    // Only add the matcher's taints to eligible nodes.
+    #[default]
+    AddOnly,
+    // Also remove the matcher's taints from nodes that are no longer eligible.
+    Managed,
+}
+
+#[derive(Default, Debug, PartialEq)]
+pub enum MissingLabelPolicy {
+    // Don't add the taint.
+    #[default]
+    Skip,
+    // Replace the placeholder with the fallback value.
+    Fallback(String),
+}
 
-            taints_to_add.push(taint_to_add)
+impl Default for Configuration {
+    fn default() -> Self {
+        Configuration {
+            name: None,
+            enabled: true,
+            mode: Mode::Taint,
+            conditions: vec![],
+            taint_conditions: vec![],
+            taints: vec![],
+            effect_mapping: HashMap::new(),
+            weight: None,
+            missing_label_policy: MissingLabelPolicy::Skip,
+            lifecycle: Lifecycle::AddOnly,
+            capacity_requires: BTreeMap::new(),
+            graceful_evict: false,
+            schedule: None,
+            reason_quorum: None,
+            required_labels: vec![],
+            node_age_min: None,
+            node_age_max: None,
+            field_manager: None,
+            on_transition_only: false,
+            priority: 0,
+            type_set: TypeSet::default(),
         }
+    }
+}
 
-        // Return immediately if we have no taints to add to the node.
-        if taints_to_add.is_empty() {
-            return;
+// node-role.kubernetes.io/master is the legacy label of control plane nodes, which older clusters
+// still use.
+const CONTROL_PLANE_ROLE_LABELS: [&str; 2] = [
+    "node-role.kubernetes.io/control-plane",
+    "node-role.kubernetes.io/master",
+];
+
+// The type of the node condition that Tainter publishes on nodes that it has tainted.
+pub const PUBLISHED_CONDITION_TYPE: &str = "TainterManaged";
+
+// Placeholders in taint values that start with this prefix are replaced by the value of the node
+// label whose key follows the prefix, e.g. ${label:topology.kubernetes.io/zone}.
+const LABEL_PLACEHOLDER_PREFIX: &str = "${label:";
+
+// Placeholder in taint values that is replaced by the time at which the taint is added. The
+// timestamp uses the basic ISO 8601 format, e.g. 20240512T112110Z, as the colons of RFC 3339 are
+// not allowed in taint values.
+const TIMESTAMP_PLACEHOLDER: &str = "${timestamp}";
+const TIMESTAMP_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+// The label that holds the weight of the PreferNoSchedule taints that Tainter adds.
+pub const PREFER_WEIGHT_LABEL: &str = "tainter.io/prefer-weight";
+
+// The annotation that matchers in report mode write instead of adding taints.
+pub const WOULD_TAINT_ANNOTATION: &str = "tainter.io/would-taint";
+
+// The annotation that lists the keys of the taints that Tainter added to a node, separated by
+// commas. Only taints listed in the annotation are ever pruned. The reconciler of a profile
+// suffixes it with the profile's name, e.g. "tainter.io/managed-taints.hardware".
+pub const MANAGED_TAINTS_ANNOTATION: &str = "tainter.io/managed-taints";
+// The annotation that lists the keys of the NoSchedule taints that Tainter added to a node before
+// evicting its pods, separated by commas. They are removed once their matchers no longer add the
+// NoExecute taints, and suffixed with the profile's name like the managed taints annotation.
+pub const EVICTING_TAINTS_ANNOTATION: &str = "tainter.io/evicting-taints";
+// The field manager of Tainter's updates unless a matcher declares its own.
+pub const DEFAULT_FIELD_MANAGER: &str = "tainter";
+
+// The annotation of mirror pods, which the kubelet creates for static pods.
+const MIRROR_POD_ANNOTATION: &str = "kubernetes.io/config.mirror";
+
+#[derive(Default, Debug, PartialEq)]
+pub enum Mode {
+    // Add the matcher's taints to eligible nodes.
+    #[default]
+    Taint,
+    // Only report the taints that would have been added via the would-taint annotation.
+    Report,
+}
+
+#[derive(Default)]
+pub struct Options {
+    // The maximum number of nodes that Tainter adds taints to in a single sweep over all nodes.
+    // A sweep happens whenever the watcher (re-)lists all nodes.
+    pub max_nodes_per_sweep: Option<usize>,
+    // If set, a sweep stops once it ran for this long, so that a sweep over a huge cluster does not
+    // hold up the watch. The remaining nodes are reconciled on their next watch event or sweep.
+    pub sweep_deadline: Option<std::time::Duration>,
+    // Cordoned nodes are often being handled by an operator, so Tainter can optionally leave them
+    // alone.
+    pub skip_cordoned_nodes: bool,
+    // Nodes that aren't Ready may be transitioning, e.g. rebooting, so Tainter can optionally
+    // defer NoSchedule and PreferNoSchedule taints until they are. NoExecute taints are exempt, as
+    // matchers add them to nodes that are unreachable and therefore not Ready by design.
+    pub require_node_ready: bool,
+    // Candidate matchers that are evaluated alongside the matchers without ever being applied.
+    // Nodes that the shadow matchers would taint differently are logged and counted, which allows
+    // rule changes to be compared before rolling them out.
+    pub shadow_matchers: Vec<Configuration>,
+    // Limits how many nodes of a group Tainter adds NoExecute taints to at once.
+    pub disruption_budgets: Vec<DisruptionBudget>,
+    // If set, taints are only added to a growing subset of the eligible nodes, e.g. to canary a
+    // remediation.
+    pub rollout: Option<Rollout>,
+    // If set, the reconciler waits a random duration of up to startup_jitter before listing nodes
+    // for the first time. Replicas that start at the same time, e.g. after a cluster upgrade,
+    // would otherwise all try to taint the same nodes at once and run into conflicts.
+    pub startup_jitter: Option<std::time::Duration>,
+    // If set, the reconciler waits this long before it processes the nodes that are listed again
+    // after the watch fell too far behind. The relist re-emits all nodes at once, which would
+    // otherwise be evaluated in a burst of updates.
+    pub relist_settle: Option<std::time::Duration>,
+    // Publish a TainterManaged node condition on nodes that Tainter adds taints to, so that other
+    // tooling can react to them.
+    pub publish_condition: bool,
+    // Remove taints that Tainter added to a node but that no matcher adds any more, e.g. because
+    // the matcher was removed from the configuration, whenever all nodes are swept.
+    pub prune_orphaned_taints: bool,
+    // If set along with prune_orphaned_taints, orphaned taints are also removed from all nodes on
+    // this interval, as nodes that never change would otherwise keep them until the next sweep.
+    pub prune_interval: Option<std::time::Duration>,
+    // Whether Tainter may replace a taint that it manages with a less severe effect.
+    pub effect_downgrade: EffectDowngrade,
+    // Limits the rate of node updates so that mass events don't overload the API server. Shared by
+    // the reconcilers of all profiles, so that the limit applies to all of Tainter's updates.
+    pub rate_limiter: Option<Arc<RateLimiter>>,
+    // If set, a node is only tainted if at least this many matchers match it, in which case the
+    // taints of all matching matchers are added.
+    pub min_matching_matchers: Option<usize>,
+    // Control plane nodes host the cluster's own components, so tainting them is rarely intended.
+    pub protect_control_plane: bool,
+    // If set, the name of the node that hosts Tainter, which Tainter never adds NoExecute taints to
+    // so that it does not evict itself, as it does not tolerate its own taints.
+    pub self_node_name: Option<String>,
+    // If set, the logs of nodes processed in response to watch events are aggregated during
+    // event storms.
+    pub log_aggregation: Option<LogAggregation>,
+    // The minimum delay after an error of the watch stream before the next event is consumed.
+    pub error_retry_delay: std::time::Duration,
+    // If set, a matcher does not taint a node if one of its matching conditions last transitioned
+    // more than this duration before the node's generation changed, as the condition may not
+    // reflect the node's current state.
+    pub stale_condition_tolerance: Option<chrono::Duration>,
+    // If set, nodes that have the same value of this label are tainted all-or-nothing during
+    // sweeps: the taints are only added if the dry runs of the updates of all nodes of the group
+    // succeed.
+    pub group_by: Option<String>,
+    // If set, a node is re-fetched and re-evaluated after taints were successfully added to it, up
+    // to this many times, so that matchers that only become eligible through the changes of other
+    // matchers apply without waiting for the next event.
+    pub cascade_max_depth: Option<usize>,
+    // If set, only nodes with these labels are reconciled, e.g. "agentpool=gpu".
+    pub node_selector: Option<String>,
+    // How nodes are written when taints are added or removed.
+    pub update_strategy: UpdateStrategy,
+    // If set, a matcher that more than this percentage of all nodes is eligible for during a sweep
+    // is suspended until the next sweep. So many nodes failing at once likely indicates a
+    // cluster-wide issue, e.g. a control plane outage that makes all nodes NotReady, rather than
+    // problems of individual nodes.
+    pub systemic_guard_percentage: Option<u8>,
+    // If set, each taint that Tainter adds or removes is posted to the webhook.
+    pub webhook: Option<Arc<Webhook>>,
+    // If set, a decision record is written for each taint that Tainter adds or removes. Shared by
+    // the reconcilers of all profiles, so that their records don't interleave.
+    pub decision_log: Option<Arc<DecisionLog>>,
+    // Whether the taints of all matchers are added in a single update of the node.
+    pub apply_strategy: ApplyStrategy,
+    // If set, matchers that come after the first matcher in taint mode that applies to a node are
+    // skipped, so that at most the taints of a single matcher are added.
+    pub short_circuit: bool,
+    // If set, the key of an annotation that records the last taints that Tainter added to a node,
+    // when and why, e.g. "not-ready:NoSchedule added at 2024-05-12T11:21:10Z because Ready=False".
+    pub last_action_annotation: Option<String>,
+    // If set, Tainter evaluates nodes but defers all updates until it observed at least this many
+    // nodes, so that a partial view of the cluster, e.g. while the cache is still warming up, does
+    // not lead to decisions such as disruption budgets being based on too few nodes.
+    pub min_nodes_before_acting: Option<usize>,
+    // If set, Tainter does not update a node again until this long after its last update, so that
+    // a flapping node's object isn't hammered with updates. Updates in between are deferred to the
+    // node's next event or sweep after the cooldown.
+    pub node_write_cooldown: Option<chrono::Duration>,
+    // If set, the name of the profile that the reconciler belongs to. Each profile tracks the
+    // taints that it manages in its own annotation, so that profiles never prune or replace each
+    // other's taints.
+    pub profile: Option<String>,
+}
+
+#[derive(Default, Debug, PartialEq)]
+pub enum ApplyStrategy {
+    // Add the taints of all matchers in a single update, which fails as a whole.
+    #[default]
+    Batch,
+    // Add the taints of each matcher in a separate update, so that a failed update only holds
+    // back the taints of one matcher at the cost of more requests.
+    PerMatcher,
+}
+
+#[derive(Default, Debug, PartialEq)]
+pub enum UpdateStrategy {
+    // Replace the whole node, which the API server rejects if the node changed since it was read.
+    #[default]
+    Replace,
+    // Patch only the node's taints, labels and annotations with a JSON patch that sets the node's
+    // resource version, which the API server rejects if the node changed since it was read.
+    JsonPatch,
+}
+
+// A taint to add to a node along with the node conditions that matched the conditions of the
+// taint's matcher.
+type TaintToAdd<'a> = (Taint, Vec<&'a NodeCondition>);
+
+// A node's generation and when Tainter observed it change.
+type ObservedGeneration = (i64, Option<chrono::DateTime<chrono::Utc>>);
+
+// The result of evaluating matchers against a node.
+#[derive(Default)]
+struct Evaluation<'a> {
+    taints_to_add: Vec<TaintToAdd<'a>>,
+    // The taints that matchers in report mode would have added.
+    would_taints: Vec<Taint>,
+    // The highest weight of the matchers that add PreferNoSchedule taints.
+    prefer_weight: Option<u8>,
+    // The taints of managed matchers whose conditions no longer match, and that no matching
+    // matcher adds.
+    taints_to_remove: Vec<Taint>,
+    // The keys and effects of the taints that matching matchers add or keep, whether or not the
+    // taints are held back, e.g. until the node is ready. A managed taint that a matching matcher
+    // keeps is never replaced by a taint with the same key but another effect.
+    kept_taints: BTreeSet<(String, String)>,
+    // The NoExecute taints of matchers that evict the node's pods gracefully before adding them.
+    graceful_evict_taints: Vec<Taint>,
+    // The taints to add grouped by the matcher that adds them, in the order of the matchers, along
+    // with the matcher's field manager.
+    matcher_taints: Vec<(Option<String>, Vec<Taint>)>,
+}
+
+// The outcome of reconciling a single node.
+#[derive(Debug, PartialEq)]
+enum Outcome {
+    // Taints were added to the node, or would have been in a dry run.
+    Tainted,
+    // No taints were added to the node, although its annotations may have been updated.
+    Unchanged,
+    // Updating the node failed.
+    Failed,
+}
+
+// Once more than threshold nodes are processed in response to watch events within a window, the
+// remaining nodes of the window are not logged individually but summarised when the window ends.
+pub struct LogAggregation {
+    pub threshold: usize,
+    pub window: std::time::Duration,
+}
+
+// Tracks the nodes processed within the current window of a LogAggregation.
+struct LogAggregator {
+    threshold: usize,
+    window: std::time::Duration,
+    state: Mutex<AggregatorState>,
+}
+
+struct AggregatorState {
+    window_started: tokio::time::Instant,
+    processed_nodes: usize,
+    aggregated_nodes: usize,
+    tainted_nodes: usize,
+}
+
+impl LogAggregator {
+    fn new(aggregation: &LogAggregation) -> Self {
+        LogAggregator {
+            threshold: aggregation.threshold,
+            window: aggregation.window,
+            state: Mutex::new(AggregatorState {
+                window_started: tokio::time::Instant::now(),
+                processed_nodes: 0,
+                aggregated_nodes: 0,
+                tainted_nodes: 0,
+            }),
         }
+    }
 
-        let taints_string = format!("{:?}", taints_to_add);
-        taints.append(taints_to_add.as_mut());
-        spec.taints = Some(taints);
-        node.spec = Some(spec);
+    // Records that a node is about to be processed, and returns whether its logs should be
+    // aggregated. The summary of a window is logged when the first node after it is processed.
+    fn aggregate(&self) -> bool {
+        let mut state = self
+            .state
+            .lock()
+            .expect("aggregator lock should not be poisoned");
 
-        let params = &PostParams {
-            dry_run: false,
-            field_manager: Some(String::from("tainter")),
-        };
-        tracing::info!(
-            node = node_name.as_ref(),
-            taints = taints_string,
-            "Adding taints to node"
-        );
-        if let Err(error) = self
-            .node_client
-            .replace(node_name.as_ref(), params, &node)
-            .await
-        {
-            let error_string = error.to_string();
-            // Conflict errors can happen when another process (perhaps another Tainter process?)
-            // modifies a node before this Tainter process can execute its update request.
-            // When this happens, Tainter will receive an HTTP 409 Conflict response.
-            // The fact that the node was modified means that Tainter will pick up another
-            // modification event and re-evaluate the node, essentially providing automatic retry.
-            if self.is_conflict_error(error_string.as_str()) {
+        let now = tokio::time::Instant::now();
+        if now.duration_since(state.window_started) >= self.window {
+            if state.aggregated_nodes > 0 {
                 tracing::info!(
-                    error = error_string,
-                    node = node_name.as_ref(),
-                    taints = taints_string,
-                    "Received conflict error when trying to add taints to node"
-                )
-            } else {
-                tracing::error!(
-                    error = error_string,
-                    node = node_name.as_ref(),
-                    taints = taints_string,
-                    "Error adding taints to node"
-                )
+                    aggregated_nodes = state.aggregated_nodes,
+                    tainted_nodes = state.tainted_nodes,
+                    window = format!("{:?}", self.window),
+                    "Summary of nodes processed without individual logs during event storm"
+                );
             }
-        } else {
-            tracing::info!(
-                node = node_name.as_ref(),
-                taints = taints_string,
-                "Successfully added taints to node"
-            )
+            state.window_started = now;
+            state.processed_nodes = 0;
+            state.aggregated_nodes = 0;
+            state.tainted_nodes = 0;
         }
-    }
 
-    fn is_conflict_error(&self, error_string: &str) -> bool {
-        error_string.contains("the object has been modified; please apply your changes to the latest version and try again")
-    }
+        state.processed_nodes += 1;
+        if state.processed_nodes <= self.threshold {
+            return false;
+        }
 
-    fn node_has_taint(&self, haystack: &Vec<Taint>, needle: &Taint) -> bool {
-        for taint in haystack {
-            if self.identical_taints(taint, needle) {
-                return true;
-            }
+        if state.aggregated_nodes == 0 {
+            tracing::warn!(
+                threshold = self.threshold,
+                window = format!("{:?}", self.window),
+                "Processing more nodes than the threshold within the window, aggregating node logs"
+            );
         }
+        state.aggregated_nodes += 1;
 
-        false
+        true
     }
 
-    // Identical taints are defined as ones that have the same key and effect.
-    // See this error from the Kubernetes API:
-    //
-    // Node "XYZ" is invalid: metadata.taints[1]: Duplicate value: core.Taint{Key:"kubernetes.azure.com/scalesetpriority", Value:"premium", Effect:"NoSchedule", TimeAdded:<nil>}: taints must be unique by key and effect pair
-    //
-    fn identical_taints(&self, this: &Taint, that: &Taint) -> bool {
-        this.key == that.key && this.effect == that.effect
+    // Records that taints were added to a node whose logs were aggregated.
+    fn record_tainted(&self) {
+        let mut state = self
+            .state
+            .lock()
+            .expect("aggregator lock should not be poisoned");
+        state.tainted_nodes += 1;
     }
+}
 
-    fn is_node_eligible(
-        &self,
-        node_name: &str,
-        have: &Vec<NodeCondition>,
-        want: &Vec<Condition>,
-    ) -> bool {
-        'search: for desired_condition in want {
-            for node_condition in have {
-                if self.conditions_match(desired_condition, node_condition) {
-                    tracing::info!(
-                        node = node_name,
-                        node_condition = format!("{:?}", node_condition).as_str(),
-                        condition = format!("{:?}", desired_condition).as_str(),
-                        "Node matches condition",
-                    );
-                    continue 'search;
-                }
-            }
+#[derive(Default, Debug, PartialEq)]
+pub enum EffectDowngrade {
+    // Keep the more severe effect. Softening e.g. NoExecute to NoSchedule could unexpectedly allow
+    // evicted pods back onto the node.
+    #[default]
+    Forbid,
+    // Replace the taint with the less severe effect.
+    Allow,
+}
 
-            // If we can't find a match for a single condition, the node is not eligible.
-            return false;
-        }
+// Inspired by PodDisruptionBudgets, a disruption budget limits the number of nodes in a group that
+// have a NoExecute taint added by Tainter at the same time.
+pub struct DisruptionBudget {
+    // The labels that a node must have to belong to the group.
+    pub selector: BTreeMap<String, String>,
+    pub max_tainted: MaxTainted,
+}
 
-        true
+#[derive(Debug, PartialEq)]
+pub enum MaxTainted {
+    // An absolute number of nodes.
+    Count(usize),
+    // A percentage of the nodes in the group, rounded up like the maxUnavailable of a pod
+    // disruption budget, so that a non-zero percentage allows at least one node of a small group.
+    Percentage(u8),
+}
+
+impl MaxTainted {
+    fn allowed(&self, group_size: usize) -> usize {
+        match self {
+            MaxTainted::Count(count) => *count,
+            MaxTainted::Percentage(percentage) => (group_size * *percentage as usize).div_ceil(100),
+        }
     }
+}
 
-    fn conditions_match(&self, this: &Condition, that: &NodeCondition) -> bool {
-        let statuses_match = this.status.is_match(that.status.as_str());
-        let types_match = this.type_.is_match(that.type_.as_str());
+// Gradually rolls out taints to a growing percentage of nodes, starting when the reconciler starts.
+// Nodes are selected by a stable hash of their name, so that a selected node stays selected as the
+// percentage grows and across restarts.
+#[derive(Debug, PartialEq)]
+pub struct Rollout {
+    // The percentage of nodes that are selected when the reconciler starts.
+    pub initial_percentage: u8,
+    // The duration over which the percentage grows linearly to 100.
+    pub ramp_duration: chrono::Duration,
+}
+
+impl Rollout {
+    // The percentage of nodes that are selected once elapsed has passed since the start.
+    fn percentage(&self, elapsed: chrono::Duration) -> f64 {
+        let initial = f64::from(self.initial_percentage.min(100));
+        if elapsed >= self.ramp_duration || self.ramp_duration <= chrono::Duration::zero() {
+            return 100.0;
+        }
+        if elapsed <= chrono::Duration::zero() {
+            return initial;
+        }
 
-        statuses_match && types_match
+        let progress =
+            elapsed.num_milliseconds() as f64 / self.ramp_duration.num_milliseconds() as f64;
+        initial + (100.0 - initial) * progress
     }
 
-    fn taint_to_string(&self, taint: &Taint) -> String {
-        let value = match taint.value.as_ref() {
-            None => String::new(),
-            Some(value) => format!("={}", value),
-        };
-        let time_added = match taint.time_added.as_ref() {
-            None => String::new(),
-            Some(time_added) => format!("/{}", time_added.0),
-        };
-        format!("{}{value}:{}{time_added}", taint.key, taint.effect)
+    // Whether the node is selected at the percentage.
+    fn selects(&self, node_name: &str, percentage: f64) -> bool {
+        (rollout_bucket(node_name) as f64) < percentage * 100.0
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use chrono::Utc;
-    use http::{Request, Response};
-    use k8s_openapi::serde_json;
-    use kube::client::Body;
-    use std::io::ErrorKind;
-    use std::path::{Path, PathBuf};
-    use std::{fs, io};
-    use tower_test::mock::Handle;
-    use tracing_test::traced_test;
+// Maps the node name to one of 10000 buckets using FNV-1a, whose output, unlike that of the
+// standard library's hasher, is stable across Rust versions.
+fn rollout_bucket(node_name: &str) -> u64 {
+    let hash = node_name
+        .bytes()
+        .fold(0xcbf29ce484222325_u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+        });
+    hash % 10_000
+}
 
-    async fn setup(
-        list_response_file: &str,
-        matchers: Vec<Configuration>,
-    ) -> Handle<Request<Body>, Response<Body>> {
-        // https://kube.rs/controllers/testing/#example.
-        let (mock_service, mut handle) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+// How adding an ad-hoc taint changed a node.
+#[derive(Debug, PartialEq)]
+pub enum AdHocChange {
+    Added,
+    // The node already has the taint.
+    Unchanged,
+    // The node has a taint with the same key and effect but another value that Tainter does not
+    // manage, which is left to its owner.
+    Unmanaged,
+}
 
-        let client = Client::new(mock_service, "default");
+// The taints that reconciling a node would add and remove.
+#[derive(Debug, Serialize)]
+pub struct NodePlan {
+    pub node: String,
+    pub add: Vec<Taint>,
+    pub remove: Vec<Taint>,
+}
 
-        let reconciler = Reconciler::new(client, matchers);
+// The number of nodes in the cluster and how many of them are eligible for each matcher.
+#[derive(Debug, Serialize)]
+pub struct SelfTestSummary {
+    pub nodes: usize,
+    pub matchers: Vec<MatcherSummary>,
+}
 
-        tokio::spawn(async move {
-            reconciler.start().await;
-        });
+#[derive(Debug, Serialize)]
+pub struct MatcherSummary {
+    pub matcher: String,
+    pub enabled: bool,
+    pub eligible: usize,
+}
 
-        let (request, response) = handle.next_request().await.expect("list nodes not called");
-        assert_eq!(request.method(), http::Method::GET);
-        assert_eq!(request.uri().to_string(), "/api/v1/nodes?&limit=500");
+impl fmt::Display for SelfTestSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Nodes: {}", self.nodes)?;
+        for summary in &self.matchers {
+            write!(
+                f,
+                "Matcher {}: {} eligible node(s)",
+                summary.matcher, summary.eligible
+            )?;
+            if !summary.enabled {
+                write!(f, " (disabled)")?;
+            }
+            writeln!(f)?;
+        }
 
-        let node_list_response_body = get_test_file(list_response_file);
+        Ok(())
+    }
+}
 
-        response.send_response(
-            Response::builder()
-                .body(Body::from(node_list_response_body.into_bytes()))
-                .unwrap(),
-        );
+// Why the reconciler's matchers do or don't apply to a node.
+#[derive(Debug, Serialize)]
+pub struct NodeExplanation {
+    pub node: String,
+    // Why the node is skipped regardless of the matchers, e.g. because it is cordoned.
+    pub skipped: Option<String>,
+    pub matchers: Vec<MatcherExplanation>,
+}
 
-        handle
+#[derive(Debug, Serialize)]
+pub struct MatcherExplanation {
+    pub matcher: String,
+    pub enabled: bool,
+    pub eligible: bool,
+    // Why the matcher does not apply to the node, empty if it does.
+    pub reasons: Vec<String>,
+}
+
+// A report on the matchers that the reconciler was configured with. Errors make the configuration
+// invalid, whereas warnings point out configuration that is valid but likely unintended.
+#[derive(Debug, Serialize)]
+pub struct ConfigReport {
+    pub valid: bool,
+    pub matchers: usize,
+    pub conditions: usize,
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+// A summary of the reconciler's runtime state for operators.
+#[derive(Debug, Serialize)]
+pub struct StatusReport {
+    // "paused" or "running".
+    pub status: &'static str,
+    // Whether the reconciler has listed and swept all nodes at least once, and observed at least
+    // min_nodes_before_acting nodes.
+    pub ready: bool,
+    pub paused: bool,
+    pub last_reconcile: Option<chrono::DateTime<chrono::Utc>>,
+    // The number of nodes that have taints that Tainter added.
+    pub tainted_nodes: usize,
+    // The number of errors of the watch stream since the reconciler started.
+    pub watch_errors: u64,
+    pub matcher_count: usize,
+    pub uptime_seconds: i64,
+}
+
+pub struct Reconciler {
+    // Used to evict pods, whereas nodes are accessed through node_client.
+    client: Client,
+    node_client: Api<Node>,
+    matchers: Vec<Configuration>,
+    options: Options,
+    metrics: Metrics,
+    clock: Box<dyn Clock>,
+    // A cache of all nodes, which disruption budgets use to count the tainted nodes of a group.
+    store: Store<Node>,
+    store_writer: Mutex<Writer<Node>>,
+    log_aggregator: Option<LogAggregator>,
+    // The generation of each node and when Tainter observed it change. The time is unknown for
+    // generations that Tainter observed when it first saw the node.
+    generations: Mutex<HashMap<String, ObservedGeneration>>,
+    // The status of each condition type of each node that Tainter last observed, which matchers
+    // that only act on transitions compare against. The history is lost when Tainter restarts.
+    condition_statuses: Mutex<HashMap<String, HashMap<String, String>>>,
+    // When Tainter last updated each node, which the node write cooldown is measured from.
+    last_writes: Mutex<HashMap<String, chrono::DateTime<chrono::Utc>>>,
+    // While paused, nodes are still evaluated but not updated.
+    paused: AtomicBool,
+    // The labels of the matchers that the systemic guard suspended during the last sweep.
+    suspended_matchers: Mutex<BTreeSet<String>>,
+    // Set once the first sweep over all nodes completed.
+    ready: AtomicBool,
+    started_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl Reconciler {
+    pub fn new(
+        client: Client,
+        matchers: Vec<Configuration>,
+        options: Options,
+        metrics: Metrics,
+        clock: Box<dyn Clock>,
+    ) -> Reconciler {
+        let (store, store_writer) = reflector::store();
+        let log_aggregator = options.log_aggregation.as_ref().map(LogAggregator::new);
+        let started_at = clock.now();
+
+        Reconciler {
+            node_client: Api::all(client.clone()),
+            client,
+            matchers,
+            options,
+            metrics,
+            clock,
+            store,
+            store_writer: Mutex::new(store_writer),
+            log_aggregator,
+            generations: Mutex::new(HashMap::new()),
+            condition_statuses: Mutex::new(HashMap::new()),
+            last_writes: Mutex::new(HashMap::new()),
+            paused: AtomicBool::new(false),
+            suspended_matchers: Mutex::new(BTreeSet::new()),
+            ready: AtomicBool::new(false),
+            started_at,
+        }
     }
 
-    #[tokio::test]
-    #[traced_test]
-    async fn test_start_checks_conditions_with_regex_and_adds_taints() {
+    pub async fn start(&self) {
+        if let Some(startup_jitter) = self.options.startup_jitter {
+            let jitter = rand::thread_rng().gen_range(std::time::Duration::ZERO..=startup_jitter);
+            tracing::info!(
+                jitter = format!("{:?}", jitter),
+                "Delaying start by a random jitter"
+            );
+            tokio::time::sleep(jitter).await;
+        }
+
+        // https://github.com/kube-rs/kube/blob/dac48d96a7b72a88fdf60857e751b122b79a3cc4/examples/node_watcher.rs.
+        let mut config = watcher::Config::default();
+        if let Some(node_selector) = &self.options.node_selector {
+            config = config.labels(node_selector);
+        }
+        tokio::join!(
+            self.run(|| watcher(self.node_client.clone(), config.clone()).default_backoff()),
+            self.prune_periodically(),
+        );
+    }
+
+    // Removes orphaned taints from all known nodes every prune_interval. Unlike sweeps, the
+    // periodic prune only ever removes taints.
+    async fn prune_periodically(&self) {
+        let Some(prune_interval) = self
+            .options
+            .prune_interval
+            .filter(|_| self.options.prune_orphaned_taints)
+        else {
+            return;
+        };
+
+        let mut interval = tokio::time::interval(prune_interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        // The first tick completes immediately, but the initial sweep prunes the nodes anyway.
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            let nodes = self.store.state();
+            tracing::info!(
+                node_count = nodes.len(),
+                "Pruning orphaned taints from all nodes"
+            );
+            for node in nodes {
+                self.prune_orphaned_taints(Node::clone(&node)).await;
+            }
+        }
+    }
+
+    // Consumes the stream returned by watch, and re-establishes the stream if it ends.
+    async fn run<S>(&self, watch: impl Fn() -> S)
+    where
+        S: Stream<Item = Result<watcher::Event<Node>, watcher::Error>>,
+    {
+        loop {
+            self.consume(watch()).await;
+            // The watcher retries errors rather than ending the stream, so this should not happen
+            // in practice. If it does, the ended stream is replaced by a new one, which starts by
+            // listing all nodes again.
+            tracing::warn!("Node watch stream ended unexpectedly, re-establishing it");
+        }
+    }
+
+    // Processes the events of the stream until it ends.
+    async fn consume<S>(&self, stream: S)
+    where
+        S: Stream<Item = Result<watcher::Event<Node>, watcher::Error>>,
+    {
+        let mut stream = pin!(stream);
+        // Whether the watch fell too far behind, so that the next restart is a forced relist.
+        let mut relist_forced = false;
+        // The nodes of the current restart, which the watcher lists one event at a time.
+        let mut relisted_nodes = vec![];
+
+        loop {
+            self.heartbeat();
+            match stream.try_next().await {
+                Ok(Some(mut event)) => {
+                    // The store identifies nodes by their name, so malformed nodes without a name
+                    // are skipped before they reach it.
+                    match &mut event {
+                        watcher::Event::Apply(node)
+                        | watcher::Event::Delete(node)
+                        | watcher::Event::InitApply(node)
+                            if node.metadata.name.is_none() =>
+                        {
+                            self.skip_nameless_node(node);
+                            continue;
+                        }
+                        _ => {}
+                    }
+
+                    self.store_writer
+                        .lock()
+                        .expect("store writer lock should not be poisoned")
+                        .apply_watcher_event(&event);
+
+                    match event {
+                        watcher::Event::Apply(node) => self.process_applied_node(node).await,
+                        // Tainter does not need to act on deleted nodes beyond forgetting their
+                        // generation.
+                        watcher::Event::Delete(node) => {
+                            if let Some(node_name) = node.metadata.name {
+                                self.generations
+                                    .lock()
+                                    .expect("generations lock should not be poisoned")
+                                    .remove(&node_name);
+                                self.condition_statuses
+                                    .lock()
+                                    .expect("condition statuses lock should not be poisoned")
+                                    .remove(&node_name);
+                                self.last_writes
+                                    .lock()
+                                    .expect("last writes lock should not be poisoned")
+                                    .remove(&node_name);
+                            }
+                        }
+                        watcher::Event::Init => relisted_nodes.clear(),
+                        watcher::Event::InitApply(node) => relisted_nodes.push(node),
+                        watcher::Event::InitDone => {
+                            let nodes = std::mem::take(&mut relisted_nodes);
+                            self.metrics.watch_restarts.inc();
+                            if relist_forced {
+                                relist_forced = false;
+                                if let Some(relist_settle) = self.options.relist_settle {
+                                    tracing::info!(
+                                        nodes = nodes.len(),
+                                        settle = format!("{:?}", relist_settle),
+                                        "Settling before processing relisted nodes"
+                                    );
+                                    tokio::time::sleep(relist_settle).await;
+                                }
+                            }
+                            self.sweep(nodes).await;
+                            self.ready.store(true, Ordering::SeqCst);
+                        }
+                    }
+                }
+                Ok(None) => return,
+                Err(error) => {
+                    self.metrics
+                        .watch_errors
+                        .with_label_values(&[self.watch_error_category(&error)])
+                        .inc();
+                    tracing::error!(error = error.to_string());
+                    if self.is_desync_error(&error) {
+                        // The watcher relists all nodes on its own, which is reported as a
+                        // restart.
+                        self.metrics.watch_relists.inc();
+                        tracing::warn!("Node watch fell too far behind, forcing a full relist");
+                        relist_forced = true;
+                    }
+                    // The watcher backs off between retries, but a stream that keeps returning
+                    // errors immediately would otherwise spin the loop.
+                    if !self.options.error_retry_delay.is_zero() {
+                        tokio::time::sleep(self.options.error_retry_delay).await;
+                    }
+                }
+            }
+        }
+    }
+
+    // Records that the reconciler is alive. Alerting on the staleness of the metric detects a
+    // wedged reconciler in a process that still serves requests.
+    fn heartbeat(&self) {
+        self.metrics
+            .last_reconcile
+            .set(self.clock.now().timestamp());
+    }
+
+    // Whether the API server no longer has the resource version that the watch resumes from, i.e.
+    // responded with 410 Gone, so that the watch must list all nodes from scratch.
+    fn is_desync_error(&self, error: &watcher::Error) -> bool {
+        match error {
+            watcher::Error::WatchError(response) => response.code == 410,
+            watcher::Error::WatchFailed(kube::Error::Api(response)) => response.code == 410,
+            _ => false,
+        }
+    }
+
+    // A coarse category of the error suitable for use as a metric label.
+    fn watch_error_category(&self, error: &watcher::Error) -> &'static str {
+        match error {
+            watcher::Error::InitialListFailed(_) => "initial_list",
+            watcher::Error::WatchStartFailed(_) => "watch_start",
+            watcher::Error::WatchError(_) => "watch",
+            watcher::Error::WatchFailed(_) => "watch_failed",
+            _ => "other",
+        }
+    }
+
+    // Stops Tainter from updating nodes until resume is called, e.g. during cluster maintenance.
+    pub fn pause(&self) {
+        tracing::info!("Pausing reconciliation");
+        self.paused.store(true, Ordering::SeqCst);
+    }
+
+    pub fn resume(&self) {
+        tracing::info!("Resuming reconciliation");
+        self.paused.store(false, Ordering::SeqCst);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::SeqCst)
+    }
+
+    // Whether the reconciler observed enough nodes to update them.
+    fn has_enough_nodes(&self) -> bool {
+        match self.options.min_nodes_before_acting {
+            Some(min_nodes) => self.store.state().len() >= min_nodes,
+            None => true,
+        }
+    }
+
+    pub fn status_report(&self) -> StatusReport {
+        let paused = self.is_paused();
+        let last_reconcile = match self.metrics.last_reconcile.get() {
+            0 => None,
+            timestamp => chrono::DateTime::from_timestamp(timestamp, 0),
+        };
+        let tainted_nodes = self
+            .store
+            .state()
+            .iter()
+            .filter(|node| !self.managed_taint_keys(&node.metadata).is_empty())
+            .count();
+
+        StatusReport {
+            status: if paused { "paused" } else { "running" },
+            ready: self.ready.load(Ordering::SeqCst) && self.has_enough_nodes(),
+            paused,
+            last_reconcile,
+            tainted_nodes,
+            watch_errors: self.metrics.watch_error_count(),
+            matcher_count: self.matchers.len(),
+            uptime_seconds: (self.clock.now() - self.started_at).num_seconds(),
+        }
+    }
+
+    // Adds the taint to the node regardless of the matchers, e.g. to cordon a node by hand. A taint
+    // with the same key and effect is only replaced if Tainter manages it, so that the taints of
+    // other controllers are left alone. Ad-hoc taints are not recorded as managed, so Tainter never
+    // removes them by itself.
+    pub async fn add_taint(
+        &self,
+        node_name: &str,
+        mut taint: Taint,
+    ) -> Result<AdHocChange, kube::Error> {
+        let mut node = self.node_client.get(node_name).await?;
+        if let Some(existing) = node_taints(&node)
+            .iter()
+            .find(|existing| existing.key == taint.key && existing.effect == taint.effect)
+        {
+            if existing.value == taint.value {
+                tracing::info!(
+                    node = node_name,
+                    key = taint.key,
+                    "Node already has ad-hoc taint"
+                );
+                return Ok(AdHocChange::Unchanged);
+            }
+            if !self.managed_taint_keys(&node.metadata).contains(&taint.key) {
+                tracing::warn!(
+                    node = node_name,
+                    key = taint.key,
+                    "Not replacing taint that Tainter does not manage with ad-hoc taint"
+                );
+                return Ok(AdHocChange::Unmanaged);
+            }
+        }
+
+        if taint.effect == "NoExecute" {
+            taint.time_added = Some(Time(self.clock.now()));
+        }
+        let key = taint.key.clone();
+        let taints = node
+            .spec
+            .get_or_insert_with(Default::default)
+            .taints
+            .get_or_insert_with(Default::default);
+        taints.retain(|existing| existing.key != taint.key || existing.effect != taint.effect);
+        taints.push(taint);
+
+        tracing::info!(node = node_name, key, "Adding ad-hoc taint to node");
+        self.write_ad_hoc_update(node_name, &node).await?;
+        tracing::info!(
+            node = node_name,
+            key,
+            "Successfully added ad-hoc taint to node"
+        );
+
+        Ok(AdHocChange::Added)
+    }
+
+    // Removes the taints with the key from the node regardless of the matchers. Returns whether the
+    // node was changed.
+    pub async fn remove_taint(&self, node_name: &str, key: &str) -> Result<bool, kube::Error> {
+        let mut node = self.node_client.get(node_name).await?;
+        if !node_taints(&node).iter().any(|taint| taint.key == key) {
+            tracing::info!(node = node_name, key, "Node does not have ad-hoc taint");
+            return Ok(false);
+        }
+
+        if let Some(taints) = node.spec.as_mut().and_then(|spec| spec.taints.as_mut()) {
+            taints.retain(|taint| taint.key != key);
+        }
+        // A managed taint that is removed by hand is no longer managed.
+        let mut managed_keys = self.managed_taint_keys(&node.metadata);
+        if managed_keys.remove(key) {
+            let annotations = node
+                .metadata
+                .annotations
+                .get_or_insert_with(Default::default);
+            if managed_keys.is_empty() {
+                annotations.remove(&self.managed_taints_annotation());
+            } else {
+                annotations.insert(
+                    self.managed_taints_annotation(),
+                    self.keys_to_string(&managed_keys),
+                );
+            }
+        }
+
+        tracing::info!(node = node_name, key, "Removing ad-hoc taint from node");
+        self.write_ad_hoc_update(node_name, &node).await?;
+        tracing::info!(
+            node = node_name,
+            key,
+            "Successfully removed ad-hoc taint from node"
+        );
+
+        Ok(true)
+    }
+
+    // Writes an ad-hoc update through the same path and field manager as the matchers' updates.
+    async fn write_ad_hoc_update(&self, node_name: &str, node: &Node) -> Result<(), kube::Error> {
+        let resource_version = node.metadata.resource_version.clone();
+        self.wait_for_rate_limit().await;
+        let updated_node = match self
+            .update_node(
+                node_name,
+                node,
+                resource_version.as_deref(),
+                DEFAULT_FIELD_MANAGER,
+                false,
+            )
+            .await
+        {
+            Ok(updated_node) => updated_node,
+            Err(error) => {
+                tracing::error!(
+                    error = error.to_string(),
+                    node = node_name,
+                    "Error writing ad-hoc taints of node"
+                );
+                return Err(error);
+            }
+        };
+        self.store_writer
+            .lock()
+            .expect("store writer lock should not be poisoned")
+            .apply_watcher_event(&watcher::Event::Apply(updated_node));
+
+        Ok(())
+    }
+
+    // Reports on the configured matchers. Settings are validated when they are loaded, but
+    // matchers that are constructed through the library API are not.
+    pub fn config_report(&self) -> ConfigReport {
+        let mut errors = vec![];
+        let mut warnings = vec![];
+
+        for (index, matcher) in self.matchers.iter().enumerate() {
+            if matcher.taints.is_empty() {
+                errors.push(format!("matcher {index} has no taints"));
+            }
+            if matcher.conditions.is_empty() {
+                errors.push(format!("matcher {index} has no conditions"));
+            }
+            for taint in &matcher.taints {
+                if is_reserved_key(&taint.key) {
+                    warnings.push(format!(
+                        "matcher {index} uses taint key {} of a reserved Kubernetes domain",
+                        taint.key
+                    ));
+                }
+            }
+            // Matchers with the same conditions that add the same taints duplicate each other
+            // whatever their names.
+            let duplicate = self.matchers[..index].iter().position(|other| {
+                other.conditions == matcher.conditions
+                    && other.taints == matcher.taints
+                    && other.effect_mapping == matcher.effect_mapping
+            });
+            if let Some(duplicate) = duplicate {
+                warnings.push(format!("matcher {index} duplicates matcher {duplicate}"));
+            }
+        }
+
+        ConfigReport {
+            valid: errors.is_empty(),
+            matchers: self.matchers.len(),
+            conditions: self
+                .matchers
+                .iter()
+                .map(|matcher| matcher.conditions.len())
+                .sum(),
+            errors,
+            warnings,
+        }
+    }
+
+    // Lists all nodes and returns the changes that reconciling them would make, without changing
+    // anything. Only nodes that would change are included.
+    pub async fn plan(&self) -> Result<Vec<NodePlan>, kube::Error> {
+        let nodes = self.node_client.list(&self.list_params()).await?;
+        // Logs emitted while planning are nested in the plan span to tell them apart from the logs
+        // of reconciling nodes.
+        let _span = tracing::info_span!("plan").entered();
+
+        let mut plans = vec![];
+        for node in nodes {
+            let Some(node_name) = node.metadata.name.clone() else {
+                continue;
+            };
+            let Some(status) = node
+                .status
+                .as_ref()
+                .filter(|status| status.conditions.is_some())
+            else {
+                continue;
+            };
+            if self.options.skip_cordoned_nodes && self.is_cordoned(&node) {
+                continue;
+            }
+            if self.options.protect_control_plane && self.is_control_plane(&node) {
+                continue;
+            }
+
+            let taints = node
+                .spec
+                .as_ref()
+                .and_then(|spec| spec.taints.clone())
+                .unwrap_or_default();
+            let evaluation = self.evaluate_matchers(
+                &self.matchers,
+                &node_name,
+                status,
+                &node.metadata,
+                &taints,
+                false,
+            );
+            let mut add = evaluation.taints_to_add;
+            if !self.options.disruption_budgets.is_empty() {
+                self.enforce_disruption_budgets(
+                    &node_name,
+                    node.metadata.labels.as_ref(),
+                    &mut add,
+                );
+            }
+            let cleared = self.managed_taints(&node.metadata, evaluation.taints_to_remove);
+            if add.is_empty() && cleared.is_empty() {
+                continue;
+            }
+
+            let add: Vec<Taint> = add.into_iter().map(|(taint, _)| taint).collect();
+            let managed_keys = self.managed_taint_keys(&node.metadata);
+            let mut remove: Vec<Taint> = taints
+                .into_iter()
+                .filter(|taint| {
+                    self.is_replaced(taint, &add, &managed_keys, &evaluation.kept_taints)
+                })
+                .collect();
+            remove.extend(cleared);
+            plans.push(NodePlan {
+                node: node_name,
+                add,
+                remove,
+            });
+        }
+
+        Ok(plans)
+    }
+
+    // Lists the nodes that the reconciler is responsible for.
+    fn list_params(&self) -> ListParams {
+        match &self.options.node_selector {
+            Some(node_selector) => ListParams::default().labels(node_selector),
+            None => ListParams::default(),
+        }
+    }
+
+    // Lists all nodes and counts how many of them are eligible for each matcher, without changing
+    // anything. Listing nodes verifies connectivity and permissions, and the counts help to verify
+    // that the matchers are configured as intended.
+    pub async fn self_test(&self) -> Result<SelfTestSummary, kube::Error> {
+        let nodes = self.node_client.list(&self.list_params()).await?;
+        let _span = tracing::info_span!("self_test").entered();
+
+        let mut eligible = vec![0; self.matchers.len()];
+        for node in nodes.iter() {
+            let node_name = node.metadata.name.as_deref().unwrap_or_default();
+            let Some(status) = node.status.as_ref() else {
+                continue;
+            };
+            let taints = node_taints(node);
+            for (index, matcher) in self.matchers.iter().enumerate() {
+                if self
+                    .matcher_conditions(
+                        node_name,
+                        matcher,
+                        status,
+                        node.metadata.labels.as_ref(),
+                        node.metadata.creation_timestamp.as_ref(),
+                        taints,
+                    )
+                    .is_some()
+                {
+                    eligible[index] += 1;
+                }
+            }
+        }
+
+        Ok(SelfTestSummary {
+            nodes: nodes.items.len(),
+            matchers: self
+                .matchers
+                .iter()
+                .zip(eligible)
+                .map(|(matcher, eligible)| MatcherSummary {
+                    matcher: self.matcher_label(matcher),
+                    enabled: matcher.enabled,
+                    eligible,
+                })
+                .collect(),
+        })
+    }
+
+    // Explains for each matcher whether the node is eligible for it and, if it is not, why, e.g.
+    // which of the matcher's conditions no node condition matches.
+    pub async fn explain(&self, node_name: &str) -> Result<NodeExplanation, kube::Error> {
+        let node = self.node_client.get(node_name).await?;
+        let _span = tracing::info_span!("explain").entered();
+
+        let skipped = if self.options.skip_cordoned_nodes && self.is_cordoned(&node) {
+            Some("node is cordoned".to_string())
+        } else if self.options.protect_control_plane && self.is_control_plane(&node) {
+            Some("node is a control plane node".to_string())
+        } else if node.status.is_none() {
+            Some("node has no status".to_string())
+        } else {
+            None
+        };
+        let status = node.status.clone().unwrap_or_default();
+        let taints = node_taints(&node);
+        let matchers = self
+            .matchers
+            .iter()
+            .map(|matcher| {
+                self.explain_matcher(
+                    node_name,
+                    matcher,
+                    &status,
+                    node.metadata.labels.as_ref(),
+                    node.metadata.creation_timestamp.as_ref(),
+                    taints,
+                )
+            })
+            .collect();
+
+        Ok(NodeExplanation {
+            node: node_name.to_string(),
+            skipped,
+            matchers,
+        })
+    }
+
+    // An instrumented version of matcher_conditions that records why the node is not eligible for
+    // the matcher rather than stopping at the first reason.
+    fn explain_matcher(
+        &self,
+        node_name: &str,
+        matcher: &Configuration,
+        status: &NodeStatus,
+        labels: Option<&BTreeMap<String, String>>,
+        created: Option<&Time>,
+        taints: &[Taint],
+    ) -> MatcherExplanation {
+        let mut reasons = vec![];
+        if !matcher.enabled {
+            reasons.push("matcher is disabled".to_string());
+        }
+        if self.is_suspended(matcher) {
+            reasons.push("matcher is suspended by the systemic guard".to_string());
+        }
+
+        let node_conditions = status.conditions.as_deref().unwrap_or_default();
+        for condition in &matcher.conditions {
+            if !node_conditions
+                .iter()
+                .any(|node_condition| self.conditions_match(condition, node_condition))
+            {
+                reasons.push(self.explain_condition(condition, node_conditions));
+            }
+        }
+        if !self.required_label_missing(node_name, matcher, labels) {
+            reasons.push("node has all of the matcher's required labels".to_string());
+        }
+        if !self.node_age_matches(node_name, matcher, created) {
+            reasons.push("node age is outside of the matcher's range".to_string());
+        }
+        if !self.taint_conditions_match(node_name, matcher, taints) {
+            reasons.push("node does not have a taint for each of the taint conditions".to_string());
+        }
+        if !self.capacity_matches(node_name, matcher, status) {
+            reasons.push("node capacity is below the matcher's requirements".to_string());
+        }
+        if !self.reason_quorum_matches(node_name, matcher, node_conditions) {
+            reasons.push("node conditions do not reach the reason quorum".to_string());
+        }
+        if let Some(schedule) = &matcher.schedule {
+            if !schedule.contains(self.clock.now()) {
+                reasons.push("current time is outside of the matcher's schedule".to_string());
+            }
+        }
+        // The stale condition tolerance and on_transition_only apply to the node conditions that
+        // the matcher's conditions match, as recorded from the node's previous events.
+        if let Some(matched_conditions) =
+            self.matching_conditions(node_name, node_conditions, matcher)
+        {
+            if let Some(stale_condition) =
+                self.stale_condition(&matched_conditions, self.generation_changed_at(node_name))
+            {
+                reasons.push(format!(
+                    "condition type {} transitioned before the node's generation changed",
+                    stale_condition.type_
+                ));
+            }
+            if matcher.on_transition_only
+                && !self.conditions_transitioned(node_name, &matched_conditions)
+            {
+                reasons.push(
+                    "matcher only adds taints when its conditions transition, which they did not"
+                        .to_string(),
+                );
+            }
+        }
+
+        MatcherExplanation {
+            matcher: self.matcher_label(matcher),
+            enabled: matcher.enabled,
+            eligible: reasons.is_empty(),
+            reasons,
+        }
+    }
+
+    // Explains why none of the node conditions match the condition, e.g. "condition type Ready
+    // matched but status True does not match False|Unknown".
+    fn explain_condition(
+        &self,
+        condition: &Condition,
+        node_conditions: &[NodeCondition],
+    ) -> String {
+        let explanations: Vec<String> = node_conditions
+            .iter()
+            .filter_map(|node_condition| {
+                let detail = match self.condition_mismatch(condition, node_condition)? {
+                    Mismatch::Type => return None,
+                    Mismatch::Status => match &condition.status {
+                        Status::Pattern(pattern) => {
+                            format!(
+                                "status {} does not match {}",
+                                node_condition.status, pattern
+                            )
+                        }
+                        Status::NotPattern(pattern) => {
+                            format!(
+                                "status {} matches excluded {}",
+                                node_condition.status, pattern
+                            )
+                        }
+                        Status::In(statuses) => format!(
+                            "status {} is not one of {}",
+                            node_condition.status,
+                            statuses.join(", ")
+                        ),
+                    },
+                    Mismatch::Reason => format!(
+                        "reason {} does not match {}",
+                        node_condition.reason.as_deref().unwrap_or("<none>"),
+                        condition
+                            .reason
+                            .as_ref()
+                            .map(Regex::as_str)
+                            .unwrap_or_default()
+                    ),
+                    Mismatch::Message => format!(
+                        "message {} does not match {}",
+                        node_condition.message.as_deref().unwrap_or("<none>"),
+                        condition
+                            .message
+                            .as_ref()
+                            .map(Regex::as_str)
+                            .unwrap_or_default()
+                    ),
+                    Mismatch::Age => format!(
+                        "it did not transition within {}s",
+                        condition.max_age.unwrap_or_default().num_seconds()
+                    ),
+                    Mismatch::Heartbeat => match &condition.heartbeat {
+                        Some(Heartbeat::Stale(max_age)) => {
+                            format!("its heartbeat is not older than {}s", max_age.num_seconds())
+                        }
+                        Some(Heartbeat::Fresh(max_age)) => {
+                            format!("its heartbeat is older than {}s", max_age.num_seconds())
+                        }
+                        None => "its heartbeat does not match".to_string(),
+                    },
+                };
+                Some(format!(
+                    "condition type {} matched but {}",
+                    node_condition.type_, detail
+                ))
+            })
+            .collect();
+        if explanations.is_empty() {
+            return format!(
+                "no node condition has a type that matches {}",
+                condition.type_
+            );
+        }
+
+        explanations.join("; ")
+    }
+
+    // A sweep processes all nodes in the cluster, which happens when the watcher starts and
+    // whenever it has to re-list nodes.
+    // Frequent watch reconnects make sweeps common, so the per-node processing logs of a sweep are
+    // emitted at debug level and the sweep is summarised by a single log instead.
+    async fn sweep(&self, nodes: Vec<Node>) {
+        let node_count = nodes.len();
+        // The watcher consumes all pages of the list response before emitting the nodes, so the
+        // count covers all nodes in the cluster regardless of the page size.
+        tracing::info!(node_count, "Listed all nodes, starting sweep");
+        self.guard_systemic_matchers(&nodes);
+        let mut tainted_nodes: usize = 0;
+        let mut skipped_nodes: usize = 0;
+        let mut processed_nodes: usize = 0;
+        let deadline = self
+            .options
+            .sweep_deadline
+            .map(|sweep_deadline| tokio::time::Instant::now() + sweep_deadline);
+
+        for (group, nodes) in self.group_nodes(nodes) {
+            // Groups are tainted all-or-nothing, so the deadline is only checked between groups.
+            if deadline.is_some_and(|deadline| tokio::time::Instant::now() >= deadline) {
+                skipped_nodes = node_count - processed_nodes;
+                tracing::warn!(
+                    sweep_deadline =
+                        format!("{:?}", self.options.sweep_deadline.unwrap_or_default()),
+                    processed_nodes,
+                    skipped_nodes,
+                    "Reached the sweep deadline, skipping the remaining nodes until the next sweep"
+                );
+                break;
+            }
+            if let Some(max_nodes_per_sweep) = self.options.max_nodes_per_sweep {
+                if tainted_nodes >= max_nodes_per_sweep {
+                    skipped_nodes = node_count - processed_nodes;
+                    tracing::warn!(
+                        max_nodes_per_sweep,
+                        skipped_nodes,
+                        "Reached the maximum number of nodes to taint in a single sweep, skipping \
+                        the remaining nodes until the next sweep"
+                    );
+                    break;
+                }
+            }
+            processed_nodes += nodes.len();
+
+            let mut pruned_nodes = Vec::with_capacity(nodes.len());
+            for node in nodes {
+                if self.options.prune_orphaned_taints {
+                    pruned_nodes.push(self.prune_orphaned_taints(node).await);
+                } else {
+                    pruned_nodes.push(node);
+                }
+            }
+
+            match group {
+                Some(group) => tainted_nodes += self.process_group(&group, pruned_nodes).await,
+                None => {
+                    for node in pruned_nodes {
+                        if self.process_node(node, true).await {
+                            tainted_nodes += 1;
+                        }
+                    }
+                }
+            }
+        }
+
+        tracing::info!(node_count, tainted_nodes, skipped_nodes, "Finished sweep");
+    }
+
+    // Suspends the matchers that more than systemic_guard_percentage of the nodes are eligible for
+    // until the next sweep.
+    fn guard_systemic_matchers(&self, nodes: &[Node]) {
+        let Some(percentage) = self.options.systemic_guard_percentage else {
+            return;
+        };
+        let _span = tracing::info_span!("systemic_guard").entered();
+
+        let mut suspended = BTreeSet::new();
+        for matcher in self.matchers.iter().filter(|matcher| matcher.enabled) {
+            let eligible_nodes = nodes
+                .iter()
+                .filter(|node| {
+                    let node_name = node.metadata.name.as_deref().unwrap_or_default();
+                    node.status.as_ref().is_some_and(|status| {
+                        self.matcher_conditions(
+                            node_name,
+                            matcher,
+                            status,
+                            node.metadata.labels.as_ref(),
+                            node.metadata.creation_timestamp.as_ref(),
+                            node_taints(node),
+                        )
+                        .is_some()
+                    })
+                })
+                .count();
+            if eligible_nodes * 100 > nodes.len() * percentage as usize {
+                let label = self.matcher_label(matcher);
+                tracing::warn!(
+                    matcher = label,
+                    eligible_nodes,
+                    node_count = nodes.len(),
+                    percentage,
+                    "Suspending matcher until the next sweep because too many nodes are eligible \
+                    for it at once, which likely indicates a cluster-wide issue rather than \
+                    problems of individual nodes"
+                );
+                suspended.insert(label);
+            }
+        }
+
+        *self
+            .suspended_matchers
+            .lock()
+            .expect("suspended matchers lock should not be poisoned") = suspended;
+    }
+
+    // Splits the nodes into the groups that are tainted all-or-nothing, keyed by the value of the
+    // group_by label, in the order of the first node of each group. Nodes without the label are
+    // not grouped and are returned one by one.
+    fn group_nodes(&self, nodes: Vec<Node>) -> Vec<(Option<String>, Vec<Node>)> {
+        let mut groups: Vec<(Option<String>, Vec<Node>)> = vec![];
+
+        for node in nodes {
+            let group = self.options.group_by.as_ref().and_then(|group_by| {
+                node.metadata
+                    .labels
+                    .as_ref()
+                    .and_then(|labels| labels.get(group_by))
+                    .cloned()
+            });
+            let existing = group.as_ref().and_then(|group| {
+                groups
+                    .iter_mut()
+                    .find(|(existing, _)| existing.as_ref() == Some(group))
+            });
+            match existing {
+                Some((_, members)) => members.push(node),
+                None => groups.push((group, vec![node])),
+            }
+        }
+
+        groups
+    }
+
+    // Taints the nodes of a group only if the dry runs of the updates of all of its nodes succeed.
+    // This is best-effort: a node may still change between the dry run and the actual update, in
+    // which case the group is left partially tainted until the next sweep. Returns the number of
+    // nodes that taints were added to.
+    async fn process_group(&self, group: &str, nodes: Vec<Node>) -> usize {
+        for node in &nodes {
+            if self.reconcile_node(node.clone(), true, true).await == Outcome::Failed {
+                tracing::warn!(
+                    group,
+                    node = node.metadata.name.as_deref().unwrap_or_default(),
+                    nodes = nodes.len(),
+                    "Skipping group because the dry run of one of its nodes failed"
+                );
+                return 0;
+            }
+        }
+
+        let mut tainted_nodes = 0;
+        for node in nodes {
+            if self.process_node(node, true).await {
+                tainted_nodes += 1;
+            }
+        }
+
+        tainted_nodes
+    }
+
+    // Processes a node in response to a watch event. During event storms the node is not logged
+    // individually if log aggregation is configured.
+    async fn process_applied_node(&self, node: Node) {
+        let Some(log_aggregator) = &self.log_aggregator else {
+            self.process_node(node, false).await;
+            return;
+        };
+
+        let aggregate = log_aggregator.aggregate();
+        if self.process_node(node, aggregate).await && aggregate {
+            log_aggregator.record_tainted();
+        }
+    }
+
+    // Returns whether taints were successfully added to the node.
+    // summarised is true when the node's processing is covered by a summary log, i.e. when it is
+    // processed as part of a sweep over all nodes or during an event storm.
+    async fn process_node(&self, node: Node, summarised: bool) -> bool {
+        // Nodes without a name are skipped by reconcile_node, so they are never re-fetched.
+        let node_name = node.metadata.name.clone().unwrap_or_default();
+        let mut tainted = self.reconcile_node(node, summarised, false).await == Outcome::Tainted;
+        let mut cascade_tainted = tainted;
+        let mut depth = 0;
+        while cascade_tainted && depth < self.options.cascade_max_depth.unwrap_or_default() {
+            depth += 1;
+            let node = match self.node_client.get(&node_name).await {
+                Ok(node) => node,
+                Err(error) => {
+                    tracing::warn!(
+                        error = error.to_string(),
+                        node = node_name,
+                        depth,
+                        "Error re-fetching node to re-evaluate it after adding taints"
+                    );
+                    break;
+                }
+            };
+            tracing::info!(
+                node = node_name,
+                depth,
+                "Re-evaluating node after adding taints"
+            );
+            cascade_tainted =
+                self.reconcile_node(node, summarised, false).await == Outcome::Tainted;
+            tainted |= cascade_tainted;
+        }
+
+        tainted
+    }
+
+    // If dry_run is true, the node's update is only validated by the API server without
+    // persisting it.
+    // All logs emitted while processing a node share the reconcile_node span, which makes it easy
+    // to correlate the logs of a single node.
+    #[tracing::instrument(
+        name = "reconcile_node",
+        skip_all,
+        fields(node = node.metadata.name.as_deref().unwrap_or_default())
+    )]
+    async fn reconcile_node(&self, node: Node, summarised: bool, dry_run: bool) -> Outcome {
+        self.heartbeat();
+        // Malformed nodes are skipped rather than panicking, which would end the watch loop.
+        let Some(node_name) = node.name() else {
+            self.skip_nameless_node(&node);
+            return Outcome::Unchanged;
+        };
+        // The resource version and generation help correlate the version of the node that Tainter
+        // read with the version that the API server had when debugging conflict errors.
+        let resource_version = node.metadata.resource_version.clone();
+        let generation = node.metadata.generation;
+        if summarised {
+            tracing::debug!(
+                node_name = node_name.as_ref(),
+                resource_version = resource_version.as_deref(),
+                generation,
+                "Processing node"
+            );
+        } else {
+            tracing::info!(
+                node_name = node_name.as_ref(),
+                resource_version = resource_version.as_deref(),
+                generation,
+                "Processing node"
+            );
+        }
+
+        if self.options.skip_cordoned_nodes && self.is_cordoned(&node) {
+            tracing::info!(node = node_name.as_ref(), "Skipping cordoned node");
+            return Outcome::Unchanged;
+        }
+
+        if self.options.protect_control_plane && self.is_control_plane(&node) {
+            tracing::info!(node = node_name.as_ref(), "Skipping control plane node");
+            return Outcome::Unchanged;
+        }
+
+        let Some(status) = node.status.as_ref() else {
+            tracing::warn!(node = node_name.as_ref(), "Skipping node without a status");
+            self.metrics
+                .malformed_nodes
+                .with_label_values(&["no_status"])
+                .inc();
+            return Outcome::Unchanged;
+        };
+        let conditions = status.conditions.as_ref();
+
+        // If a node has no conditions, then we cannot determine whether it's eligible.
+        // I'm unsure if this can happen in practice.
+        if conditions.is_none() {
+            return Outcome::Unchanged;
+        }
+
+        let mut node = node.clone();
+
+        let Some(spec) = node.spec.as_ref() else {
+            tracing::warn!(node = node_name.as_ref(), "Skipping node without a spec");
+            self.metrics
+                .malformed_nodes
+                .with_label_values(&["no_spec"])
+                .inc();
+            return Outcome::Unchanged;
+        };
+        // We deliberately unwrap_or_default to gracefully handle nodes with no taints.
+        let taints = spec.taints.clone().unwrap_or_default();
+
+        self.observe_generation(node_name.as_ref(), generation);
+        let Evaluation {
+            mut taints_to_add,
+            would_taints,
+            prefer_weight,
+            taints_to_remove,
+            kept_taints,
+            mut graceful_evict_taints,
+            mut matcher_taints,
+        } = self.evaluate_matchers(
+            &self.matchers,
+            node_name.as_ref(),
+            status,
+            &node.metadata,
+            &taints,
+            !dry_run,
+        );
+        let taints_to_remove = self.managed_taints(&node.metadata, taints_to_remove);
+
+        if !dry_run && !self.options.shadow_matchers.is_empty() {
+            self.compare_shadow_matchers(
+                node_name.as_ref(),
+                status,
+                &node.metadata,
+                &taints,
+                &taints_to_add,
+            );
+        }
+        // The statuses are only recorded once the evaluated taints landed, so that a transition that
+        // budgets, the rollout or a failed update held back is still detected on the next event.
+        let evaluated_taints: Vec<Taint> = taints_to_add
+            .iter()
+            .map(|(taint, _)| taint.clone())
+            .collect();
+        if !dry_run && evaluated_taints.is_empty() {
+            self.observe_condition_statuses(node_name.as_ref(), status);
+        }
+
+        if !self.options.disruption_budgets.is_empty() {
+            self.enforce_disruption_budgets(
+                node_name.as_ref(),
+                node.metadata.labels.as_ref(),
+                &mut taints_to_add,
+            );
+        }
+
+        if let Some(rollout) = &self.options.rollout {
+            let percentage = rollout.percentage(self.clock.now() - self.started_at);
+            if !taints_to_add.is_empty() && !rollout.selects(node_name.as_ref(), percentage) {
+                tracing::info!(
+                    node = node_name.as_ref(),
+                    triggers = self.triggers_to_string(&taints_to_add),
+                    rollout_percentage = format!("{:.1}", percentage),
+                    "Holding back taints of node that the rollout has not selected yet"
+                );
+                taints_to_add.clear();
+            }
+        }
+
+        let would_taint_annotation = self.would_taint_annotation(&node.metadata, &would_taints);
+
+        // Return immediately if we have nothing to change on the node.
+        if taints_to_add.is_empty()
+            && taints_to_remove.is_empty()
+            && would_taint_annotation.is_none()
+        {
+            return Outcome::Unchanged;
+        }
+
+        let removed_taints = self.taints_to_string(&taints_to_remove);
+        if self.is_paused() {
+            tracing::info!(
+                node = node_name.as_ref(),
+                triggers = self.triggers_to_string(&taints_to_add),
+                removed_taints,
+                would_taint = would_taint_annotation.as_deref(),
+                "Skipping update of node because reconciliation is paused"
+            );
+            return Outcome::Unchanged;
+        }
+        if !self.has_enough_nodes() {
+            tracing::info!(
+                node = node_name.as_ref(),
+                triggers = self.triggers_to_string(&taints_to_add),
+                removed_taints,
+                node_count = self.store.state().len(),
+                min_nodes_before_acting = self.options.min_nodes_before_acting,
+                "Deferring update of node until enough nodes are observed"
+            );
+            return Outcome::Unchanged;
+        }
+        // Dry runs don't write the node, so the cooldown does not hold them back.
+        if let Some(remaining) = self.write_cooldown(node_name.as_ref()).filter(|_| !dry_run) {
+            tracing::info!(
+                node = node_name.as_ref(),
+                triggers = self.triggers_to_string(&taints_to_add),
+                removed_taints,
+                remaining = format!("{}s", remaining.num_seconds()),
+                "Deferring update of node until its write cooldown elapses"
+            );
+            return Outcome::Unchanged;
+        }
+
+        // Disruption budgets may have held back taints, whose pods are then not evicted either.
+        graceful_evict_taints.retain(|taint| {
+            taints_to_add
+                .iter()
+                .any(|(added, _)| self.identical_taints(added, taint))
+        });
+        // Pods are evicted gracefully in steps, each of which is triggered by the node's next event:
+        // a NoSchedule taint with the same key as each NoExecute taint is added first, so that the
+        // evicted pods are not scheduled straight back onto the node, then the pods are evicted, and
+        // the NoExecute taints are only added once no pod is left to evict.
+        // The keys of the NoSchedule taints that are added before evicting the node's pods.
+        let mut evicting_keys: Vec<String> = vec![];
+        if !dry_run && !graceful_evict_taints.is_empty() {
+            let cordoned = graceful_evict_taints
+                .iter()
+                .all(|taint| self.node_has_taint(&taints, &no_schedule(taint)));
+            if !cordoned {
+                tracing::info!(
+                    node = node_name.as_ref(),
+                    taints = self.taints_to_string(&graceful_evict_taints),
+                    "Adding NoSchedule taints to node before evicting its pods"
+                );
+                // The NoSchedule taints are also swapped in for the taints of their matchers, so
+                // that they are written with the field managers of the matchers.
+                let added_taints = taints_to_add.iter_mut().map(|(taint, _)| taint);
+                let matcher_taints = matcher_taints.iter_mut().flat_map(|(_, taints)| taints);
+                for taint in added_taints.chain(matcher_taints) {
+                    if self.node_has_taint(&graceful_evict_taints, taint) {
+                        *taint = no_schedule(taint);
+                    }
+                }
+                evicting_keys = graceful_evict_taints
+                    .iter()
+                    .map(|taint| taint.key.clone())
+                    .collect();
+            } else if !self
+                .evict_pods(node_name.as_ref(), &graceful_evict_taints)
+                .await
+            {
+                tracing::info!(
+                    node = node_name.as_ref(),
+                    taints = self.taints_to_string(&graceful_evict_taints),
+                    "Deferring NoExecute taints until the node's pods are evicted"
+                );
+                taints_to_add
+                    .retain(|(taint, _)| !self.node_has_taint(&graceful_evict_taints, taint));
+                if taints_to_add.is_empty()
+                    && taints_to_remove.is_empty()
+                    && would_taint_annotation.is_none()
+                {
+                    return Outcome::Unchanged;
+                }
+            }
+        }
+
+        // Matchers with their own field manager are always written with updates of their own, so
+        // that the managed fields attribute their taints to them.
+        let per_matcher = self.options.apply_strategy == ApplyStrategy::PerMatcher
+            || matcher_taints
+                .iter()
+                .any(|(field_manager, _)| field_manager.is_some());
+        let written_taints: Vec<Taint> = taints_to_add
+            .iter()
+            .map(|(taint, _)| taint.clone())
+            .collect();
+        self.annotate_evicting_taints(
+            &mut node.metadata,
+            &taints,
+            &written_taints,
+            &taints_to_remove,
+            &kept_taints,
+            evicting_keys,
+        );
+        let batches = if per_matcher {
+            self.batches_per_matcher(taints_to_add, &matcher_taints)
+        } else {
+            vec![(None, taints_to_add)]
+        };
+        // Removals, the weight label and the would-taint annotation are written with the first
+        // update. Each further update starts from the node that the previous update returned.
+        let mut taints_to_remove = taints_to_remove;
+        let mut prefer_weight = prefer_weight;
+        let mut would_taint_annotation = would_taint_annotation;
+        // The last action annotation records the taints of all updates of the node, so each update
+        // writes it with the taints that the previous updates added along with its own.
+        let mut added_taints: Vec<TaintToAdd> = vec![];
+        let mut outcome = Outcome::Unchanged;
+        for (field_manager, batch) in batches {
+            let adds_taints = !batch.is_empty();
+            let mut actions = added_taints.clone();
+            actions.extend(batch.iter().cloned());
+            match self
+                .update_taints(
+                    node.clone(),
+                    batch,
+                    std::mem::take(&mut taints_to_remove),
+                    prefer_weight.take(),
+                    would_taint_annotation.take(),
+                    self.last_action(&actions),
+                    &kept_taints,
+                    field_manager.as_deref().unwrap_or(DEFAULT_FIELD_MANAGER),
+                    dry_run,
+                )
+                .await
+            {
+                Some(updated_node) => {
+                    node = updated_node;
+                    added_taints = actions;
+                    if !dry_run {
+                        self.record_write(node_name.as_ref());
+                    }
+                    if adds_taints && outcome == Outcome::Unchanged {
+                        outcome = Outcome::Tainted;
+                    }
+                }
+                None => outcome = Outcome::Failed,
+            }
+        }
+
+        if !dry_run
+            && outcome != Outcome::Failed
+            && !evaluated_taints.is_empty()
+            && evaluated_taints
+                .iter()
+                .all(|taint| self.node_has_taint(&written_taints, taint))
+        {
+            self.observe_condition_statuses(node_name.as_ref(), status);
+        }
+
+        outcome
+    }
+
+    // Records the keys of the NoSchedule taints that are added before evicting the node's pods in
+    // the node's annotation, and drops the keys of those that the update removes or replaces. The
+    // annotation is written with the first update of the node.
+    fn annotate_evicting_taints(
+        &self,
+        metadata: &mut ObjectMeta,
+        taints: &[Taint],
+        taints_to_add: &[Taint],
+        taints_to_remove: &[Taint],
+        kept_taints: &BTreeSet<(String, String)>,
+        evicting_keys: Vec<String>,
+    ) {
+        let previous_keys = self.evicting_taint_keys(metadata);
+        let managed_keys = self.managed_taint_keys(metadata);
+        let mut keys = previous_keys.clone();
+        keys.retain(|key| {
+            taints.iter().any(|taint| {
+                &taint.key == key
+                    && taint.effect == "NoSchedule"
+                    && !self.node_has_taint(taints_to_remove, taint)
+                    && !self.is_replaced(taint, taints_to_add, &managed_keys, kept_taints)
+            })
+        });
+        keys.extend(evicting_keys);
+        if keys == previous_keys {
+            return;
+        }
+
+        let annotations = metadata.annotations.get_or_insert_with(Default::default);
+        if keys.is_empty() {
+            annotations.remove(&self.evicting_taints_annotation());
+        } else {
+            annotations.insert(
+                self.evicting_taints_annotation(),
+                self.keys_to_string(&keys),
+            );
+        }
+    }
+
+    // Splits the taints to add into one batch per matcher, in the order of the matchers, along with
+    // the matcher's field manager. There is always at least one batch, which may be empty if only
+    // taints are removed.
+    fn batches_per_matcher<'a>(
+        &self,
+        taints_to_add: Vec<TaintToAdd<'a>>,
+        matcher_taints: &[(Option<String>, Vec<Taint>)],
+    ) -> Vec<(Option<String>, Vec<TaintToAdd<'a>>)> {
+        let mut batches: BTreeMap<usize, Vec<TaintToAdd>> = BTreeMap::new();
+        for taint_to_add in taints_to_add {
+            let matcher = matcher_taints
+                .iter()
+                .position(|(_, taints)| self.node_has_taint(taints, &taint_to_add.0))
+                .unwrap_or_default();
+            batches.entry(matcher).or_default().push(taint_to_add);
+        }
+
+        let mut batches: Vec<(Option<String>, Vec<TaintToAdd>)> = batches
+            .into_iter()
+            .map(|(matcher, batch)| {
+                let field_manager = matcher_taints
+                    .get(matcher)
+                    .and_then(|(field_manager, _)| field_manager.clone());
+                (field_manager, batch)
+            })
+            .collect();
+        if batches.is_empty() {
+            batches.push((None, vec![]));
+        }
+        batches
+    }
+
+    // Adds and removes the taints in a single update of the node. Returns the updated node, or
+    // None if the update failed.
+    #[allow(clippy::too_many_arguments)]
+    async fn update_taints(
+        &self,
+        mut node: Node,
+        taints_to_add: Vec<TaintToAdd<'_>>,
+        taints_to_remove: Vec<Taint>,
+        prefer_weight: Option<u8>,
+        would_taint_annotation: Option<String>,
+        last_action: Option<String>,
+        kept_taints: &BTreeSet<(String, String)>,
+        field_manager: &str,
+        dry_run: bool,
+    ) -> Option<Node> {
+        let node_name = node.metadata.name.clone().unwrap_or_default();
+        let resource_version = node.metadata.resource_version.clone();
+        let generation = node.metadata.generation;
+        let mut spec = node.spec.take().unwrap_or_default();
+        let mut taints = spec.taints.take().unwrap_or_default();
+        let adds_taints = !taints_to_add.is_empty();
+        let removes_taints = !taints_to_remove.is_empty();
+        let removed_taints = self.taints_to_string(&taints_to_remove);
+        let triggers = self.triggers_to_string(&taints_to_add);
+        // Managed taints with the key of an added taint are replaced unless a matching matcher
+        // keeps them, which is reported as their removal.
+        let managed_keys = self.managed_taint_keys(&node.metadata);
+        let new_taints: Vec<Taint> = taints_to_add
+            .iter()
+            .map(|(taint, _)| taint.clone())
+            .collect();
+        let replaced_taints: Vec<Taint> = taints
+            .iter()
+            .filter(|taint| self.is_replaced(taint, &new_taints, &managed_keys, kept_taints))
+            .cloned()
+            .collect();
+        // The events are built before the taints are moved into the node, but only sent once the
+        // node was updated.
+        let mut webhook_events = self.webhook_events(
+            node_name.as_str(),
+            &taints_to_add,
+            &taints_to_remove,
+            "The conditions of the taint's managed matcher cleared",
+        );
+        let mut decision_records = self.decision_records(
+            node_name.as_str(),
+            &taints_to_add,
+            &taints_to_remove,
+            "The conditions of the taint's managed matcher cleared",
+        );
+        for taint in &replaced_taints {
+            let reason = self.replacement_reason(taint, &new_taints);
+            let replaced = std::slice::from_ref(taint);
+            webhook_events.extend(self.webhook_events(node_name.as_str(), &[], replaced, &reason));
+            decision_records.extend(self.decision_records(
+                node_name.as_str(),
+                &[],
+                replaced,
+                &reason,
+            ));
+        }
+        let mut taints_to_add: Vec<Taint> =
+            taints_to_add.into_iter().map(|(taint, _)| taint).collect();
+        let taints_string = format!("{:?}", taints_to_add);
+        let added_taints = self.taints_to_string(&taints_to_add);
+        let taints_to_add_keys: Vec<String> = taints_to_add
+            .iter()
+            .map(|taint| taint.key.clone())
+            .collect();
+        taints.retain(|taint| !self.node_has_taint(&replaced_taints, taint));
+        taints.retain(|taint| !self.node_has_taint(&taints_to_remove, taint));
+        taints.append(taints_to_add.as_mut());
+        spec.taints = Some(taints);
+        node.spec = Some(spec);
+
+        if let Some(prefer_weight) = prefer_weight {
+            tracing::info!(
+                node = node_name.as_str(),
+                weight = prefer_weight,
+                "Labelling node with the weight of its PreferNoSchedule taints"
+            );
+            node.metadata
+                .labels
+                .get_or_insert_with(Default::default)
+                .insert(PREFER_WEIGHT_LABEL.to_string(), prefer_weight.to_string());
+        }
+
+        if adds_taints || removes_taints {
+            let mut managed_keys = self.managed_taint_keys(&node.metadata);
+            for taint in &taints_to_remove {
+                managed_keys.remove(&taint.key);
+            }
+            managed_keys.extend(taints_to_add_keys);
+            let annotations = node
+                .metadata
+                .annotations
+                .get_or_insert_with(Default::default);
+            if managed_keys.is_empty() {
+                annotations.remove(&self.managed_taints_annotation());
+            } else {
+                annotations.insert(
+                    self.managed_taints_annotation(),
+                    self.keys_to_string(&managed_keys),
+                );
+            }
+        }
+
+        if let (Some(key), Some(last_action)) = (&self.options.last_action_annotation, last_action)
+        {
+            // Overwritten on each action, so it only ever records the last one. An update that
+            // removes taints without adding any keeps the annotation.
+            node.metadata
+                .annotations
+                .get_or_insert_with(Default::default)
+                .insert(key.clone(), last_action);
+        }
+
+        let would_taint_changed = would_taint_annotation.is_some();
+        if let Some(would_taint_annotation) = would_taint_annotation {
+            tracing::info!(
+                node = node_name.as_str(),
+                would_taint = would_taint_annotation,
+                "Annotating node with taints that report mode matchers would add"
+            );
+            node.metadata
+                .annotations
+                .get_or_insert_with(Default::default)
+                .insert(WOULD_TAINT_ANNOTATION.to_string(), would_taint_annotation);
+        }
+
+        if adds_taints && !dry_run {
+            tracing::info!(
+                node = node_name.as_str(),
+                taints = taints_string,
+                triggers,
+                "Adding taints to node"
+            );
+        }
+        if removes_taints && !dry_run {
+            tracing::info!(
+                node = node_name.as_str(),
+                taints = removed_taints,
+                "Removing taints from node because the conditions of their managed matchers cleared"
+            );
+        }
+        self.wait_for_rate_limit().await;
+        let updated_node = match self
+            .update_node(
+                node_name.as_str(),
+                &node,
+                resource_version.as_deref(),
+                field_manager,
+                dry_run,
+            )
+            .await
+        {
+            Ok(updated_node) => updated_node,
+            Err(error) => {
+                let error_string = error.to_string();
+                // Conflict errors can happen when another process (perhaps another Tainter process?)
+                // modifies a node before this Tainter process can execute its update request.
+                // When this happens, Tainter will receive an HTTP 409 Conflict response.
+                // The fact that the node was modified means that Tainter will pick up another
+                // modification event and re-evaluate the node, essentially providing automatic retry.
+                if dry_run {
+                    tracing::warn!(
+                        error = error_string,
+                        node = node_name.as_str(),
+                        taints = taints_string,
+                        "Dry run of adding taints to node failed"
+                    )
+                } else if self.is_conflict_error(error_string.as_str()) {
+                    tracing::info!(
+                        error = error_string,
+                        node = node_name.as_str(),
+                        resource_version = resource_version.as_deref(),
+                        generation,
+                        taints = taints_string,
+                        "Received conflict error when trying to add taints to node"
+                    )
+                } else {
+                    tracing::error!(
+                        error = error_string,
+                        node = node_name.as_str(),
+                        taints = taints_string,
+                        "Error adding taints to node"
+                    )
+                }
+
+                return None;
+            }
+        };
+
+        if dry_run {
+            return Some(updated_node);
+        }
+
+        // The watcher only sees the update after the current sweep, so the store is updated right
+        // away for disruption budgets to account for nodes tainted earlier in the same sweep.
+        self.store_writer
+            .lock()
+            .expect("store writer lock should not be poisoned")
+            .apply_watcher_event(&watcher::Event::Apply(updated_node.clone()));
+        self.send_webhook_events(webhook_events);
+        self.write_decision_records(&decision_records);
+
+        if removes_taints {
+            tracing::info!(
+                node = node_name.as_str(),
+                taints = removed_taints,
+                "Successfully removed taints from node"
+            );
+        }
+
+        if adds_taints {
+            tracing::info!(
+                node = node_name.as_str(),
+                taints = taints_string,
+                "Successfully added taints to node"
+            );
+
+            if self.options.publish_condition {
+                let conditions = node
+                    .status
+                    .as_ref()
+                    .and_then(|status| status.conditions.as_ref());
+                if let Some(conditions) = conditions {
+                    self.publish_condition(node_name.as_str(), conditions, &added_taints)
+                        .await;
+                }
+            }
+        } else if would_taint_changed {
+            tracing::info!(node = node_name.as_str(), "Successfully annotated node");
+        }
+
+        Some(updated_node)
+    }
+
+    // Writes the node's taints, labels and annotations with the configured update strategy.
+    async fn update_node(
+        &self,
+        node_name: &str,
+        node: &Node,
+        resource_version: Option<&str>,
+        field_manager: &str,
+        dry_run: bool,
+    ) -> Result<Node, kube::Error> {
+        match self.options.update_strategy {
+            UpdateStrategy::Replace => {
+                let params = PostParams {
+                    dry_run,
+                    field_manager: Some(field_manager.to_string()),
+                };
+                self.node_client.replace(node_name, &params, node).await
+            }
+            UpdateStrategy::JsonPatch => {
+                let params = PatchParams {
+                    dry_run,
+                    field_manager: Some(field_manager.to_string()),
+                    ..Default::default()
+                };
+                let patch = json_patch(node, resource_version);
+                self.node_client
+                    .patch(node_name, &params, &Patch::Json::<()>(patch))
+                    .await
+            }
+        }
+    }
+
+    // Evicts the pods on the node that don't tolerate the taints through the Eviction API before
+    // the taints are added. Unlike the taints' abrupt evictions, the Eviction API respects pod
+    // disruption budgets. Returns whether no pod is left to evict, in which case the taints can be
+    // added. Pods that could not be evicted yet, e.g. because of a pod disruption budget, are
+    // evicted again on the node's next event.
+    async fn evict_pods(&self, node_name: &str, taints: &[Taint]) -> bool {
+        let pod_client: Api<Pod> = Api::all(self.client.clone());
+        let params = ListParams::default().fields(&format!("spec.nodeName={node_name}"));
+        let pods = match pod_client.list(&params).await {
+            Ok(pods) => pods,
+            Err(error) => {
+                tracing::warn!(
+                    error = error.to_string(),
+                    node = node_name,
+                    "Error listing pods of node to evict them before adding taints"
+                );
+                return false;
+            }
+        };
+
+        // Evicted pods are listed until they have terminated, so the taints wait for them.
+        let mut evicted = true;
+        for pod in pods.iter().filter(|pod| is_evictable(pod, taints)) {
+            evicted = false;
+            let (Some(namespace), Some(pod_name)) = (
+                pod.metadata.namespace.as_deref(),
+                pod.metadata.name.as_deref(),
+            ) else {
+                continue;
+            };
+            let pod_client: Api<Pod> = Api::namespaced(self.client.clone(), namespace);
+            match pod_client.evict(pod_name, &EvictParams::default()).await {
+                Ok(_) => tracing::info!(
+                    node = node_name,
+                    namespace,
+                    pod = pod_name,
+                    "Evicted pod from node before adding taints"
+                ),
+                Err(error) => tracing::warn!(
+                    error = error.to_string(),
+                    node = node_name,
+                    namespace,
+                    pod = pod_name,
+                    "Error evicting pod from node, retrying on the node's next event"
+                ),
+            }
+        }
+
+        evicted
+    }
+
+    // Removes the taints that Tainter added to the node but that no matcher adds any more. Returns
+    // the node to continue processing, which is the updated node if taints were removed.
+    async fn prune_orphaned_taints(&self, node: Node) -> Node {
+        let managed_keys = self.managed_taint_keys(&node.metadata);
+        let orphaned_keys: BTreeSet<String> = managed_keys
+            .iter()
+            .filter(|key| !self.is_matcher_key(key))
+            .cloned()
+            .collect();
+        if orphaned_keys.is_empty() {
+            return node;
+        }
+        if self.is_paused() {
+            tracing::info!(
+                node = node.metadata.name.as_deref().unwrap_or_default(),
+                keys = self.keys_to_string(&orphaned_keys),
+                "Skipping removal of orphaned taints because reconciliation is paused"
+            );
+            return node;
+        }
+        if !self.has_enough_nodes() {
+            tracing::info!(
+                node = node.metadata.name.as_deref().unwrap_or_default(),
+                keys = self.keys_to_string(&orphaned_keys),
+                node_count = self.store.state().len(),
+                min_nodes_before_acting = self.options.min_nodes_before_acting,
+                "Deferring removal of orphaned taints until enough nodes are observed"
+            );
+            return node;
+        }
+        if let Some(remaining) =
+            self.write_cooldown(node.metadata.name.as_deref().unwrap_or_default())
+        {
+            tracing::info!(
+                node = node.metadata.name.as_deref().unwrap_or_default(),
+                keys = self.keys_to_string(&orphaned_keys),
+                remaining = format!("{}s", remaining.num_seconds()),
+                "Deferring removal of orphaned taints until the node's write cooldown elapses"
+            );
+            return node;
+        }
+
+        let Some(node_name) = node.metadata.name.clone() else {
+            return node;
+        };
+        let orphaned_taints: Vec<Taint> = node
+            .spec
+            .as_ref()
+            .and_then(|spec| spec.taints.as_ref())
+            .map(|taints| {
+                taints
+                    .iter()
+                    .filter(|taint| orphaned_keys.contains(&taint.key))
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default();
+        let mut pruned_node = node.clone();
+        if let Some(taints) = pruned_node
+            .spec
+            .as_mut()
+            .and_then(|spec| spec.taints.as_mut())
+        {
+            taints.retain(|taint| !orphaned_keys.contains(&taint.key));
+        }
+        let remaining_keys: BTreeSet<String> =
+            managed_keys.difference(&orphaned_keys).cloned().collect();
+        let annotations = pruned_node
+            .metadata
+            .annotations
+            .get_or_insert_with(Default::default);
+        if remaining_keys.is_empty() {
+            annotations.remove(&self.managed_taints_annotation());
+        } else {
+            annotations.insert(
+                self.managed_taints_annotation(),
+                self.keys_to_string(&remaining_keys),
+            );
+        }
+
+        let keys = self.keys_to_string(&orphaned_keys);
+        tracing::info!(
+            node = node_name,
+            keys,
+            "Removing orphaned taints that no matcher adds from node"
+        );
+        self.wait_for_rate_limit().await;
+        match self
+            .update_node(
+                &node_name,
+                &pruned_node,
+                node.metadata.resource_version.as_deref(),
+                DEFAULT_FIELD_MANAGER,
+                false,
+            )
+            .await
+        {
+            Ok(updated_node) => {
+                tracing::info!(
+                    node = node_name,
+                    keys,
+                    "Successfully removed orphaned taints from node"
+                );
+                self.record_write(&node_name);
+                self.send_webhook_events(self.webhook_events(
+                    &node_name,
+                    &[],
+                    &orphaned_taints,
+                    "No matcher adds the taint any more",
+                ));
+                self.write_decision_records(&self.decision_records(
+                    &node_name,
+                    &[],
+                    &orphaned_taints,
+                    "No matcher adds the taint any more",
+                ));
+                self.store_writer
+                    .lock()
+                    .expect("store writer lock should not be poisoned")
+                    .apply_watcher_event(&watcher::Event::Apply(updated_node.clone()));
+
+                updated_node
+            }
+            Err(error) => {
+                tracing::error!(
+                    error = error.to_string(),
+                    node = node_name,
+                    keys,
+                    "Error removing orphaned taints from node"
+                );
+
+                node
+            }
+        }
+    }
+
+    // Returns the taints whose keys are listed in the node's managed taints annotation. Tainter only
+    // removes taints that it added itself.
+    fn managed_taints(&self, metadata: &ObjectMeta, taints: Vec<Taint>) -> Vec<Taint> {
+        let managed_keys = self.managed_taint_keys(metadata);
+        taints
+            .into_iter()
+            .filter(|taint| managed_keys.contains(&taint.key))
+            .collect()
+    }
+
+    // Returns the annotation that lists the keys of the taints that the reconciler manages.
+    fn managed_taints_annotation(&self) -> String {
+        match &self.options.profile {
+            Some(profile) => format!("{MANAGED_TAINTS_ANNOTATION}.{profile}"),
+            None => MANAGED_TAINTS_ANNOTATION.to_string(),
+        }
+    }
+
+    // Returns the annotation that lists the keys of the NoSchedule taints that the reconciler added
+    // before evicting the pods of a node.
+    fn evicting_taints_annotation(&self) -> String {
+        match &self.options.profile {
+            Some(profile) => format!("{EVICTING_TAINTS_ANNOTATION}.{profile}"),
+            None => EVICTING_TAINTS_ANNOTATION.to_string(),
+        }
+    }
+
+    // Returns the keys of the taints that Tainter added to the node.
+    fn managed_taint_keys(&self, metadata: &ObjectMeta) -> BTreeSet<String> {
+        self.annotation_keys(metadata, &self.managed_taints_annotation())
+    }
+
+    // Returns the keys of the NoSchedule taints that Tainter added to the node before evicting its
+    // pods.
+    fn evicting_taint_keys(&self, metadata: &ObjectMeta) -> BTreeSet<String> {
+        self.annotation_keys(metadata, &self.evicting_taints_annotation())
+    }
+
+    fn annotation_keys(&self, metadata: &ObjectMeta, annotation: &str) -> BTreeSet<String> {
+        metadata
+            .annotations
+            .as_ref()
+            .and_then(|annotations| annotations.get(annotation))
+            .map(|keys| {
+                keys.split(',')
+                    .filter(|key| !key.is_empty())
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    // Whether any matcher, including disabled ones, adds a taint with the key. Keys of matchers
+    // with templates match any key that the template can render to.
+    fn is_matcher_key(&self, key: &str) -> bool {
+        self.matchers
+            .iter()
+            .flat_map(|matcher| &matcher.taints)
+            .any(|taint| key_matches_template(key, &taint.key))
+    }
+
+    fn keys_to_string(&self, keys: &BTreeSet<String>) -> String {
+        keys.iter().cloned().collect::<Vec<String>>().join(",")
+    }
+
+    fn skip_nameless_node(&self, node: &Node) {
+        tracing::warn!(
+            uid = node.metadata.uid.as_deref(),
+            "Skipping node without a name"
+        );
+        self.metrics
+            .malformed_nodes
+            .with_label_values(&["no_name"])
+            .inc();
+    }
+
+    async fn wait_for_rate_limit(&self) {
+        if let Some(rate_limiter) = &self.options.rate_limiter {
+            rate_limiter.acquire().await;
+        }
+    }
+
+    // Patches the node's status with a condition that records the taints that Tainter added. The
+    // strategic merge patch merges conditions by type, so publishing the condition again updates
+    // the existing condition rather than adding a duplicate.
+    async fn publish_condition(
+        &self,
+        node_name: &str,
+        conditions: &[NodeCondition],
+        added_taints: &str,
+    ) {
+        let now = Time(self.clock.now());
+        // The transition time only changes when the status of the condition does.
+        let last_transition_time = conditions
+            .iter()
+            .find(|condition| {
+                condition.type_ == PUBLISHED_CONDITION_TYPE && condition.status == "True"
+            })
+            .and_then(|condition| condition.last_transition_time.clone())
+            .unwrap_or_else(|| now.clone());
+        let condition = NodeCondition {
+            last_heartbeat_time: Some(now),
+            last_transition_time: Some(last_transition_time),
+            message: Some(format!("Tainter added taints {}", added_taints)),
+            reason: Some("TaintsAdded".to_string()),
+            status: "True".to_string(),
+            type_: PUBLISHED_CONDITION_TYPE.to_string(),
+        };
+        let patch = json!({ "status": { "conditions": [condition] } });
+        let params = PatchParams {
+            field_manager: Some(DEFAULT_FIELD_MANAGER.to_string()),
+            ..Default::default()
+        };
+
+        self.wait_for_rate_limit().await;
+        match self
+            .node_client
+            .patch_status(node_name, &params, &Patch::Strategic(patch))
+            .await
+        {
+            Ok(_) => tracing::info!(
+                node = node_name,
+                condition = PUBLISHED_CONDITION_TYPE,
+                "Successfully published node condition"
+            ),
+            Err(error) => tracing::error!(
+                error = error.to_string(),
+                node = node_name,
+                condition = PUBLISHED_CONDITION_TYPE,
+                "Error publishing node condition"
+            ),
+        }
+    }
+
+    // Removes the NoExecute taints from the taints to add if the node belongs to a group whose
+    // disruption budget is exhausted. The taints are added once the budget allows it, e.g. after
+    // the taints of another node in the group have been removed.
+    fn enforce_disruption_budgets(
+        &self,
+        node_name: &str,
+        labels: Option<&BTreeMap<String, String>>,
+        taints_to_add: &mut Vec<TaintToAdd>,
+    ) {
+        if !taints_to_add
+            .iter()
+            .any(|(taint, _)| taint.effect == "NoExecute")
+        {
+            return;
+        }
+
+        let nodes = self.store.state();
+
+        for budget in &self.options.disruption_budgets {
+            if !self.selector_matches(&budget.selector, labels) {
+                continue;
+            }
+
+            let group: Vec<_> = nodes
+                .iter()
+                .filter(|node| {
+                    self.selector_matches(&budget.selector, node.metadata.labels.as_ref())
+                })
+                .collect();
+            let tainted_nodes = group
+                .iter()
+                .filter(|node| node.metadata.name.as_deref() != Some(node_name))
+                .filter(|node| self.is_disrupted(node))
+                .count();
+            let max_tainted_nodes = budget.max_tainted.allowed(group.len());
+
+            if tainted_nodes >= max_tainted_nodes {
+                tracing::info!(
+                    node = node_name,
+                    selector = self.selector_to_string(&budget.selector),
+                    tainted_nodes,
+                    max_tainted_nodes,
+                    "Deferring NoExecute taints because the disruption budget of the node's group \
+                    is exhausted"
+                );
+                taints_to_add.retain(|(taint, _)| taint.effect != "NoExecute");
+                return;
+            }
+        }
+    }
+
+    // A node is disrupted if it has a NoExecute taint with a key that one of the matchers adds,
+    // including keys that a matcher rendered from a template.
+    fn is_disrupted(&self, node: &Node) -> bool {
+        let taints = node.spec.as_ref().and_then(|spec| spec.taints.as_ref());
+
+        taints.is_some_and(|taints| {
+            taints
+                .iter()
+                .any(|taint| taint.effect == "NoExecute" && self.is_matcher_key(&taint.key))
+        })
+    }
+
+    fn selector_matches(
+        &self,
+        selector: &BTreeMap<String, String>,
+        labels: Option<&BTreeMap<String, String>>,
+    ) -> bool {
+        selector
+            .iter()
+            .all(|(key, value)| labels.and_then(|labels| labels.get(key)) == Some(value))
+    }
+
+    fn selector_to_string(&self, selector: &BTreeMap<String, String>) -> String {
+        selector
+            .iter()
+            .map(|(key, value)| format!("{key}={value}"))
+            .collect::<Vec<String>>()
+            .join(",")
+    }
+
+    // count_eligibility controls whether the per-matcher metrics are updated, which they are not
+    // for shadow matchers, plans and dry runs.
+    fn evaluate_matchers<'a>(
+        &self,
+        matchers: &[Configuration],
+        node_name: &str,
+        status: &'a NodeStatus,
+        metadata: &ObjectMeta,
+        taints: &[Taint],
+        count_eligibility: bool,
+    ) -> Evaluation<'a> {
+        let labels = metadata.labels.as_ref();
+        let created = metadata.creation_timestamp.as_ref();
+        // Only taints that Tainter added are replaced, so that a taint of another controller with
+        // the same key is left alone.
+        let managed_keys = self.managed_taint_keys(metadata);
+        let mut taints_to_add: Vec<TaintToAdd> = vec![];
+        // Taints that matchers in report mode would have added.
+        let mut would_taints: Vec<Taint> = vec![];
+        let mut graceful_evict_taints: Vec<Taint> = vec![];
+        let mut matching_matchers = 0;
+        let mut prefer_weight: Option<u8> = None;
+        let mut matcher_taints: Vec<(Option<String>, Vec<Taint>)> = vec![];
+        let generation_changed_at = self.generation_changed_at(node_name);
+        let node_ready = is_node_ready(status);
+        // Managed matchers whose conditions no longer match, and the keys of the taints that
+        // matching matchers add or keep. A taint that a matching matcher keeps is never removed,
+        // so that matchers don't remove and add the same taint in turn.
+        let mut cleared_matchers: Vec<&Configuration> = vec![];
+        let mut kept_keys: BTreeSet<String> = BTreeSet::new();
+        let mut kept_taints: BTreeSet<(String, String)> = BTreeSet::new();
+        // The matcher that short-circuits the evaluation of the remaining matchers.
+        let mut applied_matcher: Option<String> = None;
+
+        // The sort is stable, so matchers of equal priority keep their configured order.
+        let mut matchers: Vec<&Configuration> = matchers.iter().collect();
+        matchers.sort_by_key(|matcher| std::cmp::Reverse(matcher.priority));
+
+        for matcher in matchers {
+            if !matcher.enabled {
+                tracing::debug!(
+                    node = node_name,
+                    matcher = self.matcher_label(matcher),
+                    taints = self.taints_to_string(&matcher.taints),
+                    "Skipping disabled matcher"
+                );
+                continue;
+            }
+
+            if self.is_suspended(matcher) {
+                tracing::debug!(
+                    node = node_name,
+                    matcher = self.matcher_label(matcher),
+                    "Skipping matcher suspended by the systemic guard"
+                );
+                continue;
+            }
+
+            let matched_conditions =
+                self.matcher_conditions(node_name, matcher, status, labels, created, taints);
+
+            if count_eligibility {
+                let label = self.matcher_label(matcher);
+                let counter = match matched_conditions {
+                    Some(_) => &self.metrics.matcher_eligible,
+                    None => &self.metrics.matcher_ineligible,
+                };
+                counter.with_label_values(&[label.as_str()]).inc();
+            }
+
+            let Some(matched_conditions) = matched_conditions else {
+                if matcher.lifecycle == Lifecycle::Managed && matcher.mode == Mode::Taint {
+                    cleared_matchers.push(matcher);
+                }
+                continue;
+            };
+
+            if let Some(stale_condition) =
+                self.stale_condition(&matched_conditions, generation_changed_at)
+            {
+                tracing::info!(
+                    node = node_name,
+                    matcher = self.matcher_label(matcher),
+                    node_condition = format!("{:?}", stale_condition).as_str(),
+                    "Skipping matcher because its condition transitioned before the node's \
+                    generation changed"
+                );
+                continue;
+            }
+            if let Some(schedule) = &matcher.schedule {
+                if !schedule.contains(self.clock.now()) {
+                    tracing::info!(
+                        node = node_name,
+                        matcher = self.matcher_label(matcher),
+                        "Deferring matcher until its schedule's next window"
+                    );
+                    continue;
+                }
+            }
+            if let Some(applied_matcher) = &applied_matcher {
+                tracing::info!(
+                    node = node_name,
+                    matcher = self.matcher_label(matcher),
+                    applied_matcher,
+                    "Skipping matcher because a matcher of higher precedence applies"
+                );
+                continue;
+            }
+            matching_matchers += 1;
+
+            let mapped_effect = self.mapped_effect(matcher, &matched_conditions);
+            let captures = self.named_captures(matcher, &matched_conditions);
+            let mut added_taints: Vec<Taint> = vec![];
+
+            for taint in &matcher.taints {
+                let mut taint_to_add = taint.clone();
+                if let Some(effect) = mapped_effect {
+                    taint_to_add.effect = effect.clone();
+                }
+                if !captures.is_empty() {
+                    taint_to_add.key = self.render_template(&taint_to_add.key, &captures);
+                    taint_to_add.value = taint_to_add
+                        .value
+                        .map(|value| self.render_template(&value, &captures));
+                }
+                let fallback = match &matcher.missing_label_policy {
+                    MissingLabelPolicy::Skip => None,
+                    MissingLabelPolicy::Fallback(fallback) => Some(fallback.as_str()),
+                };
+                match self.render_key(&taint_to_add.key, labels, fallback, taints) {
+                    Ok(key) if is_valid_taint_key(&key) => taint_to_add.key = key,
+                    Ok(key) => {
+                        tracing::warn!(
+                            node = node_name,
+                            key,
+                            template = taint_to_add.key,
+                            "Skipping taint because its rendered key is not a valid taint key"
+                        );
+                        continue;
+                    }
+                    Err(label) => {
+                        tracing::info!(
+                            node = node_name,
+                            taint = self.taint_to_string(&taint_to_add),
+                            label,
+                            "Skipping taint because the node does not have the label of its key"
+                        );
+                        continue;
+                    }
+                }
+                if let Some(value) = &taint_to_add.value {
+                    match self
+                        .render_labels(value, labels, fallback)
+                        .map(|value| self.render_timestamp(&value))
+                    {
+                        Ok(value) => taint_to_add.value = Some(value),
+                        Err(label) => {
+                            tracing::info!(
+                                node = node_name,
+                                taint = self.taint_to_string(&taint_to_add),
+                                label,
+                                "Skipping taint because the node does not have the label of its value"
+                            );
+                            continue;
+                        }
+                    }
+                }
+
+                if matcher.mode == Mode::Taint {
+                    kept_keys.insert(taint_to_add.key.clone());
+                    kept_taints.insert((taint_to_add.key.clone(), taint_to_add.effect.clone()));
+                }
+
+                // Don't attempt to add the taint if the node already has it.
+                if self.node_has_taint(taints, &taint_to_add) {
+                    tracing::info!(
+                        node = node_name,
+                        taint = self.taint_to_string(&taint_to_add),
+                        "Node matches conditions but already has taint"
+                    );
+                    if count_eligibility {
+                        self.metrics
+                            .taints_already_present
+                            .with_label_values(&[self.matcher_label(matcher).as_str()])
+                            .inc();
+                    }
+                    continue;
+                }
+
+                if matcher.on_transition_only
+                    && !self.conditions_transitioned(node_name, &matched_conditions)
+                {
+                    tracing::info!(
+                        node = node_name,
+                        matcher = self.matcher_label(matcher),
+                        taint = self.taint_to_string(&taint_to_add),
+                        "Skipping taint because the matcher's conditions did not transition"
+                    );
+                    continue;
+                }
+
+                if taint_to_add.effect == "NoExecute"
+                    && self.options.self_node_name.as_deref() == Some(node_name)
+                {
+                    tracing::info!(
+                        node = node_name,
+                        taint = self.taint_to_string(&taint_to_add),
+                        "Skipping NoExecute taint because the node hosts Tainter"
+                    );
+                    continue;
+                }
+
+                if self.options.require_node_ready
+                    && !node_ready
+                    && taint_to_add.effect != "NoExecute"
+                {
+                    tracing::info!(
+                        node = node_name,
+                        taint = self.taint_to_string(&taint_to_add),
+                        "Deferring taint until the node is ready"
+                    );
+                    continue;
+                }
+
+                if matcher.mode == Mode::Report {
+                    if !self.node_has_taint(&would_taints, &taint_to_add) {
+                        would_taints.push(taint_to_add);
+                    }
+                    continue;
+                }
+
+                // Another matcher may already be adding the same taint.
+                if taints_to_add
+                    .iter()
+                    .any(|(added, _)| self.identical_taints(added, &taint_to_add))
+                {
+                    continue;
+                }
+
+                if taint_to_add.effect == "PreferNoSchedule" {
+                    if let Some(weight) = matcher.weight {
+                        prefer_weight = prefer_weight.max(Some(weight));
+                    }
+                }
+
+                // Only set time_added for NoExecute taints.
+                // See https://kubernetes.io/docs/reference/generated/kubernetes-api/v1.27/#taint-v1-core.
+                // time_added is set exactly once when the taint is added. Tainter never updates a
+                // taint that the node already has, so time_added is stable for as long as the taint
+                // persists. A taint that is removed and later re-added gets a fresh time_added.
+                if &taint_to_add.effect == "NoExecute" {
+                    let time_added = Time(self.clock.now());
+                    taint_to_add.time_added = Some(time_added)
+                }
+
+                if matcher.graceful_evict && taint_to_add.effect == "NoExecute" {
+                    graceful_evict_taints.push(taint_to_add.clone());
+                }
+
+                added_taints.push(taint_to_add.clone());
+                taints_to_add.push((taint_to_add, matched_conditions.clone()))
+            }
+
+            if !added_taints.is_empty() {
+                matcher_taints.push((matcher.field_manager.clone(), added_taints));
+            }
+
+            if self.options.short_circuit && matcher.mode == Mode::Taint {
+                applied_matcher = Some(self.matcher_label(matcher));
+            }
+        }
+
+        // A managed taint with the same key but another effect is replaced when a taint is added,
+        // unless a matching matcher keeps it. Which taints the matchers keep is only known once all
+        // matchers are evaluated. Upgrades to a more severe effect are always allowed.
+        if self.options.effect_downgrade == EffectDowngrade::Forbid {
+            let is_downgrade = |taint_to_add: &Taint| {
+                let Some(existing) =
+                    self.downgraded_taint(taint_to_add, taints, &managed_keys, &kept_taints)
+                else {
+                    return false;
+                };
+                tracing::info!(
+                    node = node_name,
+                    taint = self.taint_to_string(existing),
+                    effect = taint_to_add.effect,
+                    "Skipping downgrade of taint effect"
+                );
+                true
+            };
+            taints_to_add.retain(|(taint, _)| !is_downgrade(taint));
+            would_taints.retain(|taint| !is_downgrade(taint));
+            for (_, added_taints) in matcher_taints.iter_mut() {
+                added_taints.retain(|taint| {
+                    taints_to_add
+                        .iter()
+                        .any(|(added, _)| self.identical_taints(added, taint))
+                });
+            }
+            matcher_taints.retain(|(_, added_taints)| !added_taints.is_empty());
+            if !taints_to_add
+                .iter()
+                .any(|(taint, _)| taint.effect == "PreferNoSchedule")
+            {
+                prefer_weight = None;
+            }
+        }
+
+        let mut taints_to_remove: Vec<Taint> = taints
+            .iter()
+            .filter(|taint| !kept_keys.contains(&taint.key))
+            .filter(|taint| {
+                cleared_matchers
+                    .iter()
+                    .flat_map(|matcher| &matcher.taints)
+                    .any(|matcher_taint| key_matches_template(&taint.key, &matcher_taint.key))
+            })
+            .cloned()
+            .collect();
+        // The NoSchedule taints added before evicting the node's pods are removed once no matching
+        // matcher adds the NoExecute taints, whatever the lifecycle of their matchers, e.g. if the
+        // conditions cleared before the pods were evicted.
+        let evicting_keys = self.evicting_taint_keys(metadata);
+        let orphaned_taints: Vec<Taint> = taints
+            .iter()
+            .filter(|taint| taint.effect == "NoSchedule" && evicting_keys.contains(&taint.key))
+            .filter(|taint| {
+                ["NoSchedule", "NoExecute"]
+                    .iter()
+                    .all(|effect| !kept_taints.contains(&(taint.key.clone(), effect.to_string())))
+            })
+            .filter(|taint| !self.node_has_taint(&taints_to_remove, taint))
+            .cloned()
+            .collect();
+        taints_to_remove.extend(orphaned_taints);
+
+        if let Some(min_matching_matchers) = self.options.min_matching_matchers {
+            if matching_matchers < min_matching_matchers
+                && (!taints_to_add.is_empty() || !would_taints.is_empty())
+            {
+                tracing::info!(
+                    node = node_name,
+                    matching_matchers,
+                    min_matching_matchers,
+                    "Not tainting node because too few matchers match it"
+                );
+                return Evaluation {
+                    taints_to_remove,
+                    kept_taints,
+                    ..Default::default()
+                };
+            }
+        }
+
+        Evaluation {
+            taints_to_add,
+            would_taints,
+            prefer_weight,
+            taints_to_remove,
+            kept_taints,
+            graceful_evict_taints,
+            matcher_taints,
+        }
+    }
+
+    // Evaluates the shadow matchers against the node without applying their taints, and reports
+    // if they would taint the node differently than the matchers.
+    fn compare_shadow_matchers(
+        &self,
+        node_name: &str,
+        status: &NodeStatus,
+        metadata: &ObjectMeta,
+        taints: &[Taint],
+        taints_to_add: &[TaintToAdd],
+    ) {
+        // Logs emitted while evaluating the shadow matchers are nested in the shadow span to tell
+        // them apart from the logs of the matchers.
+        let span = tracing::info_span!("shadow").entered();
+        let shadow_evaluation = self.evaluate_matchers(
+            &self.options.shadow_matchers,
+            node_name,
+            status,
+            metadata,
+            taints,
+            false,
+        );
+        span.exit();
+
+        let taints_to_add: Vec<Taint> = taints_to_add
+            .iter()
+            .map(|(taint, _)| taint.clone())
+            .collect();
+        let shadow_taints_to_add: Vec<Taint> = shadow_evaluation
+            .taints_to_add
+            .into_iter()
+            .map(|(taint, _)| taint)
+            .collect();
+        let diverges = shadow_taints_to_add.len() != taints_to_add.len()
+            || shadow_taints_to_add
+                .iter()
+                .any(|taint| !self.node_has_taint(&taints_to_add, taint));
+        if !diverges {
+            return;
+        }
+
+        self.metrics.shadow_divergences.inc();
+        tracing::info!(
+            node = node_name,
+            taints = self.taints_to_string(&taints_to_add),
+            shadow_taints = self.taints_to_string(&shadow_taints_to_add),
+            "Shadow matchers would taint node differently"
+        );
+    }
+
+    // Records the node's generation, and when Tainter observed it change if it differs from the
+    // previously observed generation.
+    fn observe_generation(&self, node_name: &str, generation: Option<i64>) {
+        let (Some(_), Some(generation)) = (self.options.stale_condition_tolerance, generation)
+        else {
+            return;
+        };
+        let mut generations = self
+            .generations
+            .lock()
+            .expect("generations lock should not be poisoned");
+
+        let changed_at = match generations.get(node_name) {
+            Some((observed, changed_at)) if *observed == generation => *changed_at,
+            Some(_) => Some(self.clock.now()),
+            None => None,
+        };
+        generations.insert(node_name.to_string(), (generation, changed_at));
+    }
+
+    // Returns how long the node's write cooldown still lasts, or None if the node may be updated.
+    fn write_cooldown(&self, node_name: &str) -> Option<chrono::Duration> {
+        let cooldown = self.options.node_write_cooldown?;
+        let last_write = *self
+            .last_writes
+            .lock()
+            .expect("last writes lock should not be poisoned")
+            .get(node_name)?;
+
+        let remaining = cooldown - (self.clock.now() - last_write);
+        (remaining > chrono::Duration::zero()).then_some(remaining)
+    }
+
+    fn record_write(&self, node_name: &str) {
+        if self.options.node_write_cooldown.is_none() {
+            return;
+        }
+
+        self.last_writes
+            .lock()
+            .expect("last writes lock should not be poisoned")
+            .insert(node_name.to_string(), self.clock.now());
+    }
+
+    // Records the status of each of the node's conditions if any matcher only acts on transitions.
+    fn observe_condition_statuses(&self, node_name: &str, status: &NodeStatus) {
+        if !self
+            .matchers
+            .iter()
+            .any(|matcher| matcher.on_transition_only)
+        {
+            return;
+        }
+
+        let statuses = status
+            .conditions
+            .iter()
+            .flatten()
+            .map(|condition| (condition.type_.clone(), condition.status.clone()))
+            .collect();
+        self.condition_statuses
+            .lock()
+            .expect("condition statuses lock should not be poisoned")
+            .insert(node_name.to_string(), statuses);
+    }
+
+    // Whether the status of one of the matched conditions differs from the status that Tainter
+    // last observed, or the condition appeared since. The conditions of nodes that Tainter has not
+    // observed before, e.g. after a restart, did not transition.
+    fn conditions_transitioned(
+        &self,
+        node_name: &str,
+        matched_conditions: &[&NodeCondition],
+    ) -> bool {
+        let condition_statuses = self
+            .condition_statuses
+            .lock()
+            .expect("condition statuses lock should not be poisoned");
+        let Some(statuses) = condition_statuses.get(node_name) else {
+            return false;
+        };
+
+        matched_conditions.iter().any(|condition| {
+            statuses
+                .get(&condition.type_)
+                .is_none_or(|status| *status != condition.status)
+        })
+    }
+
+    // Returns when Tainter observed the node's generation change, if known.
+    fn generation_changed_at(&self, node_name: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.generations
+            .lock()
+            .expect("generations lock should not be poisoned")
+            .get(node_name)
+            .and_then(|(_, changed_at)| *changed_at)
+    }
+
+    // Returns the first matched condition that last transitioned more than the stale condition
+    // tolerance before the node's generation changed. Conditions without a transition time are
+    // never stale.
+    fn stale_condition<'a>(
+        &self,
+        matched_conditions: &[&'a NodeCondition],
+        generation_changed_at: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Option<&'a NodeCondition> {
+        let tolerance = self.options.stale_condition_tolerance?;
+        let generation_changed_at = generation_changed_at?;
+
+        matched_conditions.iter().copied().find(|condition| {
+            condition
+                .last_transition_time
+                .as_ref()
+                .is_some_and(|last_transition_time| {
+                    generation_changed_at - last_transition_time.0 > tolerance
+                })
+        })
+    }
+
+    // Returns the new value of the would-taint annotation, or None if the annotation does not need
+    // to change.
+    fn would_taint_annotation(
+        &self,
+        metadata: &ObjectMeta,
+        would_taints: &[Taint],
+    ) -> Option<String> {
+        if would_taints.is_empty() {
+            return None;
+        }
+
+        let value = self.taints_to_string(would_taints);
+        let current = metadata
+            .annotations
+            .as_ref()
+            .and_then(|annotations| annotations.get(WOULD_TAINT_ANNOTATION));
+
+        if current == Some(&value) {
+            return None;
+        }
+
+        Some(value)
+    }
+
+    // A managed taint is replaced by an added taint with the same key, which changes the taint's
+    // effect, unless a matching matcher keeps the managed taint, e.g. because the matcher adds
+    // taints with the same key but different effects. A taint with the same key and effect is
+    // always replaced, as a node can't have both. Taints that Tainter does not manage are never
+    // replaced.
+    fn is_replaced(
+        &self,
+        taint: &Taint,
+        taints_to_add: &[Taint],
+        managed_keys: &BTreeSet<String>,
+        kept_taints: &BTreeSet<(String, String)>,
+    ) -> bool {
+        let kept = kept_taints.contains(&(taint.key.clone(), taint.effect.clone()));
+        managed_keys.contains(&taint.key)
+            && taints_to_add
+                .iter()
+                .any(|added| added.key == taint.key && (added.effect == taint.effect || !kept))
+    }
+
+    // The reason of the removal of a managed taint that an added taint with the same key replaces.
+    fn replacement_reason(&self, taint: &Taint, taints_to_add: &[Taint]) -> String {
+        let effect = taints_to_add
+            .iter()
+            .find(|added| added.key == taint.key && added.effect != taint.effect)
+            .or_else(|| taints_to_add.iter().find(|added| added.key == taint.key))
+            .map(|added| added.effect.as_str())
+            .unwrap_or_default();
+        format!("Replaced by a {effect} taint with the same key")
+    }
+
+    // Returns the taint of the node that adding the taint would replace with a less severe effect.
+    fn downgraded_taint<'t>(
+        &self,
+        taint_to_add: &Taint,
+        taints: &'t [Taint],
+        managed_keys: &BTreeSet<String>,
+        kept_taints: &BTreeSet<(String, String)>,
+    ) -> Option<&'t Taint> {
+        taints.iter().find(|existing| {
+            self.is_replaced(
+                existing,
+                std::slice::from_ref(taint_to_add),
+                managed_keys,
+                kept_taints,
+            ) && effect_severity(&taint_to_add.effect) < effect_severity(&existing.effect)
+        })
+    }
+
+    fn is_cordoned(&self, node: &Node) -> bool {
+        node.spec
+            .as_ref()
+            .and_then(|spec| spec.unschedulable)
+            .unwrap_or(false)
+    }
+
+    // Whether the node has one of the role labels of control plane nodes. Only the presence of the
+    // label matters, its value is usually empty.
+    fn is_control_plane(&self, node: &Node) -> bool {
+        node.metadata.labels.as_ref().is_some_and(|labels| {
+            CONTROL_PLANE_ROLE_LABELS
+                .iter()
+                .any(|label| labels.contains_key(*label))
+        })
+    }
+
+    fn is_conflict_error(&self, error_string: &str) -> bool {
+        error_string.contains("the object has been modified; please apply your changes to the latest version and try again")
+    }
+
+    fn node_has_taint(&self, haystack: &[Taint], needle: &Taint) -> bool {
+        for taint in haystack {
+            if self.identical_taints(taint, needle) {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    // Identical taints are defined as ones that have the same key and effect.
+    // See this error from the Kubernetes API:
+    //
+    // Node "XYZ" is invalid: metadata.taints[1]: Duplicate value: core.Taint{Key:"kubernetes.azure.com/scalesetpriority", Value:"premium", Effect:"NoSchedule", TimeAdded:<nil>}: taints must be unique by key and effect pair
+    //
+    fn identical_taints(&self, this: &Taint, that: &Taint) -> bool {
+        this.key == that.key && this.effect == that.effect
+    }
+
+    // Returns the node conditions that match the matcher's conditions, in the order of the
+    // matcher's conditions, or None if the node is not eligible.
+    fn matching_conditions<'a>(
+        &self,
+        node_name: &str,
+        have: &'a [NodeCondition],
+        matcher: &Configuration,
+    ) -> Option<Vec<&'a NodeCondition>> {
+        let want = &matcher.conditions;
+        let mut matched_conditions = Vec::with_capacity(want.len());
+        // The types of normalized conditions are matched after normalization, so the set only
+        // prunes node conditions for conditions that aren't normalized.
+        let type_matches: Option<Vec<_>> = matcher.type_set().map(|type_set| {
+            have.iter()
+                .map(|node_condition| type_set.matches(&node_condition.type_))
+                .collect()
+        });
+
+        'search: for (index, desired_condition) in want.iter().enumerate() {
+            for (position, node_condition) in have.iter().enumerate() {
+                if desired_condition.normalize == Normalize::Off
+                    && type_matches
+                        .as_ref()
+                        .is_some_and(|type_matches| !type_matches[position].matched(index))
+                {
+                    continue;
+                }
+                if self.conditions_match(desired_condition, node_condition) {
+                    tracing::info!(
+                        node = node_name,
+                        node_condition = format!("{:?}", node_condition).as_str(),
+                        condition = format!("{:?}", desired_condition).as_str(),
+                        "Node matches condition",
+                    );
+                    matched_conditions.push(node_condition);
+                    continue 'search;
+                }
+            }
+
+            // If we can't find a match for a single condition, the node is not eligible.
+            return None;
+        }
+
+        Some(matched_conditions)
+    }
+
+    fn is_suspended(&self, matcher: &Configuration) -> bool {
+        self.options.systemic_guard_percentage.is_some()
+            && self
+                .suspended_matchers
+                .lock()
+                .expect("suspended matchers lock should not be poisoned")
+                .contains(&self.matcher_label(matcher))
+    }
+
+    // Returns the node conditions that match the matcher's conditions if the node is eligible for
+    // the matcher, regardless of whether the matcher is enabled.
+    fn matcher_conditions<'a>(
+        &self,
+        node_name: &str,
+        matcher: &Configuration,
+        status: &'a NodeStatus,
+        labels: Option<&BTreeMap<String, String>>,
+        created: Option<&Time>,
+        taints: &[Taint],
+    ) -> Option<Vec<&'a NodeCondition>> {
+        let conditions = status.conditions.as_deref().unwrap_or_default();
+        self.matching_conditions(node_name, conditions, matcher)
+            .filter(|_| self.required_label_missing(node_name, matcher, labels))
+            .filter(|_| self.node_age_matches(node_name, matcher, created))
+            .filter(|_| self.taint_conditions_match(node_name, matcher, taints))
+            .filter(|_| self.capacity_matches(node_name, matcher, status))
+            .filter(|_| self.reason_quorum_matches(node_name, matcher, conditions))
+    }
+
+    // Whether the node is missing one of the matcher's required labels. Matchers without required
+    // labels apply regardless of the node's labels.
+    fn required_label_missing(
+        &self,
+        node_name: &str,
+        matcher: &Configuration,
+        labels: Option<&BTreeMap<String, String>>,
+    ) -> bool {
+        if matcher.required_labels.is_empty() {
+            return true;
+        }
+
+        let missing_label = matcher
+            .required_labels
+            .iter()
+            .find(|label| labels.is_none_or(|labels| !labels.contains_key(*label)));
+        if let Some(label) = missing_label {
+            tracing::info!(node = node_name, label, "Node is missing required label");
+        }
+
+        missing_label.is_some()
+    }
+
+    // Whether the age of the node is within the matcher's node age range. Matchers without a range
+    // apply regardless of the node's age.
+    fn node_age_matches(
+        &self,
+        node_name: &str,
+        matcher: &Configuration,
+        created: Option<&Time>,
+    ) -> bool {
+        if matcher.node_age_min.is_none() && matcher.node_age_max.is_none() {
+            return true;
+        }
+
+        let Some(created) = created else {
+            tracing::info!(
+                node = node_name,
+                "Node has no creation time to tell its age"
+            );
+            return false;
+        };
+        let age = self.clock.now() - created.0;
+        let matches = matcher.node_age_min.is_none_or(|min| age >= min)
+            && matcher.node_age_max.is_none_or(|max| age <= max);
+        if !matches {
+            tracing::info!(
+                node = node_name,
+                age = format!("{}s", age.num_seconds()),
+                "Node age is outside of the matcher's range"
+            );
+        }
+
+        matches
+    }
+
+    // Whether at least the quorum's count of the node's conditions report the same reason that
+    // matches the quorum's reason, regardless of their types and statuses.
+    fn reason_quorum_matches(
+        &self,
+        node_name: &str,
+        matcher: &Configuration,
+        conditions: &[NodeCondition],
+    ) -> bool {
+        let Some(quorum) = &matcher.reason_quorum else {
+            return true;
+        };
+
+        let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+        for reason in conditions
+            .iter()
+            .filter_map(|condition| condition.reason.as_deref())
+            .filter(|reason| quorum.reason.is_match(reason))
+        {
+            *counts.entry(reason).or_default() += 1;
+        }
+
+        match counts.into_iter().max_by_key(|(_, count)| *count) {
+            Some((reason, count)) if count >= quorum.count => {
+                tracing::info!(
+                    node = node_name,
+                    reason,
+                    count,
+                    quorum = quorum.count,
+                    "Node conditions reach reason quorum"
+                );
+                true
+            }
+            _ => false,
+        }
+    }
+
+    // Whether the node has a taint that matches each of the matcher's taint conditions. Taints
+    // that Tainter's matchers add are ignored, so that matchers never trigger themselves or each
+    // other in a loop.
+    fn taint_conditions_match(
+        &self,
+        node_name: &str,
+        matcher: &Configuration,
+        taints: &[Taint],
+    ) -> bool {
+        matcher.taint_conditions.iter().all(|taint_condition| {
+            let matching_taint = taints
+                .iter()
+                .filter(|taint| !self.is_matcher_key(&taint.key))
+                .find(|taint| taint_condition.is_match(taint));
+            if let Some(taint) = matching_taint {
+                tracing::info!(
+                    node = node_name,
+                    taint = self.taint_to_string(taint),
+                    taint_condition = format!("{:?}", taint_condition).as_str(),
+                    "Node matches taint condition",
+                );
+            }
+
+            matching_taint.is_some()
+        })
+    }
+
+    // Whether the node's capacity has at least the quantities of resources that the matcher
+    // requires.
+    fn capacity_matches(
+        &self,
+        node_name: &str,
+        matcher: &Configuration,
+        status: &NodeStatus,
+    ) -> bool {
+        matcher.capacity_requires.iter().all(|(resource, minimum)| {
+            let quantity = status
+                .capacity
+                .as_ref()
+                .and_then(|capacity| capacity.get(resource))
+                .and_then(|quantity| quantity::parse(&quantity.0));
+            let matches = quantity.is_some_and(|quantity| quantity >= *minimum);
+            if !matches {
+                tracing::info!(
+                    node = node_name,
+                    resource,
+                    minimum,
+                    "Node does not have the capacity that the matcher requires"
+                );
+            }
+
+            matches
+        })
+    }
+
+    // Returns the values of the named capture groups of the matcher's conditions. If several
+    // capture groups have the same name, the value of the last one wins.
+    fn named_captures(
+        &self,
+        matcher: &Configuration,
+        matched_conditions: &[&NodeCondition],
+    ) -> HashMap<String, String> {
+        let mut captures = HashMap::new();
+
+        for (condition, node_condition) in matcher.conditions.iter().zip(matched_conditions) {
+            // An inverted pattern doesn't match the status, so it captures nothing.
+            let status = match &condition.status {
+                Status::Pattern(status) => status.regex(),
+                Status::NotPattern(_) | Status::In(_) => None,
+            };
+            let type_ = condition.normalize.apply(&node_condition.type_);
+            let node_status = condition.normalize.apply(&node_condition.status);
+            let patterns = [
+                (condition.type_.regex(), Some(type_.as_ref())),
+                (status, Some(node_status.as_ref())),
+                (condition.reason.as_ref(), node_condition.reason.as_deref()),
+                (
+                    condition.message.as_ref(),
+                    node_condition.message.as_deref(),
+                ),
+            ];
+
+            for (pattern, value) in patterns {
+                let (Some(pattern), Some(value)) = (pattern, value) else {
+                    continue;
+                };
+                let Some(pattern_captures) = pattern.captures(value) else {
+                    continue;
+                };
+                for name in pattern.capture_names().flatten() {
+                    if let Some(capture) = pattern_captures.name(name) {
+                        captures.insert(name.to_string(), capture.as_str().to_string());
+                    }
+                }
+            }
+        }
+
+        captures
+    }
+
+    // Replaces ${name} placeholders with the value of the capture group with that name.
+    fn render_template(&self, template: &str, captures: &HashMap<String, String>) -> String {
+        captures
+            .iter()
+            .fold(template.to_string(), |rendered, (name, value)| {
+                rendered.replace(&format!("${{{}}}", name), value)
+            })
+    }
+
+    // Replaces the ${timestamp} placeholder with the current time. Taints are identified by their
+    // key and effect, so a taint with a timestamp is added once and keeps its timestamp for as long
+    // as it persists.
+    fn render_timestamp(&self, template: &str) -> String {
+        if !template.contains(TIMESTAMP_PLACEHOLDER) {
+            return template.to_string();
+        }
+
+        let timestamp = self.clock.now().format(TIMESTAMP_FORMAT).to_string();
+        template.replace(TIMESTAMP_PLACEHOLDER, &timestamp)
+    }
+
+    // Renders the label and timestamp placeholders of a taint key. Taints are identified by their
+    // key, so if the node already has a taint whose key only differs in its timestamp, that taint's
+    // key is returned to keep the taint rather than adding another one whenever the node matches.
+    fn render_key(
+        &self,
+        template: &str,
+        labels: Option<&BTreeMap<String, String>>,
+        fallback: Option<&str>,
+        taints: &[Taint],
+    ) -> Result<String, String> {
+        let key = self.render_labels(template, labels, fallback)?;
+        if !key.contains(TIMESTAMP_PLACEHOLDER) {
+            return Ok(key);
+        }
+
+        match taints
+            .iter()
+            .find(|taint| key_matches_template(&taint.key, &key))
+        {
+            Some(taint) => Ok(taint.key.clone()),
+            None => Ok(self.render_timestamp(&key)),
+        }
+    }
+
+    // Replaces ${label:<key>} placeholders in the template with the values of the node's labels. A
+    // missing label is replaced with the fallback if there is one, otherwise its key is returned as
+    // the error.
+    fn render_labels(
+        &self,
+        template: &str,
+        labels: Option<&BTreeMap<String, String>>,
+        fallback: Option<&str>,
+    ) -> Result<String, String> {
+        let mut rendered = String::new();
+        let mut rest = template;
+
+        while let Some(start) = rest.find(LABEL_PLACEHOLDER_PREFIX) {
+            let Some(length) = rest[start..].find('}') else {
+                break;
+            };
+            let key = &rest[start + LABEL_PLACEHOLDER_PREFIX.len()..start + length];
+            let value = labels
+                .and_then(|labels| labels.get(key))
+                .map(String::as_str)
+                .or(fallback)
+                .ok_or_else(|| key.to_string())?;
+            rendered.push_str(&rest[..start]);
+            rendered.push_str(value);
+            rest = &rest[start + length + 1..];
+        }
+        rendered.push_str(rest);
+
+        Ok(rendered)
+    }
+
+    // Returns the effect mapped from the severity captured by the first condition whose status
+    // regular expression has a "severity" capture group.
+    fn mapped_effect<'a>(
+        &self,
+        matcher: &'a Configuration,
+        matched_conditions: &[&NodeCondition],
+    ) -> Option<&'a String> {
+        if matcher.effect_mapping.is_empty() {
+            return None;
+        }
+
+        matcher
+            .conditions
+            .iter()
+            .zip(matched_conditions)
+            .find_map(|(condition, node_condition)| {
+                let Status::Pattern(Pattern::Regex(status)) = &condition.status else {
+                    return None;
+                };
+                let captures = status.captures(node_condition.status.as_str())?;
+                let severity = captures.name("severity")?;
+                matcher.effect_mapping.get(severity.as_str())
+            })
+    }
+
+    fn conditions_match(&self, this: &Condition, that: &NodeCondition) -> bool {
+        self.condition_mismatch(this, that).is_none()
+    }
+
+    // Returns the first field of the node condition that does not match the condition, or None if
+    // the node condition matches.
+    fn condition_mismatch(&self, this: &Condition, that: &NodeCondition) -> Option<Mismatch> {
+        if !this.type_.is_match(&this.normalize.apply(&that.type_)) {
+            return Some(Mismatch::Type);
+        }
+        if !this.status.is_match(&this.normalize.apply(&that.status)) {
+            return Some(Mismatch::Status);
+        }
+        // A node condition without a reason never matches a reason pattern.
+        if !self.optional_match(this.reason.as_ref(), that.reason.as_deref()) {
+            return Some(Mismatch::Reason);
+        }
+        // A node condition without a message never matches a message pattern.
+        if !self.optional_match(this.message.as_ref(), that.message.as_deref()) {
+            return Some(Mismatch::Message);
+        }
+
+        let recent_enough = match this.max_age {
+            None => true,
+            Some(max_age) => {
+                that.last_transition_time
+                    .as_ref()
+                    .is_some_and(|last_transition_time| {
+                        self.clock.now() - last_transition_time.0 <= max_age
+                    })
+            }
+        };
+
+        let heartbeat_matches = match &this.heartbeat {
+            None => true,
+            Some(heartbeat) => {
+                that.last_heartbeat_time
+                    .as_ref()
+                    .is_some_and(|last_heartbeat_time| {
+                        let age = self.clock.now() - last_heartbeat_time.0;
+                        match heartbeat {
+                            Heartbeat::Fresh(max_age) => age <= *max_age,
+                            Heartbeat::Stale(max_age) => age > *max_age,
+                        }
+                    })
+            }
+        };
+
+        if !recent_enough {
+            return Some(Mismatch::Age);
+        }
+        if !heartbeat_matches {
+            return Some(Mismatch::Heartbeat);
+        }
+
+        None
+    }
+
+    // An unset pattern matches anything, whereas a set pattern never matches an unset value.
+    fn optional_match(&self, pattern: Option<&Regex>, value: Option<&str>) -> bool {
+        match pattern {
+            None => true,
+            Some(pattern) => value.is_some_and(|value| pattern.is_match(value)),
+        }
+    }
+
+    // Identifies the matcher in metrics and logs by its name, or by its taint keys if it has none.
+    fn matcher_label(&self, matcher: &Configuration) -> String {
+        if let Some(name) = &matcher.name {
+            return name.clone();
+        }
+
+        matcher
+            .taints
+            .iter()
+            .map(|taint| taint.key.as_str())
+            .collect::<Vec<&str>>()
+            .join(",")
+    }
+
+    fn taint_to_string(&self, taint: &Taint) -> String {
+        let value = match taint.value.as_ref() {
+            None => String::new(),
+            Some(value) => format!("={}", value),
+        };
+        let time_added = match taint.time_added.as_ref() {
+            None => String::new(),
+            Some(time_added) => format!("/{}", time_added.0),
+        };
+        format!("{}{value}:{}{time_added}", taint.key, taint.effect)
+    }
+
+    // Describes which node conditions triggered each taint, e.g.
+    // "network=severed:NoSchedule by PrivateLink=severed (LinkDown)".
+    fn triggers_to_string(&self, taints_to_add: &[TaintToAdd]) -> String {
+        taints_to_add
+            .iter()
+            .map(|(taint, conditions)| {
+                format!(
+                    "{} by {}",
+                    self.taint_to_string(taint),
+                    self.conditions_to_string(conditions)
+                )
+            })
+            .collect::<Vec<String>>()
+            .join(",")
+    }
+
+    // The value of the last action annotation, or None if no taints are added.
+    fn last_action(&self, taints_to_add: &[TaintToAdd]) -> Option<String> {
+        if taints_to_add.is_empty() {
+            return None;
+        }
+
+        let now = self
+            .clock
+            .now()
+            .to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+        let actions = taints_to_add
+            .iter()
+            .map(|(taint, conditions)| {
+                format!(
+                    "{} added at {} because {}",
+                    self.taint_to_string(taint),
+                    now,
+                    self.conditions_to_string(conditions)
+                )
+            })
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        Some(actions)
+    }
+
+    fn conditions_to_string(&self, conditions: &[&NodeCondition]) -> String {
+        conditions
+            .iter()
+            .map(|condition| match &condition.reason {
+                Some(reason) => format!("{}={} ({})", condition.type_, condition.status, reason),
+                None => format!("{}={}", condition.type_, condition.status),
+            })
+            .collect::<Vec<String>>()
+            .join(" and ")
+    }
+
+    // The webhook events of the taints added to and removed from a node. Taints are added because
+    // of the node conditions that triggered them, whereas all taints are removed for the same
+    // reason.
+    fn webhook_events(
+        &self,
+        node_name: &str,
+        taints_to_add: &[TaintToAdd],
+        taints_to_remove: &[Taint],
+        removal_reason: &str,
+    ) -> Vec<webhook::Event> {
+        if self.options.webhook.is_none() {
+            return vec![];
+        }
+
+        let timestamp = self.clock.now();
+        let added = taints_to_add
+            .iter()
+            .map(|(taint, conditions)| webhook::Event {
+                node: node_name.to_string(),
+                action: webhook::Action::Added,
+                taint: taint.clone(),
+                reason: self.conditions_to_string(conditions),
+                timestamp,
+            });
+        let removed = taints_to_remove.iter().map(|taint| webhook::Event {
+            node: node_name.to_string(),
+            action: webhook::Action::Removed,
+            taint: taint.clone(),
+            reason: removal_reason.to_string(),
+            timestamp,
+        });
+
+        added.chain(removed).collect()
+    }
+
+    fn send_webhook_events(&self, events: Vec<webhook::Event>) {
+        if let Some(webhook) = &self.options.webhook {
+            webhook.send(events);
+        }
+    }
+
+    // The decision records of the taints added to and removed from a node, with the same reasons
+    // as the webhook events.
+    fn decision_records(
+        &self,
+        node_name: &str,
+        taints_to_add: &[TaintToAdd],
+        taints_to_remove: &[Taint],
+        removal_reason: &str,
+    ) -> Vec<decision_log::Record> {
+        if self.options.decision_log.is_none() {
+            return vec![];
+        }
+
+        let timestamp = self.clock.now();
+        let record = |action, taint: &Taint, reason| decision_log::Record {
+            version: decision_log::SCHEMA_VERSION,
+            node: node_name.to_string(),
+            matcher: self.taint_matcher(taint),
+            action,
+            taint: taint.clone(),
+            reason,
+            timestamp,
+        };
+        let added = taints_to_add.iter().map(|(taint, conditions)| {
+            record(
+                webhook::Action::Added,
+                taint,
+                self.conditions_to_string(conditions),
+            )
+        });
+        let removed = taints_to_remove
+            .iter()
+            .map(|taint| record(webhook::Action::Removed, taint, removal_reason.to_string()));
+
+        added.chain(removed).collect()
+    }
+
+    fn write_decision_records(&self, records: &[decision_log::Record]) {
+        if let Some(decision_log) = &self.options.decision_log {
+            decision_log.write(records);
+        }
+    }
+
+    // Identifies the first matcher that adds a taint with the key of the taint.
+    fn taint_matcher(&self, taint: &Taint) -> Option<String> {
+        self.matchers
+            .iter()
+            .find(|matcher| {
+                matcher
+                    .taints
+                    .iter()
+                    .any(|matcher_taint| key_matches_template(&taint.key, &matcher_taint.key))
+            })
+            .map(|matcher| self.matcher_label(matcher))
+    }
+
+    fn taints_to_string(&self, taints: &[Taint]) -> String {
+        taints
+            .iter()
+            .map(|taint| self.taint_to_string(taint))
+            .collect::<Vec<String>>()
+            .join(",")
+    }
+}
+
+fn node_taints(node: &Node) -> &[Taint] {
+    node.spec
+        .as_ref()
+        .and_then(|spec| spec.taints.as_deref())
+        .unwrap_or_default()
+}
+
+// Orders taint effects from least to most severe.
+fn effect_severity(effect: &str) -> u8 {
+    match effect {
+        "PreferNoSchedule" => 0,
+        "NoSchedule" => 1,
+        "NoExecute" => 2,
+        _ => 0,
+    }
+}
+
+// A JSON patch that writes the node's taints, labels and annotations. The patch sets the resource
+// version that Tainter read, which the API server checks like the resource version of a replaced
+// node, so a patch of a modified node fails with a conflict. A test operation would instead fail
+// with an unprocessable entity error.
+fn json_patch(node: &Node, resource_version: Option<&str>) -> json_patch::Patch {
+    let mut operations = vec![];
+    if let Some(resource_version) = resource_version {
+        operations.push(json!({
+            "op": "replace",
+            "path": "/metadata/resourceVersion",
+            "value": resource_version,
+        }));
+    }
+    let taints = node.spec.as_ref().and_then(|spec| spec.taints.as_ref());
+    operations.push(json!({"op": "add", "path": "/spec/taints", "value": taints}));
+    if let Some(labels) = &node.metadata.labels {
+        operations.push(json!({"op": "add", "path": "/metadata/labels", "value": labels}));
+    }
+    if let Some(annotations) = &node.metadata.annotations {
+        operations.push(json!({
+            "op": "add",
+            "path": "/metadata/annotations",
+            "value": annotations,
+        }));
+    }
+
+    serde_json::from_value(json!(operations)).expect("operations should be a valid JSON patch")
+}
+
+// Returns the NoSchedule taint with the same key and value as the taint, which keeps new pods off a
+// node whose pods are evicted before the taint is added.
+fn no_schedule(taint: &Taint) -> Taint {
+    Taint {
+        effect: "NoSchedule".to_string(),
+        time_added: None,
+        ..taint.clone()
+    }
+}
+
+// Whether a NoExecute taint would evict the pod, i.e. whether the pod has not terminated and does
+// not tolerate one of the taints. Mirror pods of static pods cannot be evicted through the API.
+fn is_evictable(pod: &Pod, taints: &[Taint]) -> bool {
+    let is_mirror = pod
+        .metadata
+        .annotations
+        .as_ref()
+        .is_some_and(|annotations| annotations.contains_key(MIRROR_POD_ANNOTATION));
+    let phase = pod
+        .status
+        .as_ref()
+        .and_then(|status| status.phase.as_deref());
+    if is_mirror || matches!(phase, Some("Succeeded") | Some("Failed")) {
+        return false;
+    }
+
+    let tolerations = pod
+        .spec
+        .as_ref()
+        .and_then(|spec| spec.tolerations.as_deref())
+        .unwrap_or_default();
+    taints.iter().any(|taint| {
+        !tolerations
+            .iter()
+            .any(|toleration| tolerates(toleration, taint))
+    })
+}
+
+// See https://kubernetes.io/docs/concepts/scheduling-eviction/taint-and-toleration/.
+fn tolerates(toleration: &Toleration, taint: &Taint) -> bool {
+    let effect = toleration.effect.as_deref().unwrap_or_default();
+    if !effect.is_empty() && effect != taint.effect {
+        return false;
+    }
+
+    let key = toleration.key.as_deref().unwrap_or_default();
+    match toleration.operator.as_deref() {
+        // An empty key with the Exists operator tolerates all taints.
+        Some("Exists") => key.is_empty() || key == taint.key,
+        _ => {
+            key == taint.key
+                && toleration.value.as_deref().unwrap_or_default()
+                    == taint.value.as_deref().unwrap_or_default()
+        }
+    }
+}
+
+// Whether the key can be rendered from the template by replacing its ${...} placeholders. A
+// placeholder matches any non-empty text.
+fn key_matches_template(key: &str, template: &str) -> bool {
+    let mut literals = vec![];
+    let mut rest = template;
+    while let Some(start) = rest.find("${") {
+        let Some(length) = rest[start..].find('}') else {
+            break;
+        };
+        literals.push(&rest[..start]);
+        rest = &rest[start + length + 1..];
+    }
+    if literals.is_empty() {
+        return key == template;
+    }
+    literals.push(rest);
+
+    let first = literals[0];
+    let last = literals[literals.len() - 1];
+    let Some(mut remaining) = key.strip_prefix(first) else {
+        return false;
+    };
+    for literal in &literals[1..literals.len() - 1] {
+        // Every placeholder matches at least one character.
+        let Some(position) = remaining.get(1..).and_then(|text| text.find(literal)) else {
+            return false;
+        };
+        remaining = &remaining[1 + position + literal.len()..];
+    }
+
+    remaining.len() > last.len() && remaining.ends_with(last)
+}
+
+fn is_node_ready(status: &NodeStatus) -> bool {
+    status
+        .conditions
+        .iter()
+        .flatten()
+        .any(|condition| condition.type_ == "Ready" && condition.status == "True")
+}
+
+// Whether the key is a valid qualified name, i.e. a name of at most 63 alphanumeric characters,
+// '-', '_' or '.' that starts and ends with an alphanumeric character, optionally prefixed by a DNS
+// subdomain and '/'. Keys with placeholders are only known once rendered, so the API server would
+// otherwise reject the update of the node.
+pub(crate) fn is_valid_taint_key(key: &str) -> bool {
+    let (prefix, name) = match key.split_once('/') {
+        Some((prefix, name)) => (Some(prefix), name),
+        None => (None, key),
+    };
+
+    let valid_name = !name.is_empty()
+        && name.len() <= 63
+        && name.starts_with(|c: char| c.is_ascii_alphanumeric())
+        && name.ends_with(|c: char| c.is_ascii_alphanumeric())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.');
+    let valid_prefix = prefix.is_none_or(|prefix| {
+        prefix.len() <= 253
+            && prefix.split('.').all(|label| {
+                !label.is_empty()
+                    && label.len() <= 63
+                    && label.starts_with(|c: char| c.is_ascii_lowercase() || c.is_ascii_digit())
+                    && label.ends_with(|c: char| c.is_ascii_lowercase() || c.is_ascii_digit())
+                    && label
+                        .chars()
+                        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+            })
+    });
+
+    valid_name && valid_prefix
+}
+
+// Whether the key's prefix is in one of the domains that Kubernetes reserves for its own taints
+// and labels, e.g. node.kubernetes.io/not-ready.
+fn is_reserved_key(key: &str) -> bool {
+    let Some((prefix, _)) = key.split_once('/') else {
+        return false;
+    };
+
+    ["kubernetes.io", "k8s.io"]
+        .iter()
+        .any(|domain| prefix == *domain || prefix.ends_with(&format!(".{domain}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{FixedClock, UtcClock};
+    use chrono::{DateTime, Utc};
+    use futures::{stream, StreamExt};
+    use http::{Request, Response};
+    use k8s_openapi::api::core::v1::NodeSpec;
+    use k8s_openapi::serde_json;
+    use kube::client::Body;
+    use std::io::ErrorKind;
+    use std::path::{Path, PathBuf};
+    use std::str::FromStr;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::Arc;
+    use std::{fs, io};
+    use test_case::test_case;
+    use tower_test::mock::Handle;
+    use tracing_test::traced_test;
+
+    async fn setup(
+        list_response_file: &str,
+        matchers: Vec<Configuration>,
+    ) -> Handle<Request<Body>, Response<Body>> {
+        setup_with_options(
+            list_response_file,
+            matchers,
+            Options::default(),
+            Metrics::new(),
+        )
+        .await
+    }
+
+    async fn setup_with_options(
+        list_response_file: &str,
+        matchers: Vec<Configuration>,
+        options: Options,
+        metrics: Metrics,
+    ) -> Handle<Request<Body>, Response<Body>> {
+        setup_with_clock(
+            list_response_file,
+            matchers,
+            options,
+            metrics,
+            Box::new(UtcClock),
+        )
+        .await
+    }
+
+    async fn setup_with_clock(
+        list_response_file: &str,
+        matchers: Vec<Configuration>,
+        options: Options,
+        metrics: Metrics,
+        clock: Box<dyn Clock>,
+    ) -> Handle<Request<Body>, Response<Body>> {
+        // https://kube.rs/controllers/testing/#example.
+        let (mock_service, mut handle) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+
+        let client = Client::new(mock_service, "default");
+
+        let reconciler = Reconciler::new(client, matchers, options, metrics, clock);
+
+        tokio::spawn(async move {
+            reconciler.start().await;
+        });
+
+        let (request, response) = handle.next_request().await.expect("list nodes not called");
+        assert_eq!(request.method(), http::Method::GET);
+        assert_eq!(request.uri().to_string(), "/api/v1/nodes?&limit=500");
+
+        let node_list_response_body = get_test_file(list_response_file);
+
+        response.send_response(
+            Response::builder()
+                .body(Body::from(node_list_response_body.into_bytes()))
+                .unwrap(),
+        );
+
+        handle
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_start_checks_conditions_with_regex_and_adds_taints() {
+        let matchers = vec![
+            Configuration {
+                taints: vec![Taint {
+                    effect: "NoExecute".to_string(),
+                    key: "pressure".to_string(),
+                    time_added: None,
+                    value: Some("memory".to_string()),
+                }],
+                conditions: vec![condition("OutOfMemory", "True")],
+                ..Default::default()
+            },
+            Configuration {
+                taints: vec![Taint {
+                    effect: "NoSchedule".to_string(),
+                    key: "network-partition".to_string(),
+                    time_added: None,
+                    value: None,
+                }],
+                conditions: vec![
+                    condition("NetworkInterfaceCard", "Kaput|Ruined"),
+                    condition("PrivateLink", "Severed"),
+                ],
+                ..Default::default()
+            },
+        ];
+        let mut handle = setup("list-nodes-multiple-eligible-regex.json", matchers).await;
+
+        let (request, response) = handle
+            .next_request()
+            .await
+            .expect("PUT node not called for aks-artemis1-41950716-vmss000082");
+        assert_eq!(request.method(), http::Method::PUT);
+        assert_eq!(
+            request.uri().to_string(),
+            "/api/v1/nodes/aks-artemis1-41950716-vmss000082?&fieldManager=tainter"
+        );
+        let node = node_from_body(request).await;
+        let taints = node.spec.unwrap().taints.unwrap();
+        assert_eq!(taints.len(), 2);
+        let taint = taints.get(1).unwrap();
+        let expected = Taint {
+            effect: "NoExecute".to_string(),
+            key: "pressure".to_string(),
+            time_added: Some(Time(Utc::now())),
+            value: Some("memory".to_string()),
+        };
+        assert_eq!(taint.effect, expected.effect);
+        assert_eq!(taint.key, expected.key);
+        assert_eq!(taint.value, expected.value);
+        assert!(within_duration(
+            taint.time_added.as_ref().unwrap().0,
+            expected.time_added.as_ref().unwrap().0,
+            chrono::Duration::seconds(5),
+        ));
+
+        response.send_response(
+            Response::builder()
+                .body(Body::from(
+                    get_test_file("node-put-success.json").into_bytes(),
+                ))
+                .unwrap(),
+        );
+
+        let (request, response) = handle
+            .next_request()
+            .await
+            .expect("PUT node not called for aks-poseidon1-41950716-vmss000082");
+        assert_eq!(request.method(), http::Method::PUT);
+        assert_eq!(
+            request.uri().to_string(),
+            "/api/v1/nodes/aks-poseidon1-41950716-vmss000082?&fieldManager=tainter"
+        );
+        let node = node_from_body(request).await;
+        let taints = node.spec.unwrap().taints.unwrap();
+        assert_eq!(taints.len(), 3);
+        let taint = taints.get(1).unwrap();
+        let expected = Taint {
+            effect: "NoExecute".to_string(),
+            key: "pressure".to_string(),
+            time_added: Some(Time(Utc::now())),
+            value: Some("memory".to_string()),
+        };
+        assert_eq!(taint.effect, expected.effect);
+        assert_eq!(taint.key, expected.key);
+        assert_eq!(taint.value, expected.value);
+        assert!(within_duration(
+            taint.time_added.as_ref().unwrap().0,
+            expected.time_added.as_ref().unwrap().0,
+            chrono::Duration::seconds(5),
+        ));
+
+        let taint = taints.get(2).unwrap();
+        let expected = Taint {
+            effect: "NoSchedule".to_string(),
+            key: "network-partition".to_string(),
+            time_added: None,
+            value: None,
+        };
+        assert_eq!(taint.effect, expected.effect);
+        assert_eq!(taint.key, expected.key);
+        assert_eq!(taint.value, expected.value);
+        assert_eq!(taint.time_added, expected.time_added);
+
+        response.send_response(
+            Response::builder()
+                .body(Body::from(
+                    get_test_file("node-put-success.json").into_bytes(),
+                ))
+                .unwrap(),
+        );
+
+        let (request, _) = handle.next_request().await.expect("watch nodes not called");
+        assert_eq!(request.method(), http::Method::GET);
+        assert_eq!(
+            request.uri().to_string(),
+            "/api/v1/nodes?&watch=true&timeoutSeconds=290&\
+        allowWatchBookmarks=true&resourceVersion=test"
+        );
+
+        assert!(logs_contain(
+            r#"Processing node node_name="aks-ares1-41950716-vmss000082""#
+        ));
+        assert!(logs_contain(
+            r#"Processing node node_name="aks-artemis1-41950716-vmss000082""#
+        ));
+        assert!(logs_contain(
+            r#"Adding taints to node node="aks-artemis1-41950716-vmss000082" taints="[Taint { effect: \"NoExecute\", key: \"pressure\""#
+        ));
+        assert!(logs_contain(
+            r#"Successfully added taints to node node="aks-artemis1-41950716-vmss000082" taints="[Taint { effect: \"NoExecute\", key: \"pressure\""#
+        ));
+        assert!(logs_contain(
+            r#"Node matches condition node="aks-artemis1-41950716-vmss000082" node_condition="NodeCondition { last_heartbeat_time: Some(Time(2024-05-12T11:21:10Z)), last_transition_time: Some(Time(2024-05-07T08:32:09Z)), message: Some(\"The VM has no surplus memory\"), reason: Some(\"NoSurplusMemory\"), status: \"True\", type_: \"OutOfMemory\" }""#
+        ));
+        assert!(logs_contain(
+            r#"Processing node node_name="aks-athena1-41950716-vmss000082""#
+        ));
+        assert!(logs_contain(
+            r#"Processing node node_name="aks-poseidon1-41950716-vmss000082""#
+        ));
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_start_logs_conditions_that_triggered_taints() {
+        let matchers = vec![Configuration {
+            taints: vec![Taint {
+                effect: "NoSchedule".to_string(),
+                key: "event".to_string(),
+                time_added: None,
+                value: Some("scheduled".to_string()),
+            }],
+            conditions: vec![
+                condition("VMEventScheduled", "True"),
+                condition("Ready", "False"),
+            ],
+            ..Default::default()
+        }];
+        let mut handle = setup("list-nodes-single-eligible.json", matchers).await;
+
+        let (request, _) = handle.next_request().await.expect("PUT node not called");
+        assert_eq!(request.method(), http::Method::PUT);
+
+        assert!(logs_contain(
+            r#"Adding taints to node node="aks-zeus1-41950716-vmss000082" taints="[Taint { effect: \"NoSchedule\", key: \"event\", time_added: None, value: Some(\"scheduled\") }]" triggers="event=scheduled:NoSchedule by VMEventScheduled=True (VMEventScheduled) and Ready=False (KubeletReady)""#
+        ));
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_start_processes_node_and_logs_error_if_update_fails() {
+        let matchers = vec![Configuration {
+            taints: vec![Taint {
+                effect: "NoExecute".to_string(),
+                key: "event".to_string(),
+                time_added: None,
+                value: None,
+            }],
+            conditions: vec![condition("VMEventScheduled", "True")],
+            ..Default::default()
+        }];
+        let mut handle = setup("list-nodes-single-eligible.json", matchers).await;
+
+        let (request, response) = handle.next_request().await.expect("PUT node not called");
+        assert_eq!(request.method(), http::Method::PUT);
+        assert_eq!(
+            request.uri().to_string(),
+            "/api/v1/nodes/aks-zeus1-41950716-vmss000082?&fieldManager=tainter"
+        );
+
+        let node_put_response_body = get_test_file("node-put-invalid-response.json");
+
+        response.send_response(
+            Response::builder()
+                .body(Body::from(node_put_response_body.into_bytes()))
+                .unwrap(),
+        );
+
+        let (_, _) = handle.next_request().await.expect("watch nodes not called");
+
+        assert!(logs_contain(
+            r#"Error adding taints to node error="Error deserializing response: invalid type: integer `123`, expected Node at line 1 column 3" node="aks-zeus1-41950716-vmss000082" taints="[Taint { effect: \"NoExecute\", key: \"event\""#
+        ))
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_start_applies_taints_per_matcher_despite_failed_update() {
+        let matchers = vec![
+            Configuration {
+                taints: vec![Taint {
+                    effect: "NoSchedule".to_string(),
+                    key: "event".to_string(),
+                    time_added: None,
+                    value: None,
+                }],
+                conditions: vec![condition("VMEventScheduled", "True")],
+                ..Default::default()
+            },
+            Configuration {
+                taints: vec![Taint {
+                    effect: "NoSchedule".to_string(),
+                    key: "not-ready".to_string(),
+                    time_added: None,
+                    value: None,
+                }],
+                conditions: vec![condition("Ready", "False")],
+                ..Default::default()
+            },
+        ];
+        let options = Options {
+            apply_strategy: ApplyStrategy::PerMatcher,
+            ..Default::default()
+        };
+        let mut handle = setup_with_options(
+            "list-nodes-single-eligible.json",
+            matchers,
+            options,
+            Metrics::new(),
+        )
+        .await;
+
+        let (request, response) = handle.next_request().await.expect("PUT node not called");
+        assert_eq!(request.method(), http::Method::PUT);
+        let taints = node_from_body(request).await.spec.unwrap().taints.unwrap();
+        assert!(taints.iter().any(|taint| taint.key == "event"));
+        assert!(!taints.iter().any(|taint| taint.key == "not-ready"));
+        response.send_response(
+            Response::builder()
+                .body(Body::from(
+                    get_test_file("node-put-invalid-response.json").into_bytes(),
+                ))
+                .unwrap(),
+        );
+
+        // The second matcher's taint is added although the first matcher's update failed.
+        let (request, response) = handle.next_request().await.expect("PUT node not called");
+        assert_eq!(request.method(), http::Method::PUT);
+        let taints = node_from_body(request).await.spec.unwrap().taints.unwrap();
+        assert!(!taints.iter().any(|taint| taint.key == "event"));
+        assert!(taints.iter().any(|taint| taint.key == "not-ready"));
+        response.send_response(
+            Response::builder()
+                .body(Body::from(
+                    get_test_file("node-put-success.json").into_bytes(),
+                ))
+                .unwrap(),
+        );
+
+        let (_, _) = handle.next_request().await.expect("watch nodes not called");
+
+        assert!(logs_contain("Error adding taints to node"));
+        assert!(logs_contain(
+            r#"Successfully added taints to node node="aks-zeus1-41950716-vmss000082" taints="[Taint { effect: \"NoSchedule\", key: \"not-ready\""#
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_start_sets_last_action_annotation_of_all_matchers_per_matcher() {
+        let matchers = ["event", "not-ready"]
+            .into_iter()
+            .zip([
+                condition("VMEventScheduled", "True"),
+                condition("Ready", "False"),
+            ])
+            .map(|(key, condition)| Configuration {
+                taints: vec![Taint {
+                    effect: "NoSchedule".to_string(),
+                    key: key.to_string(),
+                    time_added: None,
+                    value: None,
+                }],
+                conditions: vec![condition],
+                ..Default::default()
+            })
+            .collect();
+        let options = Options {
+            apply_strategy: ApplyStrategy::PerMatcher,
+            last_action_annotation: Some("tainter.io/last-action".to_string()),
+            ..Default::default()
+        };
+        let mut handle = setup_with_clock(
+            "list-nodes-single-eligible.json",
+            matchers,
+            options,
+            Metrics::new(),
+            Box::new(FixedClock(fixed_time())),
+        )
+        .await;
+
+        // Each update records the taints of the previous updates along with its own.
+        let expected_actions = [
+            "event:NoSchedule added at 2024-05-12T11:21:10Z because VMEventScheduled=True (VMEventScheduled)",
+            "event:NoSchedule added at 2024-05-12T11:21:10Z because VMEventScheduled=True (VMEventScheduled), \
+            not-ready:NoSchedule added at 2024-05-12T11:21:10Z because Ready=False (KubeletReady)",
+        ];
+        for expected_action in expected_actions {
+            let (request, response) = handle.next_request().await.expect("PUT node not called");
+            assert_eq!(request.method(), http::Method::PUT);
+            let node = node_from_body(request).await;
+            assert_eq!(
+                Some(&expected_action.to_string()),
+                node.metadata
+                    .annotations
+                    .unwrap()
+                    .get("tainter.io/last-action")
+            );
+            response.send_response(
+                Response::builder()
+                    .body(Body::from(
+                        get_test_file("node-put-success.json").into_bytes(),
+                    ))
+                    .unwrap(),
+            );
+        }
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_start_applies_taints_under_field_manager_of_matcher() {
+        let matchers = vec![
+            Configuration {
+                taints: vec![Taint {
+                    effect: "NoSchedule".to_string(),
+                    key: "event".to_string(),
+                    time_added: None,
+                    value: None,
+                }],
+                conditions: vec![condition("VMEventScheduled", "True")],
+                field_manager: Some("tainter-event".to_string()),
+                ..Default::default()
+            },
+            Configuration {
+                taints: vec![Taint {
+                    effect: "NoSchedule".to_string(),
+                    key: "not-ready".to_string(),
+                    time_added: None,
+                    value: None,
+                }],
+                conditions: vec![condition("Ready", "False")],
+                ..Default::default()
+            },
+        ];
+        // Matchers with a field manager are written separately even with the batch strategy.
+        let mut handle = setup("list-nodes-single-eligible.json", matchers).await;
+
+        for (field_manager, key) in [("tainter-event", "event"), ("tainter", "not-ready")] {
+            let (request, response) = handle.next_request().await.expect("PUT node not called");
+            assert_eq!(request.method(), http::Method::PUT);
+            assert_eq!(
+                request.uri().to_string(),
+                format!(
+                    "/api/v1/nodes/aks-zeus1-41950716-vmss000082?&fieldManager={field_manager}"
+                )
+            );
+            let taints = node_from_body(request).await.spec.unwrap().taints.unwrap();
+            assert!(taints.iter().any(|taint| taint.key == key));
+            response.send_response(
+                Response::builder()
+                    .body(Body::from(
+                        get_test_file("node-put-success.json").into_bytes(),
+                    ))
+                    .unwrap(),
+            );
+        }
+
+        let (request, _) = handle.next_request().await.expect("watch nodes not called");
+        assert_eq!(request.method(), http::Method::GET);
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_start_adds_taint_only_if_node_does_not_already_have_it() {
+        let matchers = vec![Configuration {
+            taints: vec![Taint {
+                effect: "NoExecute".to_string(),
+                key: "node.kubernetes.io/out-of-service".to_string(),
+                time_added: None,
+                value: None,
+            }],
+            conditions: vec![condition("Ready", "False|Unknown")],
+            ..Default::default()
+        }];
+        let mut handle = setup("list-nodes-eligible-and-has-taint.json", matchers).await;
+
+        let (request, _) = handle.next_request().await.expect("watch nodes not called");
+        assert_eq!(request.method(), http::Method::GET);
+        assert_eq!(
+            request.uri().to_string(),
+            "/api/v1/nodes?&watch=true&timeoutSeconds=290&\
+        allowWatchBookmarks=true&resourceVersion=test"
+        );
+
+        assert!(logs_contain(
+            r#"Node matches conditions but already has taint node="aks-artemis1-41950716-vmss000082" taint="node.kubernetes.io/out-of-service:NoExecute"#
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_start_counts_taints_already_present() {
+        let matchers = vec![Configuration {
+            taints: vec![Taint {
+                effect: "NoExecute".to_string(),
+                key: "node.kubernetes.io/out-of-service".to_string(),
+                time_added: None,
+                value: None,
+            }],
+            conditions: vec![condition("Ready", "False|Unknown")],
+            name: Some("out-of-service".to_string()),
+            ..Default::default()
+        }];
+        let metrics = Metrics::new();
+        let mut handle = setup_with_options(
+            "list-nodes-eligible-and-has-taint.json",
+            matchers,
+            Options::default(),
+            metrics.clone(),
+        )
+        .await;
+
+        let (request, _) = handle.next_request().await.expect("watch nodes not called");
+        assert_eq!(request.method(), http::Method::GET);
+
+        assert_eq!(
+            1,
+            metrics
+                .taints_already_present
+                .with_label_values(&["out-of-service"])
+                .get()
+        );
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_start_gracefully_handles_conflict_error() {
+        let matchers = vec![Configuration {
+            taints: vec![Taint {
+                effect: "NoSchedule".to_string(),
+                key: "not-ready".to_string(),
+                time_added: None,
+                value: None,
+            }],
+            conditions: vec![condition("Ready", "False|Unknown")],
+            ..Default::default()
+        }];
+        let mut handle = setup("list-nodes-single-eligible.json", matchers).await;
+
+        let (request, response) = handle.next_request().await.expect("PUT node not called");
+        assert_eq!(request.method(), http::Method::PUT);
+        assert_eq!(
+            request.uri().to_string(),
+            "/api/v1/nodes/aks-zeus1-41950716-vmss000082?&fieldManager=tainter"
+        );
+
+        let node_put_response_body = get_test_file("node-put-conflict-response.json");
+
+        response.send_response(
+            Response::builder()
+                .status(409)
+                .body(Body::from(node_put_response_body.into_bytes()))
+                .unwrap(),
+        );
+
+        let (_, _) = handle.next_request().await.expect("watch nodes not called");
+
+        assert!(logs_contain(
+            r#"Received conflict error when trying to add taints to node error="ApiError: Operation cannot be fulfilled on nodes \"aks-zeus1-41950716-vmss000082\": the object has been modified; please apply your changes to the latest version and try again: Conflict (ErrorResponse { status: \"Failure\", message: \"Operation cannot be fulfilled on nodes \\\"aks-zeus1-41950716-vmss000082\\\": the object has been modified; please apply your changes to the latest version and try again\", reason: \"Conflict\", code: 409 })" node="aks-zeus1-41950716-vmss000082" resource_version="1906423380" taints="[Taint { effect: \"NoSchedule\", key: \"not-ready\", time_added: None, value: None }]"#
+        ));
+        assert!(!logs_contain("Error adding taint to node"))
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_start_patches_node_with_resource_version_precondition() {
+        let matchers = vec![Configuration {
+            taints: vec![Taint {
+                effect: "NoSchedule".to_string(),
+                key: "not-ready".to_string(),
+                time_added: None,
+                value: None,
+            }],
+            conditions: vec![condition("Ready", "False|Unknown")],
+            ..Default::default()
+        }];
+        let options = Options {
+            update_strategy: UpdateStrategy::JsonPatch,
+            ..Default::default()
+        };
+        let mut handle = setup_with_options(
+            "list-nodes-single-eligible.json",
+            matchers,
+            options,
+            Metrics::new(),
+        )
+        .await;
+
+        let (request, response) = handle.next_request().await.expect("PATCH node not called");
+        assert_eq!(request.method(), http::Method::PATCH);
+        assert_eq!(
+            request.uri().to_string(),
+            "/api/v1/nodes/aks-zeus1-41950716-vmss000082?&fieldManager=tainter"
+        );
+        assert_eq!(
+            request.headers().get("content-type").unwrap(),
+            "application/json-patch+json"
+        );
+        let body = request.into_body().collect_bytes().await.unwrap();
+        let patch: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            serde_json::json!({
+                "op": "replace",
+                "path": "/metadata/resourceVersion",
+                "value": "1906423380",
+            }),
+            patch[0]
+        );
+        assert_eq!("/spec/taints", patch[1]["path"]);
+        assert_eq!("not-ready", patch[1]["value"][1]["key"]);
+
+        // The API server rejects the patch of a node whose resource version changed with the same
+        // conflict as a replace.
+        response.send_response(
+            Response::builder()
+                .status(409)
+                .body(Body::from(
+                    get_test_file("node-put-conflict-response.json").into_bytes(),
+                ))
+                .unwrap(),
+        );
+
+        let (_, _) = handle.next_request().await.expect("watch nodes not called");
+
+        assert!(logs_contain(
+            "Received conflict error when trying to add taints to node"
+        ));
+        assert!(logs_contain(r#"resource_version="1906423380""#));
+        assert!(!logs_contain("Error adding taints to node"));
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_start_posts_added_taints_to_webhook() {
+        let (url, mut events) = crate::webhook::tests::server(vec![]).await;
+        let matchers = vec![Configuration {
+            taints: vec![Taint {
+                effect: "NoSchedule".to_string(),
+                key: "not-ready".to_string(),
+                time_added: None,
+                value: None,
+            }],
+            conditions: vec![condition("Ready", "False|Unknown")],
+            ..Default::default()
+        }];
+        let options = Options {
+            webhook: Some(Arc::new(Webhook::new(
+                url,
+                std::time::Duration::from_secs(5),
+                0,
+                std::time::Duration::ZERO,
+            ))),
+            ..Default::default()
+        };
+        let mut handle = setup_with_clock(
+            "list-nodes-single-eligible.json",
+            matchers,
+            options,
+            Metrics::new(),
+            Box::new(FixedClock(fixed_time())),
+        )
+        .await;
+
+        let (_, response) = handle.next_request().await.expect("PUT node not called");
+        response.send_response(
+            Response::builder()
+                .body(Body::from(
+                    get_test_file("node-put-success.json").into_bytes(),
+                ))
+                .unwrap(),
+        );
+
+        let event = events.recv().await.expect("webhook not called");
+        assert_eq!(
+            serde_json::json!({
+                "node": "aks-zeus1-41950716-vmss000082",
+                "action": "Added",
+                "taint": {
+                    "effect": "NoSchedule",
+                    "key": "not-ready",
+                },
+                "reason": "Ready=False (KubeletReady)",
+                "timestamp": "2024-05-12T11:21:10Z",
+            }),
+            event
+        );
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_start_writes_decision_record_of_added_taint() {
+        let buffer = crate::decision_log::tests::Buffer::default();
+        let matchers = vec![Configuration {
+            name: Some("node-not-ready".to_string()),
+            taints: vec![Taint {
+                effect: "NoSchedule".to_string(),
+                key: "not-ready".to_string(),
+                time_added: None,
+                value: None,
+            }],
+            conditions: vec![condition("Ready", "False|Unknown")],
+            ..Default::default()
+        }];
+        let options = Options {
+            decision_log: Some(Arc::new(DecisionLog::new(buffer.clone()))),
+            ..Default::default()
+        };
+        let mut handle = setup_with_clock(
+            "list-nodes-single-eligible.json",
+            matchers,
+            options,
+            Metrics::new(),
+            Box::new(FixedClock(fixed_time())),
+        )
+        .await;
+
+        let (_, response) = handle.next_request().await.expect("PUT node not called");
+        response.send_response(
+            Response::builder()
+                .body(Body::from(
+                    get_test_file("node-put-success.json").into_bytes(),
+                ))
+                .unwrap(),
+        );
+        let (_, _) = handle.next_request().await.expect("watch nodes not called");
+
+        assert_eq!(
+            vec![serde_json::json!({
+                "version": 1,
+                "node": "aks-zeus1-41950716-vmss000082",
+                "matcher": "node-not-ready",
+                "action": "Added",
+                "taint": {
+                    "effect": "NoSchedule",
+                    "key": "not-ready",
+                },
+                "reason": "Ready=False (KubeletReady)",
+                "timestamp": "2024-05-12T11:21:10Z",
+            })],
+            buffer.lines()
+        );
+    }
+
+    // 9 of the 10 nodes are NotReady.
+    #[test_case(50, false ; "suppresses matcher above percentage")]
+    #[test_case(90, true ; "taints at percentage")]
+    #[tokio::test]
+    #[traced_test]
+    async fn test_start_systemic_guard(percentage: u8, taints: bool) {
+        let matchers = vec![Configuration {
+            taints: vec![Taint {
+                effect: "NoSchedule".to_string(),
+                key: "not-ready".to_string(),
+                time_added: None,
+                value: None,
+            }],
+            conditions: vec![condition("Ready", "False")],
+            ..Default::default()
+        }];
+        let options = Options {
+            systemic_guard_percentage: Some(percentage),
+            ..Default::default()
+        };
+        let mut handle = setup_with_options(
+            "list-nodes-systemic.json",
+            matchers,
+            options,
+            Metrics::new(),
+        )
+        .await;
+
+        let (request, _) = handle.next_request().await.expect("no request sent");
+        if taints {
+            assert_eq!(request.method(), http::Method::PUT);
+            assert!(!logs_contain("Suspending matcher"));
+        } else {
+            assert!(request.uri().to_string().contains("watch=true"));
+            assert!(logs_contain(
+                "Suspending matcher until the next sweep because too many nodes are eligible for \
+                it at once, which likely indicates a cluster-wide issue rather than problems of \
+                individual nodes matcher=\"not-ready\" eligible_nodes=9 node_count=10 percentage=50"
+            ));
+        }
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_start_does_not_add_taint_for_disabled_matcher() {
+        let matchers = vec![Configuration {
+            enabled: false,
+            taints: vec![Taint {
+                effect: "NoExecute".to_string(),
+                key: "event".to_string(),
+                time_added: None,
+                value: None,
+            }],
+            conditions: vec![condition("VMEventScheduled", "True")],
+            ..Default::default()
+        }];
+        let mut handle = setup("list-nodes-single-eligible.json", matchers).await;
+
+        // The node matches the disabled matcher's conditions, so the next request would be a PUT
+        // if the matcher was not skipped.
+        let (request, _) = handle.next_request().await.expect("watch nodes not called");
+        assert_eq!(request.method(), http::Method::GET);
+        assert_eq!(
+            request.uri().to_string(),
+            "/api/v1/nodes?&watch=true&timeoutSeconds=290&\
+        allowWatchBookmarks=true&resourceVersion=test"
+        );
+
+        assert!(logs_contain(
+            r#"Skipping disabled matcher node="aks-zeus1-41950716-vmss000082" matcher="event" taints="event:NoExecute""#
+        ));
+        assert!(!logs_contain("Adding taints to node"));
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_start_logs_resource_version_when_processing_node() {
+        let matchers = vec![Configuration {
+            taints: vec![Taint {
+                effect: "NoSchedule".to_string(),
+                key: "unused".to_string(),
+                time_added: None,
+                value: None,
+            }],
+            conditions: vec![condition("NonExistentCondition", "True")],
+            ..Default::default()
+        }];
+        let mut handle = setup("list-nodes-single-eligible.json", matchers).await;
+
+        let (_, _) = handle.next_request().await.expect("watch nodes not called");
+
+        assert!(logs_contain(
+            r#"Processing node node_name="aks-zeus1-41950716-vmss000082" resource_version="1906423380""#
+        ));
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_start_summarises_sweep_instead_of_logging_each_node() {
+        let matchers = vec![Configuration {
+            taints: vec![Taint {
+                effect: "NoSchedule".to_string(),
+                key: "unused".to_string(),
+                time_added: None,
+                value: None,
+            }],
+            conditions: vec![condition("NonExistentCondition", "True")],
+            ..Default::default()
+        }];
+        let mut handle = setup("list-nodes-multiple-eligible-regex.json", matchers).await;
+
+        let (_, _) = handle.next_request().await.expect("watch nodes not called");
+
+        assert!(logs_contain(
+            "Finished sweep node_count=4 tainted_nodes=0 skipped_nodes=0"
+        ));
+        logs_assert(|lines: &[&str]| {
+            let info_processing_logs = lines
+                .iter()
+                .filter(|line| line.contains(" INFO ") && line.contains("Processing node"))
+                .count();
+            match info_processing_logs {
+                0 => Ok(()),
+                n => Err(format!("expected no info processing logs, got {}", n)),
+            }
+        });
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_start_stops_adding_taints_when_max_nodes_per_sweep_is_reached() {
+        let matchers = vec![Configuration {
+            taints: vec![Taint {
+                effect: "NoSchedule".to_string(),
+                key: "pressure".to_string(),
+                time_added: None,
+                value: Some("memory".to_string()),
+            }],
+            conditions: vec![condition("OutOfMemory", "True")],
+            ..Default::default()
+        }];
+        let options = Options {
+            max_nodes_per_sweep: Some(1),
+            ..Default::default()
+        };
+        let mut handle = setup_with_options(
+            "list-nodes-multiple-eligible-regex.json",
+            matchers,
+            options,
+            Metrics::new(),
+        )
+        .await;
+
+        let (request, response) = handle.next_request().await.expect("PUT node not called");
+        assert_eq!(request.method(), http::Method::PUT);
+        assert_eq!(
+            request.uri().to_string(),
+            "/api/v1/nodes/aks-artemis1-41950716-vmss000082?&fieldManager=tainter"
+        );
+
+        response.send_response(
+            Response::builder()
+                .body(Body::from(
+                    get_test_file("node-put-success.json").into_bytes(),
+                ))
+                .unwrap(),
+        );
+
+        // aks-poseidon1-41950716-vmss000082 is also eligible, but must not be updated because the
+        // limit has been reached.
+        let (request, _) = handle.next_request().await.expect("watch nodes not called");
+        assert_eq!(request.method(), http::Method::GET);
+        assert_eq!(
+            request.uri().to_string(),
+            "/api/v1/nodes?&watch=true&timeoutSeconds=290&\
+        allowWatchBookmarks=true&resourceVersion=test"
+        );
+
+        assert!(logs_contain(
+            "Reached the maximum number of nodes to taint in a single sweep"
+        ));
+        assert!(logs_contain("max_nodes_per_sweep=1"));
+        assert!(!logs_contain(
+            r#"Adding taints to node node="aks-poseidon1-41950716-vmss000082""#
+        ));
+    }
+
+    #[tokio::test(start_paused = true)]
+    #[traced_test]
+    async fn test_start_stops_sweep_when_sweep_deadline_is_reached() {
+        let matchers = vec![Configuration {
+            taints: vec![Taint {
+                effect: "NoSchedule".to_string(),
+                key: "pressure".to_string(),
+                time_added: None,
+                value: Some("memory".to_string()),
+            }],
+            conditions: vec![condition("OutOfMemory", "True")],
+            ..Default::default()
+        }];
+        let options = Options {
+            sweep_deadline: Some(std::time::Duration::from_secs(5)),
+            ..Default::default()
+        };
+        let mut handle = setup_with_options(
+            "list-nodes-multiple-eligible-regex.json",
+            matchers,
+            options,
+            Metrics::new(),
+        )
+        .await;
+
+        let (request, response) = handle.next_request().await.expect("PUT node not called");
+        assert_eq!(request.method(), http::Method::PUT);
+        assert_eq!(
+            request.uri().to_string(),
+            "/api/v1/nodes/aks-artemis1-41950716-vmss000082?&fieldManager=tainter"
+        );
+        // The update of the first node takes longer than the deadline.
+        tokio::time::advance(std::time::Duration::from_secs(6)).await;
+        response.send_response(
+            Response::builder()
+                .body(Body::from(
+                    get_test_file("node-put-success.json").into_bytes(),
+                ))
+                .unwrap(),
+        );
+
+        // aks-poseidon1-41950716-vmss000082 is also eligible, but must not be updated because the
+        // deadline has been reached.
+        let (request, _) = handle.next_request().await.expect("watch nodes not called");
+        assert_eq!(request.method(), http::Method::GET);
+        assert!(request.uri().to_string().contains("watch=true"));
+
+        assert!(logs_contain(
+            "Reached the sweep deadline, skipping the remaining nodes until the next sweep"
+        ));
+        // aks-ares1-41950716-vmss000082 is processed before the first node, but isn't eligible.
+        assert!(logs_contain(
+            r#"sweep_deadline="5s" processed_nodes=2 skipped_nodes=2"#
+        ));
+        assert!(!logs_contain(
+            r#"Adding taints to node node="aks-poseidon1-41950716-vmss000082""#
+        ));
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_start_maps_captured_severity_to_taint_effect() {
+        let matchers = vec![Configuration {
+            taints: vec![Taint {
+                effect: "NoSchedule".to_string(),
+                key: "hardware".to_string(),
+                time_added: None,
+                value: Some("faulty".to_string()),
+            }],
+            conditions: vec![condition(
+                "HardwareProblem",
+                "(?P<severity>Warning|Critical)",
+            )],
+            effect_mapping: HashMap::from([
+                ("Warning".to_string(), "PreferNoSchedule".to_string()),
+                ("Critical".to_string(), "NoExecute".to_string()),
+            ]),
+            ..Default::default()
+        }];
+        let mut handle = setup("list-nodes-severity.json", matchers).await;
+
+        let (request, response) = handle
+            .next_request()
+            .await
+            .expect("PUT node not called for aks-hermes1-41950716-vmss000082");
+        assert_eq!(request.method(), http::Method::PUT);
+        assert_eq!(
+            request.uri().to_string(),
+            "/api/v1/nodes/aks-hermes1-41950716-vmss000082?&fieldManager=tainter"
+        );
+        let node = node_from_body(request).await;
+        let taints = node.spec.unwrap().taints.unwrap();
+        assert_eq!(taints.len(), 1);
+        let taint = taints.first().unwrap();
+        assert_eq!(taint.effect, "PreferNoSchedule");
+        assert_eq!(taint.key, "hardware");
+        assert_eq!(taint.time_added, None);
+
+        response.send_response(
+            Response::builder()
+                .body(Body::from(
+                    get_test_file("node-put-success.json").into_bytes(),
+                ))
+                .unwrap(),
+        );
+
+        let (request, _) = handle
+            .next_request()
+            .await
+            .expect("PUT node not called for aks-hades1-41950716-vmss000082");
+        assert_eq!(request.method(), http::Method::PUT);
+        assert_eq!(
+            request.uri().to_string(),
+            "/api/v1/nodes/aks-hades1-41950716-vmss000082?&fieldManager=tainter"
+        );
+        let node = node_from_body(request).await;
+        let taints = node.spec.unwrap().taints.unwrap();
+        assert_eq!(taints.len(), 1);
+        let taint = taints.first().unwrap();
+        assert_eq!(taint.effect, "NoExecute");
+        assert_eq!(taint.key, "hardware");
+        assert!(taint.time_added.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_start_sets_time_added_from_clock() {
+        let matchers = vec![Configuration {
+            taints: vec![Taint {
+                effect: "NoExecute".to_string(),
+                key: "not-ready".to_string(),
+                time_added: None,
+                value: None,
+            }],
+            conditions: vec![condition("Ready", "False")],
+            ..Default::default()
+        }];
+        let mut handle = setup_with_clock(
+            "list-nodes-single-eligible.json",
+            matchers,
+            Options::default(),
+            Metrics::new(),
+            Box::new(FixedClock(fixed_time())),
+        )
+        .await;
+
+        let (request, _) = handle.next_request().await.expect("PUT node not called");
+        assert_eq!(request.method(), http::Method::PUT);
+        let node = node_from_body(request).await;
+        let taints = node.spec.unwrap().taints.unwrap();
+        let taint = taints.last().unwrap();
+        assert_eq!(taint.key, "not-ready");
+        assert_eq!(taint.time_added, Some(Time(fixed_time())));
+    }
+
+    #[tokio::test]
+    async fn test_start_renders_timestamp_into_taint_value() {
+        let matchers = vec![Configuration {
+            taints: vec![Taint {
+                effect: "NoSchedule".to_string(),
+                key: "not-ready".to_string(),
+                time_added: None,
+                value: Some("detected-${timestamp}".to_string()),
+            }],
+            conditions: vec![condition("Ready", "False")],
+            ..Default::default()
+        }];
+        let mut handle = setup_with_clock(
+            "list-nodes-single-eligible.json",
+            matchers,
+            Options::default(),
+            Metrics::new(),
+            Box::new(FixedClock(fixed_time())),
+        )
+        .await;
+
+        let (request, _) = handle.next_request().await.expect("PUT node not called");
+        assert_eq!(request.method(), http::Method::PUT);
+        let node = node_from_body(request).await;
+        let taints = node.spec.unwrap().taints.unwrap();
+        let value = taints.last().unwrap().value.clone().unwrap();
+        let timestamp = value.strip_prefix("detected-").unwrap();
+        let parsed = chrono::NaiveDateTime::parse_from_str(timestamp, TIMESTAMP_FORMAT).unwrap();
+        assert_eq!(fixed_time(), parsed.and_utc());
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_start_reports_shadow_matcher_divergence_without_applying_it() {
+        let matchers = vec![Configuration {
+            taints: vec![Taint {
+                effect: "NoSchedule".to_string(),
+                key: "unused".to_string(),
+                time_added: None,
+                value: None,
+            }],
+            conditions: vec![condition("NonExistentCondition", "True")],
+            ..Default::default()
+        }];
+        let options = Options {
+            shadow_matchers: vec![Configuration {
+                taints: vec![Taint {
+                    effect: "NoSchedule".to_string(),
+                    key: "not-ready".to_string(),
+                    time_added: None,
+                    value: None,
+                }],
+                conditions: vec![condition("Ready", "False")],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let metrics = Metrics::new();
+        let mut handle = setup_with_options(
+            "list-nodes-single-eligible.json",
+            matchers,
+            options,
+            metrics.clone(),
+        )
+        .await;
+
+        // The shadow matchers would taint the node, but only the watch request must follow.
+        let (request, _) = handle.next_request().await.expect("watch nodes not called");
+        assert_eq!(request.method(), http::Method::GET);
+        assert_eq!(
+            request.uri().to_string(),
+            "/api/v1/nodes?&watch=true&timeoutSeconds=290&\
+        allowWatchBookmarks=true&resourceVersion=test"
+        );
+
+        assert!(logs_contain(
+            r#"Shadow matchers would taint node differently node="aks-zeus1-41950716-vmss000082" taints="" shadow_taints="not-ready:NoSchedule""#
+        ));
+        assert_eq!(1, metrics.shadow_divergences.get());
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_start_does_not_taint_node_if_too_few_matchers_match() {
+        let matchers = vec![
+            Configuration {
+                taints: vec![Taint {
+                    effect: "NoSchedule".to_string(),
+                    key: "event".to_string(),
+                    time_added: None,
+                    value: None,
+                }],
+                conditions: vec![condition("VMEventScheduled", "True")],
+                ..Default::default()
+            },
+            Configuration {
+                taints: vec![Taint {
+                    effect: "NoSchedule".to_string(),
+                    key: "unused".to_string(),
+                    time_added: None,
+                    value: None,
+                }],
+                conditions: vec![condition("NonExistentCondition", "True")],
+                ..Default::default()
+            },
+        ];
+        let options = Options {
+            min_matching_matchers: Some(2),
+            ..Default::default()
+        };
+        let mut handle = setup_with_options(
+            "list-nodes-single-eligible.json",
+            matchers,
+            options,
+            Metrics::new(),
+        )
+        .await;
+
+        // Only one matcher matches, so only the watch request must follow.
+        let (request, _) = handle.next_request().await.expect("watch nodes not called");
+        assert_eq!(request.method(), http::Method::GET);
+        assert_eq!(
+            request.uri().to_string(),
+            "/api/v1/nodes?&watch=true&timeoutSeconds=290&\
+        allowWatchBookmarks=true&resourceVersion=test"
+        );
+
+        assert!(logs_contain(
+            r#"Not tainting node because too few matchers match it node="aks-zeus1-41950716-vmss000082" matching_matchers=1 min_matching_matchers=2"#
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_start_adds_taints_of_all_matchers_if_enough_matchers_match() {
+        let matchers = vec![
+            Configuration {
+                taints: vec![Taint {
+                    effect: "NoSchedule".to_string(),
+                    key: "event".to_string(),
+                    time_added: None,
+                    value: None,
+                }],
+                conditions: vec![condition("VMEventScheduled", "True")],
+                ..Default::default()
+            },
+            Configuration {
+                taints: vec![Taint {
+                    effect: "NoSchedule".to_string(),
+                    key: "not-ready".to_string(),
+                    time_added: None,
+                    value: None,
+                }],
+                conditions: vec![condition("Ready", "False")],
+                ..Default::default()
+            },
+        ];
+        let options = Options {
+            min_matching_matchers: Some(2),
+            ..Default::default()
+        };
+        let mut handle = setup_with_options(
+            "list-nodes-single-eligible.json",
+            matchers,
+            options,
+            Metrics::new(),
+        )
+        .await;
+
+        let (request, _) = handle.next_request().await.expect("PUT node not called");
+        assert_eq!(request.method(), http::Method::PUT);
+        let node = node_from_body(request).await;
+        let taints = node.spec.unwrap().taints.unwrap();
+        assert_eq!(taints.len(), 3);
+        assert_eq!(taints.get(1).unwrap().key, "event");
+        assert_eq!(taints.get(2).unwrap().key, "not-ready");
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_start_defers_no_execute_taint_when_disruption_budget_is_exhausted() {
+        let matchers = vec![Configuration {
+            taints: vec![Taint {
+                effect: "NoExecute".to_string(),
+                key: "pressure".to_string(),
+                time_added: None,
+                value: Some("memory".to_string()),
+            }],
+            conditions: vec![condition("OutOfMemory", "True")],
+            ..Default::default()
+        }];
+        let options = Options {
+            disruption_budgets: vec![DisruptionBudget {
+                selector: BTreeMap::from([("agentpool".to_string(), "hermes1".to_string())]),
+                max_tainted: MaxTainted::Count(1),
+            }],
+            ..Default::default()
+        };
+        let mut handle = setup_with_options(
+            "list-nodes-disruption-budget.json",
+            matchers,
+            options,
+            Metrics::new(),
+        )
+        .await;
+
+        // aks-hermes1-41950716-vmss000082 is already tainted, which exhausts the budget of the
+        // hermes1 group, so aks-hades1-41950716-vmss000082 is deferred. aks-zeus1-41950716-vmss000082
+        // is not part of a group with a budget.
+        let (request, _) = handle.next_request().await.expect("PUT node not called");
+        assert_eq!(request.method(), http::Method::PUT);
+        assert_eq!(
+            request.uri().to_string(),
+            "/api/v1/nodes/aks-zeus1-41950716-vmss000082?&fieldManager=tainter"
+        );
+
+        assert!(logs_contain(
+            r#"Deferring NoExecute taints because the disruption budget of the node's group is exhausted node="aks-hades1-41950716-vmss000082" selector="agentpool=hermes1" tainted_nodes=1 max_tainted_nodes=1"#
+        ));
+    }
+
+    #[test_case(MaxTainted::Count(2), 10, 2 ; "count ignores group size")]
+    #[test_case(MaxTainted::Percentage(25), 10, 3 ; "percentage rounds up")]
+    #[test_case(MaxTainted::Percentage(100), 3, 3 ; "percentage allows whole group")]
+    #[test_case(MaxTainted::Percentage(20), 3, 1 ; "percentage of small group allows one")]
+    #[test_case(MaxTainted::Percentage(0), 3, 0 ; "zero percentage allows none")]
+    fn test_max_tainted_allowed(max_tainted: MaxTainted, group_size: usize, expected: usize) {
+        assert_eq!(expected, max_tainted.allowed(group_size));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_start_lists_nodes_after_startup_jitter() {
+        let (mock_service, mut handle) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+        let client = Client::new(mock_service, "default");
+        let startup_jitter = std::time::Duration::from_secs(30);
+        let options = Options {
+            startup_jitter: Some(startup_jitter),
+            ..Default::default()
+        };
+        let reconciler =
+            Reconciler::new(client, vec![], options, Metrics::new(), Box::new(UtcClock));
+        let started = tokio::time::Instant::now();
+
+        tokio::spawn(async move {
+            reconciler.start().await;
+        });
+
+        let (request, _) = handle.next_request().await.expect("list nodes not called");
+        assert_eq!(request.uri().to_string(), "/api/v1/nodes?&limit=500");
+        // Time is paused, so the elapsed time is exactly the jitter.
+        assert!(started.elapsed() <= startup_jitter);
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_start_publishes_condition_after_adding_taints() {
+        let matchers = vec![Configuration {
+            taints: vec![Taint {
+                effect: "NoSchedule".to_string(),
+                key: "not-ready".to_string(),
+                time_added: None,
+                value: None,
+            }],
+            conditions: vec![condition("Ready", "False")],
+            ..Default::default()
+        }];
+        let options = Options {
+            publish_condition: true,
+            ..Default::default()
+        };
+        let mut handle = setup_with_clock(
+            "list-nodes-single-eligible.json",
+            matchers,
+            options,
+            Metrics::new(),
+            Box::new(FixedClock(fixed_time())),
+        )
+        .await;
+
+        let (request, response) = handle.next_request().await.expect("PUT node not called");
+        assert_eq!(request.method(), http::Method::PUT);
+        response.send_response(
+            Response::builder()
+                .body(Body::from(
+                    get_test_file("node-put-success.json").into_bytes(),
+                ))
+                .unwrap(),
+        );
+
+        let (request, response) = handle
+            .next_request()
+            .await
+            .expect("PATCH node status not called");
+        assert_eq!(request.method(), http::Method::PATCH);
+        assert_eq!(
+            request.uri().to_string(),
+            "/api/v1/nodes/aks-zeus1-41950716-vmss000082/status?&fieldManager=tainter"
+        );
+        assert_eq!(
+            request.headers().get("content-type").unwrap(),
+            "application/strategic-merge-patch+json"
+        );
+        let body = request.into_body().collect_bytes().await.unwrap();
+        let patch: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            patch,
+            serde_json::json!({
+                "status": {
+                    "conditions": [{
+                        "lastHeartbeatTime": "2024-05-12T11:21:10Z",
+                        "lastTransitionTime": "2024-05-12T11:21:10Z",
+                        "message": "Tainter added taints not-ready:NoSchedule",
+                        "reason": "TaintsAdded",
+                        "status": "True",
+                        "type": "TainterManaged"
+                    }]
+                }
+            })
+        );
+        response.send_response(
+            Response::builder()
+                .body(Body::from(
+                    get_test_file("node-put-success.json").into_bytes(),
+                ))
+                .unwrap(),
+        );
+
+        let (_, _) = handle.next_request().await.expect("watch nodes not called");
+        assert!(logs_contain("Successfully published node condition"));
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_start_sets_last_action_annotation_alongside_taint() {
+        let matchers = vec![Configuration {
+            taints: vec![Taint {
+                effect: "NoSchedule".to_string(),
+                key: "not-ready".to_string(),
+                time_added: None,
+                value: None,
+            }],
+            conditions: vec![condition("Ready", "False")],
+            ..Default::default()
+        }];
+        let options = Options {
+            last_action_annotation: Some("tainter.io/last-action".to_string()),
+            ..Default::default()
+        };
+        let mut handle = setup_with_clock(
+            "list-nodes-single-eligible.json",
+            matchers,
+            options,
+            Metrics::new(),
+            Box::new(FixedClock(fixed_time())),
+        )
+        .await;
+
+        let (request, response) = handle.next_request().await.expect("PUT node not called");
+        assert_eq!(request.method(), http::Method::PUT);
+        let node = node_from_body(request).await;
+        assert!(node
+            .spec
+            .unwrap()
+            .taints
+            .unwrap()
+            .iter()
+            .any(|taint| taint.key == "not-ready"));
+        assert_eq!(
+            "not-ready:NoSchedule added at 2024-05-12T11:21:10Z because Ready=False (KubeletReady)",
+            node.metadata
+                .annotations
+                .unwrap()
+                .get("tainter.io/last-action")
+                .unwrap()
+        );
+        response.send_response(
+            Response::builder()
+                .body(Body::from(
+                    get_test_file("node-put-success.json").into_bytes(),
+                ))
+                .unwrap(),
+        );
+
+        let (_, _) = handle.next_request().await.expect("watch nodes not called");
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_start_does_not_downgrade_taint_effect_by_default() {
+        let matchers = vec![Configuration {
+            taints: vec![Taint {
+                effect: "NoSchedule".to_string(),
+                key: "node.kubernetes.io/out-of-service".to_string(),
+                time_added: None,
+                value: None,
+            }],
+            conditions: vec![condition("Ready", "False|Unknown")],
+            ..Default::default()
+        }];
+        let mut handle = setup("list-nodes-eligible-and-has-managed-taint.json", matchers).await;
+
+        let (request, _) = handle.next_request().await.expect("watch nodes not called");
+        assert_eq!(request.method(), http::Method::GET);
+        assert_eq!(
+            request.uri().to_string(),
+            "/api/v1/nodes?&watch=true&timeoutSeconds=290&\
+        allowWatchBookmarks=true&resourceVersion=test"
+        );
+
+        assert!(logs_contain(
+            r#"Skipping downgrade of taint effect node="aks-artemis1-41950716-vmss000082" taint="node.kubernetes.io/out-of-service=spot:NoExecute" effect="NoSchedule""#
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_start_downgrades_taint_effect_when_allowed() {
+        let matchers = vec![Configuration {
+            taints: vec![Taint {
+                effect: "NoSchedule".to_string(),
+                key: "node.kubernetes.io/out-of-service".to_string(),
+                time_added: None,
+                value: None,
+            }],
+            conditions: vec![condition("Ready", "False|Unknown")],
+            ..Default::default()
+        }];
+        let options = Options {
+            effect_downgrade: EffectDowngrade::Allow,
+            ..Default::default()
+        };
+        let mut handle = setup_with_options(
+            "list-nodes-eligible-and-has-managed-taint.json",
+            matchers,
+            options,
+            Metrics::new(),
+        )
+        .await;
+
+        let (request, _) = handle.next_request().await.expect("PUT node not called");
+        assert_eq!(request.method(), http::Method::PUT);
+        let node = node_from_body(request).await;
+        let taints = node.spec.unwrap().taints.unwrap();
+        let out_of_service: Vec<&Taint> = taints
+            .iter()
+            .filter(|taint| taint.key == "node.kubernetes.io/out-of-service")
+            .collect();
+        assert_eq!(1, out_of_service.len());
+        assert_eq!("NoSchedule", out_of_service.first().unwrap().effect);
+    }
+
+    #[tokio::test]
+    async fn test_start_writes_decision_record_of_replaced_taint() {
+        let buffer = crate::decision_log::tests::Buffer::default();
+        let matchers = vec![Configuration {
+            name: Some("out-of-service".to_string()),
+            taints: vec![Taint {
+                effect: "NoSchedule".to_string(),
+                key: "node.kubernetes.io/out-of-service".to_string(),
+                time_added: None,
+                value: None,
+            }],
+            conditions: vec![condition("Ready", "False|Unknown")],
+            ..Default::default()
+        }];
+        let options = Options {
+            effect_downgrade: EffectDowngrade::Allow,
+            decision_log: Some(Arc::new(DecisionLog::new(buffer.clone()))),
+            ..Default::default()
+        };
+        let mut handle = setup_with_clock(
+            "list-nodes-eligible-and-has-managed-taint.json",
+            matchers,
+            options,
+            Metrics::new(),
+            Box::new(FixedClock(fixed_time())),
+        )
+        .await;
+
+        let (_, response) = handle.next_request().await.expect("PUT node not called");
+        response.send_response(
+            Response::builder()
+                .body(Body::from(
+                    get_test_file("node-put-success.json").into_bytes(),
+                ))
+                .unwrap(),
+        );
+        let (_, _) = handle.next_request().await.expect("watch nodes not called");
+
+        let removed: Vec<serde_json::Value> = buffer
+            .lines()
+            .into_iter()
+            .filter(|record| record["action"] == "Removed")
+            .collect();
+        assert_eq!(
+            vec![serde_json::json!({
+                "version": 1,
+                "node": "aks-artemis1-41950716-vmss000082",
+                "matcher": "out-of-service",
+                "action": "Removed",
+                "taint": {
+                    "effect": "NoExecute",
+                    "key": "node.kubernetes.io/out-of-service",
+                    "value": "spot",
+                },
+                "reason": "Replaced by a NoSchedule taint with the same key",
+                "timestamp": "2024-05-12T11:21:10Z",
+            })],
+            removed
+        );
+    }
+
+    #[test_case(EffectDowngrade::Forbid ; "downgrades forbidden")]
+    #[test_case(EffectDowngrade::Allow ; "downgrades allowed")]
+    #[tokio::test]
+    #[traced_test]
+    async fn test_start_keeps_managed_taint_of_matcher_with_two_effects(
+        effect_downgrade: EffectDowngrade,
+    ) {
+        let taint = |effect: &str| Taint {
+            effect: effect.to_string(),
+            key: "node.kubernetes.io/out-of-service".to_string(),
+            time_added: None,
+            value: Some("spot".to_string()),
+        };
+        let matchers = vec![Configuration {
+            taints: vec![taint("NoSchedule"), taint("NoExecute")],
+            conditions: vec![condition("Ready", "False|Unknown")],
+            ..Default::default()
+        }];
+        let options = Options {
+            effect_downgrade,
+            ..Default::default()
+        };
+        let mut handle = setup_with_options(
+            "list-nodes-eligible-and-has-managed-taint.json",
+            matchers,
+            options,
+            Metrics::new(),
+        )
+        .await;
+
+        // The node already has the matcher's NoExecute taint, which the matcher keeps, so the
+        // NoSchedule taint is added next to it rather than replacing it.
+        let (request, _) = handle.next_request().await.expect("PUT node not called");
+        assert_eq!(request.method(), http::Method::PUT);
+        let node = node_from_body(request).await;
+        let effects: Vec<String> = node
+            .spec
+            .unwrap()
+            .taints
+            .unwrap()
+            .into_iter()
+            .filter(|taint| taint.key == "node.kubernetes.io/out-of-service")
+            .map(|taint| taint.effect)
+            .collect();
+        assert_eq!(vec!["NoExecute", "NoSchedule"], effects);
+        assert!(!logs_contain("Skipping downgrade of taint effect"));
+    }
+
+    #[tokio::test]
+    async fn test_start_upgrades_taint_effect() {
+        let matchers = vec![Configuration {
+            taints: vec![Taint {
+                effect: "NoExecute".to_string(),
+                key: "kubernetes.azure.com/scalesetpriority".to_string(),
+                time_added: None,
+                value: Some("spot".to_string()),
+            }],
+            conditions: vec![condition("Ready", "False")],
+            ..Default::default()
+        }];
+        let mut handle = setup("list-nodes-eligible-and-has-managed-taint.json", matchers).await;
+
+        let (request, _) = handle.next_request().await.expect("PUT node not called");
+        assert_eq!(request.method(), http::Method::PUT);
+        let node = node_from_body(request).await;
+        let taints = node.spec.unwrap().taints.unwrap();
+        let scalesetpriority: Vec<&Taint> = taints
+            .iter()
+            .filter(|taint| taint.key == "kubernetes.azure.com/scalesetpriority")
+            .collect();
+        assert_eq!(1, scalesetpriority.len());
+        assert_eq!("NoExecute", scalesetpriority.first().unwrap().effect);
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_start_keeps_unmanaged_taint_with_same_key() {
+        let matchers = vec![Configuration {
+            taints: vec![Taint {
+                effect: "NoExecute".to_string(),
+                key: "kubernetes.azure.com/scalesetpriority".to_string(),
+                time_added: None,
+                value: Some("spot".to_string()),
+            }],
+            conditions: vec![condition("Ready", "False")],
+            ..Default::default()
+        }];
+        let mut handle = setup("list-nodes-single-eligible.json", matchers).await;
+
+        // The node's NoSchedule taint was not added by Tainter, so the NoExecute taint is added
+        // next to it rather than replacing it.
+        let (request, _) = handle.next_request().await.expect("PUT node not called");
+        assert_eq!(request.method(), http::Method::PUT);
+        let node = node_from_body(request).await;
+        let taints = node.spec.unwrap().taints.unwrap();
+        let effects: Vec<&str> = taints
+            .iter()
+            .filter(|taint| taint.key == "kubernetes.azure.com/scalesetpriority")
+            .map(|taint| taint.effect.as_str())
+            .collect();
+        assert_eq!(vec!["NoSchedule", "NoExecute"], effects);
+        assert!(!logs_contain("Skipping downgrade of taint effect"));
+    }
+
+    #[test_case("PreferNoSchedule", "NoSchedule" ; "prefer no schedule is less severe than no schedule")]
+    #[test_case("NoSchedule", "NoExecute" ; "no schedule is less severe than no execute")]
+    fn test_effect_severity(less_severe: &str, more_severe: &str) {
+        assert!(effect_severity(less_severe) < effect_severity(more_severe));
+    }
+
+    #[tokio::test]
+    async fn test_start_counts_matcher_eligibility() {
+        let matchers = vec![
+            Configuration {
+                taints: vec![Taint {
+                    effect: "NoSchedule".to_string(),
+                    key: "not-ready".to_string(),
+                    time_added: None,
+                    value: None,
+                }],
+                conditions: vec![condition("Ready", "False")],
+                ..Default::default()
+            },
+            Configuration {
+                taints: vec![Taint {
+                    effect: "NoSchedule".to_string(),
+                    key: "unused".to_string(),
+                    time_added: None,
+                    value: None,
+                }],
+                conditions: vec![condition("NonExistentCondition", "True")],
+                ..Default::default()
+            },
+        ];
+        let metrics = Metrics::new();
+        let mut handle = setup_with_options(
+            "list-nodes-single-eligible.json",
+            matchers,
+            Options::default(),
+            metrics.clone(),
+        )
+        .await;
+
+        let (request, _) = handle.next_request().await.expect("PUT node not called");
+        assert_eq!(request.method(), http::Method::PUT);
+
+        let eligible = |key: &str| metrics.matcher_eligible.with_label_values(&[key]).get();
+        let ineligible = |key: &str| metrics.matcher_ineligible.with_label_values(&[key]).get();
+        assert_eq!(1, eligible("not-ready"));
+        assert_eq!(0, ineligible("not-ready"));
+        assert_eq!(0, eligible("unused"));
+        assert_eq!(1, ineligible("unused"));
+    }
+
+    #[tokio::test]
+    async fn test_start_labels_matcher_metrics_with_name() {
+        let matchers = vec![Configuration {
+            name: Some("nic-failure".to_string()),
+            taints: vec![Taint {
+                effect: "NoSchedule".to_string(),
+                key: "example.com/network-interface-card-failure".to_string(),
+                time_added: None,
+                value: None,
+            }],
+            conditions: vec![condition("Ready", "False")],
+            ..Default::default()
+        }];
+        let metrics = Metrics::new();
+        let mut handle = setup_with_options(
+            "list-nodes-single-eligible.json",
+            matchers,
+            Options::default(),
+            metrics.clone(),
+        )
+        .await;
+
+        let (request, _) = handle.next_request().await.expect("PUT node not called");
+        assert_eq!(request.method(), http::Method::PUT);
+
+        assert_eq!(
+            1,
+            metrics
+                .matcher_eligible
+                .with_label_values(&["nic-failure"])
+                .get()
+        );
+        assert_eq!(
+            0,
+            metrics
+                .matcher_eligible
+                .with_label_values(&["example.com/network-interface-card-failure"])
+                .get()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_start_renders_named_captures_into_taint() {
+        let matchers = vec![Configuration {
+            taints: vec![Taint {
+                effect: "NoSchedule".to_string(),
+                key: "vm-${event}".to_string(),
+                time_added: None,
+                value: Some("${reason}".to_string()),
+            }],
+            conditions: vec![Condition {
+                reason: Some(Regex::new("(?P<reason>VMEvent[A-Za-z]+)").unwrap()),
+                message: Some(Regex::new(r"VM has (?P<event>\w+) event").unwrap()),
+                ..condition("VMEventScheduled", "True")
+            }],
+            ..Default::default()
+        }];
+        let mut handle = setup("list-nodes-single-eligible.json", matchers).await;
+
+        let (request, _) = handle.next_request().await.expect("PUT node not called");
+        assert_eq!(request.method(), http::Method::PUT);
+        let node = node_from_body(request).await;
+        let taints = node.spec.unwrap().taints.unwrap();
+        let taint = taints.last().unwrap();
+        assert_eq!("vm-scheduled", taint.key);
+        assert_eq!(Some("VMEventScheduled".to_string()), taint.value);
+    }
+
+    #[tokio::test]
+    async fn test_render_template_leaves_unknown_placeholders() {
+        let reconciler = reconciler();
+        let captures = HashMap::from([("zone".to_string(), "westeurope-1".to_string())]);
+
+        assert_eq!(
+            "westeurope-1/${rack}",
+            reconciler.render_template("${zone}/${rack}", &captures)
+        );
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_run_re_establishes_ended_watch_stream() {
+        let reconciler = reconciler();
+        let watches = AtomicUsize::new(0);
+
+        let watch = || {
+            // The first two streams end after a single restart, the third one never ends.
+            if watches.fetch_add(1, Ordering::SeqCst) < 2 {
+                stream::iter(relist(vec![])).boxed()
+            } else {
+                stream::pending().boxed()
+            }
+        };
+        let res =
+            tokio::time::timeout(std::time::Duration::from_millis(100), reconciler.run(watch))
+                .await;
+
+        assert!(res.is_err(), "run should never return");
+        assert_eq!(3, watches.load(Ordering::SeqCst));
+        assert_eq!(2, reconciler.metrics.watch_restarts.get());
+        assert!(logs_contain(
+            "Node watch stream ended unexpectedly, re-establishing it"
+        ));
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_consume_skips_malformed_nodes() {
+        let reconciler = reconciler();
+        let list: kube::api::ObjectList<Node> =
+            serde_json::from_str(&get_test_file("list-nodes-single-eligible.json")).unwrap();
+        let node = list.items.first().unwrap();
+        let mut without_spec = node.clone();
+        without_spec.spec = None;
+        let mut without_status = node.clone();
+        without_status.status = None;
+        let mut without_name = node.clone();
+        without_name.metadata.name = None;
+        let events = stream::iter(
+            [without_spec, without_status, without_name]
+                .into_iter()
+                .map(|node| Ok(watcher::Event::Apply(node)))
+                .collect::<Vec<Result<watcher::Event<Node>, watcher::Error>>>(),
+        );
+
+        reconciler.consume(events).await;
+
+        assert!(logs_contain(
+            "Skipping node without a spec node=\"aks-zeus1-41950716-vmss000082\""
+        ));
+        assert!(logs_contain(
+            "Skipping node without a status node=\"aks-zeus1-41950716-vmss000082\""
+        ));
+        assert!(logs_contain("Skipping node without a name"));
+        for reason in ["no_spec", "no_status", "no_name"] {
+            assert_eq!(
+                1,
+                reconciler
+                    .metrics
+                    .malformed_nodes
+                    .with_label_values(&[reason])
+                    .get()
+            );
+        }
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_consume_counts_nameless_nodes_of_restarts() {
+        let reconciler = reconciler();
+        let list: kube::api::ObjectList<Node> =
+            serde_json::from_str(&get_test_file("list-nodes-single-eligible.json")).unwrap();
+        let mut without_name = list.items.first().unwrap().clone();
+        without_name.metadata.name = None;
+        let events = stream::iter(relist(vec![without_name.clone(), without_name]));
+
+        reconciler.consume(events).await;
+
+        assert_eq!(
+            2,
+            reconciler
+                .metrics
+                .malformed_nodes
+                .with_label_values(&["no_name"])
+                .get()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_status_report_after_first_sweep() {
+        let reconciler = reconciler_with_clock(Box::new(FixedClock(fixed_time())));
+        let report = reconciler.status_report();
+        assert!(!report.ready);
+        assert_eq!(None, report.last_reconcile);
+
+        let list: kube::api::ObjectList<Node> =
+            serde_json::from_str(&get_test_file("list-nodes-single-eligible.json")).unwrap();
+        let node = list.items.first().unwrap();
+        let mut tainted = node.clone();
+        tainted.metadata.name = Some("aks-zeus1-41950716-vmss000083".to_string());
+        tainted
+            .metadata
+            .annotations
+            .get_or_insert_with(Default::default)
+            .insert(MANAGED_TAINTS_ANNOTATION.to_string(), "event".to_string());
+        let events = stream::iter(relist(vec![node.clone(), tainted]));
+        reconciler.consume(events).await;
+        reconciler.pause();
+
+        let report = reconciler.status_report();
+        assert!(report.ready);
+        assert!(report.paused);
+        assert_eq!("paused", report.status);
+        assert_eq!(Some(fixed_time()), report.last_reconcile);
+        assert_eq!(1, report.tainted_nodes);
+        assert_eq!(0, report.watch_errors);
+        assert_eq!(0, report.matcher_count);
+        assert_eq!(0, report.uptime_seconds);
+    }
+
+    #[tokio::test(start_paused = true)]
+    #[traced_test]
+    async fn test_consume_aggregates_node_logs_during_event_storm() {
+        let (mock_service, _) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+        let options = Options {
+            log_aggregation: Some(LogAggregation {
+                threshold: 2,
+                window: std::time::Duration::from_secs(10),
+            }),
+            ..Default::default()
+        };
+        let reconciler = Reconciler::new(
+            Client::new(mock_service, "default"),
+            vec![],
+            options,
+            Metrics::new(),
+            Box::new(UtcClock),
+        );
+        let list: kube::api::ObjectList<Node> =
+            serde_json::from_str(&get_test_file("list-nodes-single-eligible.json")).unwrap();
+        let node = list.items.first().unwrap().clone();
+        let events = |count: usize| {
+            stream::iter(
+                (0..count)
+                    .map(|_| Ok(watcher::Event::Apply(node.clone())))
+                    .collect::<Vec<Result<watcher::Event<Node>, watcher::Error>>>(),
+            )
+        };
+
+        reconciler.consume(events(5)).await;
+        tokio::time::advance(std::time::Duration::from_secs(10)).await;
+        reconciler.consume(events(1)).await;
+
+        assert!(logs_contain(
+            r#"Processing more nodes than the threshold within the window, aggregating node logs threshold=2 window="10s""#
+        ));
+        assert!(logs_contain(
+            r#"Summary of nodes processed without individual logs during event storm aggregated_nodes=3 tainted_nodes=0 window="10s""#
+        ));
+        // The first two nodes of the storm and the first node after it are logged individually.
+        logs_assert(|lines: &[&str]| {
+            let count = lines
+                .iter()
+                .filter(|line| line.contains(" INFO ") && line.contains("Processing node"))
+                .count();
+            match count {
+                3 => Ok(()),
+                count => Err(format!("expected 3 individual node logs, got {count}")),
+            }
+        });
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_consume_waits_between_errors() {
+        let (mock_service, _) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+        let options = Options {
+            error_retry_delay: std::time::Duration::from_millis(250),
+            ..Default::default()
+        };
+        let metrics = Metrics::new();
+        let reconciler = Reconciler::new(
+            Client::new(mock_service, "default"),
+            vec![],
+            options,
+            metrics.clone(),
+            Box::new(UtcClock),
+        );
+        let errors = stream::iter(
+            (0..4)
+                .map(|_| Err(watcher::Error::NoResourceVersion))
+                .collect::<Vec<Result<watcher::Event<Node>, watcher::Error>>>(),
+        );
+        let start = tokio::time::Instant::now();
+
+        reconciler.consume(errors).await;
+
+        assert_eq!(std::time::Duration::from_secs(1), start.elapsed());
+        assert_eq!(4, metrics.watch_errors.with_label_values(&["other"]).get());
+    }
+
+    #[tokio::test(start_paused = true)]
+    #[traced_test]
+    async fn test_consume_settles_after_forced_relist() {
+        let (mock_service, _) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+        let options = Options {
+            relist_settle: Some(std::time::Duration::from_secs(5)),
+            ..Default::default()
+        };
+        let metrics = Metrics::new();
+        let reconciler = Reconciler::new(
+            Client::new(mock_service, "default"),
+            vec![],
+            options,
+            metrics.clone(),
+            Box::new(UtcClock),
+        );
+        let events = || {
+            stream::iter(
+                vec![Err(watcher::Error::WatchError(
+                    kube::error::ErrorResponse {
+                        status: "Failure".to_string(),
+                        message: "too old resource version: 1906423380 (1906423999)".to_string(),
+                        reason: "Expired".to_string(),
+                        code: 410,
+                    },
+                ))]
+                .into_iter()
+                .chain(relist(vec![])),
+            )
+        };
+        let start = tokio::time::Instant::now();
+
+        reconciler.consume(events()).await;
+
+        assert_eq!(std::time::Duration::from_secs(5), start.elapsed());
+        assert_eq!(1, metrics.watch_relists.get());
+        assert_eq!(1, metrics.watch_restarts.get());
+        assert!(logs_contain(
+            "Node watch fell too far behind, forcing a full relist"
+        ));
+        assert!(logs_contain(
+            r#"Settling before processing relisted nodes nodes=0 settle="5s""#
+        ));
+
+        // Restarts that are not forced relists, e.g. the initial list, are processed right away.
+        let start = tokio::time::Instant::now();
+        reconciler.consume(stream::iter(relist(vec![]))).await;
+
+        assert_eq!(std::time::Duration::ZERO, start.elapsed());
+    }
+
+    #[tokio::test]
+    async fn test_process_node_updates_last_reconcile_timestamp() {
+        let (mock_service, _) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+        let metrics = Metrics::new();
+        let reconciler = Reconciler::new(
+            Client::new(mock_service, "default"),
+            vec![],
+            Options::default(),
+            metrics.clone(),
+            Box::new(FixedClock(fixed_time())),
+        );
+        let list: kube::api::ObjectList<Node> =
+            serde_json::from_str(&get_test_file("list-nodes-single-eligible.json")).unwrap();
+        assert_eq!(0, metrics.last_reconcile.get());
+
+        reconciler
+            .process_node(list.items.first().unwrap().clone(), false)
+            .await;
+
+        assert_eq!(fixed_time().timestamp(), metrics.last_reconcile.get());
+    }
+
+    #[test_case(Some(10), true, true ; "transition long before generation change")]
+    #[test_case(Some(5), true, false ; "transition exactly tolerance before generation change")]
+    #[test_case(Some(-1), true, false ; "transition after generation change")]
+    #[test_case(None, true, false ; "condition without transition time")]
+    #[test_case(Some(10), false, false ; "generation change time unknown")]
+    #[tokio::test]
+    async fn test_stale_condition(
+        minutes_before_change: Option<i64>,
+        generation_change_known: bool,
+        expected: bool,
+    ) {
+        let (mock_service, _) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+        let reconciler = Reconciler::new(
+            Client::new(mock_service, "default"),
+            vec![],
+            Options {
+                stale_condition_tolerance: Some(chrono::Duration::minutes(5)),
+                ..Default::default()
+            },
+            Metrics::new(),
+            Box::new(FixedClock(fixed_time())),
+        );
+        let node_condition = NodeCondition {
+            last_transition_time: minutes_before_change
+                .map(|minutes| Time(fixed_time() - chrono::Duration::minutes(minutes))),
+            status: "True".to_string(),
+            type_: "KernelDeadlock".to_string(),
+            ..Default::default()
+        };
+        let generation_changed_at = generation_change_known.then(fixed_time);
+
+        let stale = reconciler.stale_condition(&[&node_condition], generation_changed_at);
+
+        assert_eq!(expected, stale.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_observe_generation() {
+        let (mock_service, _) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+        let reconciler = Reconciler::new(
+            Client::new(mock_service, "default"),
+            vec![],
+            Options {
+                stale_condition_tolerance: Some(chrono::Duration::minutes(5)),
+                ..Default::default()
+            },
+            Metrics::new(),
+            Box::new(FixedClock(fixed_time())),
+        );
+
+        // The time of the change is unknown for the generation that the node had when first seen.
+        reconciler.observe_generation("node", Some(1));
+        assert_eq!(None, reconciler.generation_changed_at("node"));
+        reconciler.observe_generation("node", Some(1));
+        assert_eq!(None, reconciler.generation_changed_at("node"));
+        reconciler.observe_generation("node", Some(2));
+        assert_eq!(Some(fixed_time()), reconciler.generation_changed_at("node"));
+        reconciler.observe_generation("node", Some(2));
+        assert_eq!(Some(fixed_time()), reconciler.generation_changed_at("node"));
+        reconciler.observe_generation("other", None);
+        assert_eq!(None, reconciler.generation_changed_at("other"));
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_start_skips_group_if_dry_run_of_a_node_fails() {
+        let matchers = vec![Configuration {
+            taints: vec![Taint {
+                effect: "NoSchedule".to_string(),
+                key: "pressure".to_string(),
+                time_added: None,
+                value: Some("memory".to_string()),
+            }],
+            conditions: vec![condition("OutOfMemory", "True")],
+            ..Default::default()
+        }];
+        let options = Options {
+            group_by: Some("topology.kubernetes.io/zone".to_string()),
+            ..Default::default()
+        };
+        let mut handle =
+            setup_with_options("list-nodes-group.json", matchers, options, Metrics::new()).await;
+
+        let (request, response) = handle.next_request().await.expect("PUT node not called");
+        assert_eq!(request.method(), http::Method::PUT);
+        assert_eq!(
+            request.uri().to_string(),
+            "/api/v1/nodes/aks-hera1-41950716-vmss000082?&dryRun=All&fieldManager=tainter"
+        );
+        response.send_response(
+            Response::builder()
+                .body(Body::from(
+                    get_test_file("node-put-success.json").into_bytes(),
+                ))
+                .unwrap(),
+        );
+
+        let (request, response) = handle.next_request().await.expect("PUT node not called");
+        assert_eq!(request.method(), http::Method::PUT);
+        assert_eq!(
+            request.uri().to_string(),
+            "/api/v1/nodes/aks-hera2-41950716-vmss000082?&dryRun=All&fieldManager=tainter"
+        );
+        response.send_response(
+            Response::builder()
+                .status(409)
+                .body(Body::from(
+                    get_test_file("node-put-conflict-response.json").into_bytes(),
+                ))
+                .unwrap(),
+        );
+
+        // Neither node of the group is updated.
+        let (request, _) = handle.next_request().await.expect("watch nodes not called");
+        assert_eq!(request.method(), http::Method::GET);
+        assert_eq!(
+            request.uri().to_string(),
+            "/api/v1/nodes?&watch=true&timeoutSeconds=290&\
+        allowWatchBookmarks=true&resourceVersion=test"
+        );
+
+        assert!(logs_contain(
+            r#"Skipping group because the dry run of one of its nodes failed group="westeurope-1" node="aks-hera2-41950716-vmss000082" nodes=2"#
+        ));
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_consume_defers_updates_until_min_nodes_are_observed() {
+        let (mock_service, mut handle) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+        let matchers = vec![Configuration {
+            taints: vec![Taint {
+                effect: "NoSchedule".to_string(),
+                key: "event".to_string(),
+                time_added: None,
+                value: None,
+            }],
+            conditions: vec![condition("VMEventScheduled", "True")],
+            ..Default::default()
+        }];
+        let options = Options {
+            min_nodes_before_acting: Some(2),
+            ..Default::default()
+        };
+        let reconciler = Arc::new(Reconciler::new(
+            Client::new(mock_service, "default"),
+            matchers,
+            options,
+            Metrics::new(),
+            Box::new(UtcClock),
+        ));
+        let list: kube::api::ObjectList<Node> =
+            serde_json::from_str(&get_test_file("list-nodes-single-eligible.json")).unwrap();
+        let node = list.items.first().unwrap().clone();
+        let mut other = node.clone();
+        other.metadata.name = Some("aks-zeus1-41950716-vmss000083".to_string());
+
+        // The node is eligible, but no PUT is sent below the threshold, so consuming completes
+        // without a response from the mock.
+        reconciler
+            .consume(stream::iter(relist(vec![node.clone()])))
+            .await;
+        assert!(logs_contain(
+            "Deferring update of node until enough nodes are observed"
+        ));
+        assert!(logs_contain("node_count=1 min_nodes_before_acting=2"));
+        assert!(!reconciler.status_report().ready);
+
+        let consuming = reconciler.clone();
+        tokio::spawn(async move {
+            consuming
+                .consume(stream::iter(relist(vec![node, other])))
+                .await;
+        });
+
+        let (request, _) = handle.next_request().await.expect("PUT node not called");
+        assert_eq!(request.method(), http::Method::PUT);
+        assert!(request
+            .uri()
+            .path()
+            .starts_with("/api/v1/nodes/aks-zeus1-41950716-vmss00008"));
+    }
+
+    #[test_case(25, 0, 25.0 ; "initial percentage at start")]
+    #[test_case(25, 30, 62.5 ; "halfway through ramp")]
+    #[test_case(25, 60, 100.0 ; "end of ramp")]
+    #[test_case(25, 90, 100.0 ; "after ramp")]
+    #[test_case(0, 15, 25.0 ; "zero initial percentage")]
+    fn test_rollout_percentage(initial_percentage: u8, elapsed_minutes: i64, expected: f64) {
+        let rollout = Rollout {
+            initial_percentage,
+            ramp_duration: chrono::Duration::minutes(60),
+        };
+        assert_eq!(
+            expected,
+            rollout.percentage(chrono::Duration::minutes(elapsed_minutes))
+        );
+    }
+
+    #[test]
+    fn test_rollout_selects_configured_fraction_of_nodes() {
+        let rollout = Rollout {
+            initial_percentage: 10,
+            ramp_duration: chrono::Duration::minutes(60),
+        };
+        let nodes: Vec<String> = (0..1000)
+            .map(|index| format!("aks-zeus1-41950716-vmss{:06}", index))
+            .collect();
+        let selected = |percentage: f64| -> BTreeSet<&str> {
+            nodes
+                .iter()
+                .map(String::as_str)
+                .filter(|node| rollout.selects(node, percentage))
+                .collect()
+        };
+
+        let mut previous = BTreeSet::new();
+        for stage in [10.0, 25.0, 50.0, 75.0, 100.0] {
+            let current = selected(stage);
+            let expected = stage * 10.0;
+            assert!(
+                (current.len() as f64 - expected).abs() <= 50.0,
+                "selected {} nodes at {}%",
+                current.len(),
+                stage
+            );
+            // Nodes that were selected at an earlier stage stay selected.
+            assert!(previous.is_subset(&current));
+            previous = current;
+        }
+        assert_eq!(1000, previous.len());
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_process_node_holds_back_taints_of_node_not_selected_by_rollout() {
+        let (mock_service, _) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+        let matchers = vec![Configuration {
+            taints: vec![Taint {
+                effect: "NoSchedule".to_string(),
+                key: "event".to_string(),
+                time_added: None,
+                value: None,
+            }],
+            conditions: vec![condition("VMEventScheduled", "True")],
+            ..Default::default()
+        }];
+        let options = Options {
+            rollout: Some(Rollout {
+                initial_percentage: 50,
+                ramp_duration: chrono::Duration::hours(1),
+            }),
+            ..Default::default()
+        };
+        let reconciler = Reconciler::new(
+            Client::new(mock_service, "default"),
+            matchers,
+            options,
+            Metrics::new(),
+            Box::new(FixedClock(fixed_time())),
+        );
+        let list: kube::api::ObjectList<Node> =
+            serde_json::from_str(&get_test_file("list-nodes-single-eligible.json")).unwrap();
+        let node = list.items.first().unwrap().clone();
+
+        // The node's name hashes into the upper half, so no PUT is sent at 50%, and processing
+        // completes without a response from the mock.
+        assert!(!reconciler.process_node(node, false).await);
+        assert!(logs_contain(
+            "Holding back taints of node that the rollout has not selected yet"
+        ));
+        assert!(logs_contain(r#"rollout_percentage="50.0""#));
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_process_node_only_taints_on_transition() {
+        let (mock_service, mut handle) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+        let matchers = vec![Configuration {
+            taints: vec![Taint {
+                effect: "NoSchedule".to_string(),
+                key: "event".to_string(),
+                time_added: None,
+                value: None,
+            }],
+            conditions: vec![condition("VMEventScheduled", "True")],
+            on_transition_only: true,
+            ..Default::default()
+        }];
+        let reconciler = Arc::new(Reconciler::new(
+            Client::new(mock_service, "default"),
+            matchers,
+            Options::default(),
+            Metrics::new(),
+            Box::new(UtcClock),
+        ));
+        let list: kube::api::ObjectList<Node> =
+            serde_json::from_str(&get_test_file("list-nodes-single-eligible.json")).unwrap();
+        let node_with_status = |status: &str| {
+            let mut node = list.items.first().unwrap().clone();
+            for condition in node.status.as_mut().unwrap().conditions.as_mut().unwrap() {
+                if condition.type_ == "VMEventScheduled" {
+                    condition.status = status.to_string();
+                }
+            }
+            node
+        };
+
+        // The node has matched since Tainter first observed it, so no PUT is sent and processing
+        // completes without a response from the mock.
+        assert!(
+            !reconciler
+                .process_node(node_with_status("True"), false)
+                .await
+        );
+        assert!(
+            !reconciler
+                .process_node(node_with_status("True"), false)
+                .await
+        );
+        assert!(logs_contain(
+            "Skipping taint because the matcher's conditions did not transition"
+        ));
+        assert!(
+            !reconciler
+                .process_node(node_with_status("False"), false)
+                .await
+        );
+
+        let transitioned = reconciler.clone();
+        let node = node_with_status("True");
+        tokio::spawn(async move {
+            transitioned.process_node(node, false).await;
+        });
+
+        let (request, _) = handle.next_request().await.expect("PUT node not called");
+        assert_eq!(request.method(), http::Method::PUT);
+        let node = node_from_body(request).await;
+        let keys: Vec<String> = node
+            .spec
+            .unwrap()
+            .taints
+            .unwrap()
+            .into_iter()
+            .map(|taint| taint.key)
+            .collect();
+        assert!(keys.contains(&"event".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_process_node_keeps_transition_that_did_not_land() {
+        let (mock_service, mut handle) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+        let matchers = vec![Configuration {
+            taints: vec![Taint {
+                effect: "NoSchedule".to_string(),
+                key: "event".to_string(),
+                time_added: None,
+                value: None,
+            }],
+            conditions: vec![condition("VMEventScheduled", "True")],
+            on_transition_only: true,
+            ..Default::default()
+        }];
+        let reconciler = Arc::new(Reconciler::new(
+            Client::new(mock_service, "default"),
+            matchers,
+            Options::default(),
+            Metrics::new(),
+            Box::new(UtcClock),
+        ));
+        let list: kube::api::ObjectList<Node> =
+            serde_json::from_str(&get_test_file("list-nodes-single-eligible.json")).unwrap();
+        let node_with_status = |status: &str| {
+            let mut node = list.items.first().unwrap().clone();
+            for condition in node.status.as_mut().unwrap().conditions.as_mut().unwrap() {
+                if condition.type_ == "VMEventScheduled" {
+                    condition.status = status.to_string();
+                }
+            }
+            node
+        };
+
+        assert!(
+            !reconciler
+                .process_node(node_with_status("False"), false)
+                .await
+        );
+        // The condition transitions while reconciliation is paused, so the taint does not land.
+        reconciler.pause();
+        assert!(
+            !reconciler
+                .process_node(node_with_status("True"), false)
+                .await
+        );
+        reconciler.resume();
+
+        let transitioned = reconciler.clone();
+        let node = node_with_status("True");
+        tokio::spawn(async move {
+            transitioned.process_node(node, false).await;
+        });
+
+        let (request, _) = handle.next_request().await.expect("PUT node not called");
+        assert_eq!(request.method(), http::Method::PUT);
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_process_node_defers_updates_within_node_write_cooldown() {
+        let (mock_service, mut handle) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+        let matchers = vec![Configuration {
+            taints: vec![Taint {
+                effect: "NoSchedule".to_string(),
+                key: "event".to_string(),
+                time_added: None,
+                value: None,
+            }],
+            conditions: vec![condition("VMEventScheduled", "True")],
+            ..Default::default()
+        }];
+        let options = Options {
+            node_write_cooldown: Some(chrono::Duration::minutes(5)),
+            ..Default::default()
+        };
+        let reconciler = Arc::new(Reconciler::new(
+            Client::new(mock_service, "default"),
+            matchers,
+            options,
+            Metrics::new(),
+            Box::new(UtcClock),
+        ));
+        let list: kube::api::ObjectList<Node> =
+            serde_json::from_str(&get_test_file("list-nodes-single-eligible.json")).unwrap();
+        let node = list.items.first().unwrap().clone();
+
+        let first = reconciler.clone();
+        let first_node = node.clone();
+        let processing = tokio::spawn(async move { first.process_node(first_node, false).await });
+        let (request, response) = handle.next_request().await.expect("PUT node not called");
+        assert_eq!(request.method(), http::Method::PUT);
+        response.send_response(
+            Response::builder()
+                .body(Body::from(
+                    get_test_file("node-put-success.json").into_bytes(),
+                ))
+                .unwrap(),
+        );
+        assert!(processing.await.unwrap());
+
+        // Rapid events of the same node within the cooldown don't send another PUT, so processing
+        // completes without a response from the mock.
+        for _ in 0..3 {
+            assert!(!reconciler.process_node(node.clone(), false).await);
+        }
+        assert!(logs_contain(
+            "Deferring update of node until its write cooldown elapses"
+        ));
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_process_node_skips_updates_while_paused() {
+        let (mock_service, mut handle) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+        let matchers = vec![Configuration {
+            taints: vec![Taint {
+                effect: "NoSchedule".to_string(),
+                key: "event".to_string(),
+                time_added: None,
+                value: None,
+            }],
+            conditions: vec![condition("VMEventScheduled", "True")],
+            ..Default::default()
+        }];
+        let reconciler = Arc::new(Reconciler::new(
+            Client::new(mock_service, "default"),
+            matchers,
+            Options::default(),
+            Metrics::new(),
+            Box::new(UtcClock),
+        ));
+        let list: kube::api::ObjectList<Node> =
+            serde_json::from_str(&get_test_file("list-nodes-single-eligible.json")).unwrap();
+        let node = list.items.first().unwrap().clone();
+
+        // The node is eligible, but no PUT is sent while paused, so processing completes without
+        // a response from the mock.
+        reconciler.pause();
+        assert!(reconciler.is_paused());
+        assert!(!reconciler.process_node(node.clone(), false).await);
+        assert!(logs_contain(
+            "Skipping update of node because reconciliation is paused"
+        ));
+
+        reconciler.resume();
+        assert!(!reconciler.is_paused());
+        let resumed = reconciler.clone();
+        tokio::spawn(async move {
+            resumed.process_node(node, false).await;
+        });
+
+        let (request, _) = handle.next_request().await.expect("PUT node not called");
+        assert_eq!(request.method(), http::Method::PUT);
+        assert_eq!(
+            request.uri().to_string(),
+            "/api/v1/nodes/aks-zeus1-41950716-vmss000082?&fieldManager=tainter"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_start_prunes_orphaned_managed_taints() {
+        let matchers = vec![Configuration {
+            taints: vec![Taint {
+                effect: "NoExecute".to_string(),
+                key: "pressure".to_string(),
+                time_added: None,
+                value: Some("memory".to_string()),
+            }],
+            conditions: vec![condition("OutOfMemory", "True")],
+            ..Default::default()
+        }];
+        let options = Options {
+            prune_orphaned_taints: true,
+            ..Default::default()
+        };
+        let mut handle = setup_with_options(
+            "list-nodes-orphaned-taint.json",
+            matchers,
+            options,
+            Metrics::new(),
+        )
+        .await;
+
+        let (request, _) = handle.next_request().await.expect("PUT node not called");
+        assert_eq!(request.method(), http::Method::PUT);
+        assert_eq!(
+            request.uri().to_string(),
+            "/api/v1/nodes/aks-hera1-41950716-vmss000082?&fieldManager=tainter"
+        );
+        let node = node_from_body(request).await;
+        let keys: Vec<String> = node
+            .spec
+            .unwrap()
+            .taints
+            .unwrap()
+            .into_iter()
+            .map(|taint| taint.key)
+            .collect();
+        // The taint that Tainter does not manage is left alone.
+        assert_eq!(
+            vec!["pressure", "kubernetes.azure.com/scalesetpriority"],
+            keys
+        );
+        let annotations = node.metadata.annotations.unwrap();
+        assert_eq!(
+            "pressure",
+            annotations.get(MANAGED_TAINTS_ANNOTATION).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_start_prunes_orphaned_managed_taints_with_json_patch() {
+        let options = Options {
+            prune_orphaned_taints: true,
+            update_strategy: UpdateStrategy::JsonPatch,
+            ..Default::default()
+        };
+        let mut handle = setup_with_options(
+            "list-nodes-orphaned-taint.json",
+            vec![],
+            options,
+            Metrics::new(),
+        )
+        .await;
+
+        let (request, _) = handle.next_request().await.expect("PATCH node not called");
+        assert_eq!(request.method(), http::Method::PATCH);
+        assert_eq!(
+            request.uri().to_string(),
+            "/api/v1/nodes/aks-hera1-41950716-vmss000082?&fieldManager=tainter"
+        );
+        let body = request.into_body().collect_bytes().await.unwrap();
+        let patch: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!("/spec/taints", patch[1]["path"]);
+        assert_eq!(1, patch[1]["value"].as_array().unwrap().len());
+        assert_eq!(
+            "kubernetes.azure.com/scalesetpriority",
+            patch[1]["value"][0]["key"]
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    #[traced_test]
+    async fn test_prune_periodically_removes_orphaned_taints_without_node_events() {
+        let (mock_service, mut handle) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+        let options = Options {
+            prune_orphaned_taints: true,
+            prune_interval: Some(std::time::Duration::from_secs(600)),
+            ..Default::default()
+        };
+        let reconciler = Arc::new(Reconciler::new(
+            Client::new(mock_service, "default"),
+            vec![],
+            options,
+            Metrics::new(),
+            Box::new(UtcClock),
+        ));
+        let list: kube::api::ObjectList<Node> =
+            serde_json::from_str(&get_test_file("list-nodes-orphaned-taint.json")).unwrap();
+        // The node is known from the initial list, after which it never changes.
+        for event in relist(list.items) {
+            reconciler
+                .store_writer
+                .lock()
+                .unwrap()
+                .apply_watcher_event(&event.unwrap());
+        }
+
+        let pruning = reconciler.clone();
+        tokio::spawn(async move {
+            pruning.prune_periodically().await;
+        });
+        let start = tokio::time::Instant::now();
+
+        let (request, _) = handle.next_request().await.expect("PUT node not called");
+        assert_eq!(std::time::Duration::from_secs(600), start.elapsed());
+        assert_eq!(request.method(), http::Method::PUT);
+        assert_eq!(
+            request.uri().to_string(),
+            "/api/v1/nodes/aks-hera1-41950716-vmss000082?&fieldManager=tainter"
+        );
+        let node = node_from_body(request).await;
+        let keys: Vec<String> = node
+            .spec
+            .unwrap()
+            .taints
+            .unwrap()
+            .into_iter()
+            .map(|taint| taint.key)
+            .collect();
+        assert_eq!(vec!["kubernetes.azure.com/scalesetpriority"], keys);
+        assert!(logs_contain("Pruning orphaned taints from all nodes"));
+    }
+
+    #[tokio::test]
+    async fn test_start_records_managed_taint_keys() {
+        let matchers = vec![Configuration {
+            taints: vec![Taint {
+                effect: "NoExecute".to_string(),
+                key: "pressure".to_string(),
+                time_added: None,
+                value: Some("memory".to_string()),
+            }],
+            conditions: vec![condition("VMEventScheduled", "True")],
+            ..Default::default()
+        }];
+        let mut handle = setup("list-nodes-single-eligible.json", matchers).await;
+
+        let (request, _) = handle.next_request().await.expect("PUT node not called");
+        let node = node_from_body(request).await;
+        let annotations = node.metadata.annotations.unwrap();
+        assert_eq!(
+            "pressure",
+            annotations.get(MANAGED_TAINTS_ANNOTATION).unwrap()
+        );
+    }
+
+    #[test_case("pressure", "pressure", true ; "identical key")]
+    #[test_case("pressure", "hardware", false ; "other key")]
+    #[test_case("hardware-gpu", "hardware-${component}", true ; "placeholder")]
+    #[test_case("hardware-", "hardware-${component}", false ; "empty placeholder")]
+    #[test_case("tainter.io/gpu-faulty", "tainter.io/${component}-${state}", true ; "multiple placeholders")]
+    #[test_case("tainter.io/faulty", "tainter.io/${component}-${state}", false ; "missing literal")]
+    fn test_key_matches_template(key: &str, template: &str, expected: bool) {
+        assert_eq!(expected, key_matches_template(key, template));
+    }
+
+    #[test_case("hardware-gpu", "NoExecute", true ; "rendered key")]
+    #[test_case("hardware-gpu", "NoSchedule", false ; "rendered key without NoExecute")]
+    #[test_case("pressure", "NoExecute", false ; "key of no matcher")]
+    #[tokio::test]
+    async fn test_is_disrupted_matches_rendered_keys(key: &str, effect: &str, disrupted: bool) {
+        let (mock_service, _) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+        let matchers = vec![Configuration {
+            taints: vec![Taint {
+                effect: "NoExecute".to_string(),
+                key: "hardware-${component}".to_string(),
+                time_added: None,
+                value: None,
+            }],
+            ..Default::default()
+        }];
+        let reconciler = Reconciler::new(
+            Client::new(mock_service, "default"),
+            matchers,
+            Options::default(),
+            Metrics::new(),
+            Box::new(UtcClock),
+        );
+        let node = Node {
+            spec: Some(NodeSpec {
+                taints: Some(vec![Taint {
+                    effect: effect.to_string(),
+                    key: key.to_string(),
+                    time_added: None,
+                    value: None,
+                }]),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        assert_eq!(disrupted, reconciler.is_disrupted(&node));
+    }
+
+    #[test_case(None, &["event"] ; "reconciler")]
+    #[test_case(Some("hardware"), &["hardware"] ; "profile")]
+    #[test_case(Some("network"), &[] ; "other profile")]
+    #[tokio::test]
+    async fn test_managed_taint_keys_are_namespaced_per_profile(
+        profile: Option<&str>,
+        expected: &[&str],
+    ) {
+        let (mock_service, _) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+        let options = Options {
+            profile: profile.map(String::from),
+            ..Default::default()
+        };
+        let reconciler = Reconciler::new(
+            Client::new(mock_service, "default"),
+            vec![],
+            options,
+            Metrics::new(),
+            Box::new(UtcClock),
+        );
+        let metadata = ObjectMeta {
+            annotations: Some(BTreeMap::from([
+                (MANAGED_TAINTS_ANNOTATION.to_string(), "event".to_string()),
+                (
+                    format!("{MANAGED_TAINTS_ANNOTATION}.hardware"),
+                    "hardware".to_string(),
+                ),
+            ])),
+            ..Default::default()
+        };
+
+        let keys: Vec<String> = reconciler
+            .managed_taint_keys(&metadata)
+            .into_iter()
+            .collect();
+        assert_eq!(expected, keys);
+    }
+
+    #[test_case("node.kubernetes.io/out-of-service", true ; "subdomain of kubernetes.io")]
+    #[test_case("k8s.io/taint", true ; "k8s.io")]
+    #[test_case("tainter.io/event", false ; "other domain")]
+    #[test_case("notkubernetes.io/event", false ; "domain ending in kubernetes.io")]
+    #[test_case("event", false ; "no prefix")]
+    fn test_is_reserved_key(key: &str, expected: bool) {
+        assert_eq!(expected, is_reserved_key(key));
+    }
+
+    #[tokio::test]
+    async fn test_config_report() {
+        let taint = |key: &str| Taint {
+            effect: "NoSchedule".to_string(),
+            key: key.to_string(),
+            time_added: None,
+            value: None,
+        };
+        let (mock_service, _) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+        let matchers = vec![
+            Configuration {
+                taints: vec![taint("node.kubernetes.io/out-of-service")],
+                conditions: vec![condition("Ready", "False"), condition("Shutdown", "True")],
+                ..Default::default()
+            },
+            Configuration {
+                taints: vec![taint("event")],
+                conditions: vec![condition("VMEventScheduled", "True")],
+                ..Default::default()
+            },
+            // Names don't tell matchers apart.
+            Configuration {
+                name: Some("scheduled-event".to_string()),
+                taints: vec![taint("event")],
+                conditions: vec![condition("VMEventScheduled", "True")],
+                ..Default::default()
+            },
+            Configuration {
+                taints: vec![],
+                conditions: vec![condition("VMEventScheduled", "True")],
+                ..Default::default()
+            },
+            Configuration {
+                taints: vec![taint("event")],
+                conditions: vec![Condition {
+                    reason: Some(Regex::new("Freeze").unwrap()),
+                    ..condition("VMEventScheduled", "True")
+                }],
+                ..Default::default()
+            },
+        ];
+        let reconciler = Reconciler::new(
+            Client::new(mock_service, "default"),
+            matchers,
+            Options::default(),
+            Metrics::new(),
+            Box::new(UtcClock),
+        );
+
+        let report = reconciler.config_report();
+
+        assert!(!report.valid);
+        assert_eq!(5, report.matchers);
+        assert_eq!(6, report.conditions);
+        assert_eq!(vec!["matcher 3 has no taints".to_string()], report.errors);
+        assert_eq!(
+            vec![
+                "matcher 0 uses taint key node.kubernetes.io/out-of-service of a reserved \
+                Kubernetes domain"
+                    .to_string(),
+                "matcher 2 duplicates matcher 1".to_string(),
+            ],
+            report.warnings
+        );
+    }
+
+    #[tokio::test]
+    async fn test_start_counts_watch_restarts() {
+        let metrics = Metrics::new();
+        let mut handle = setup_with_options(
+            "list-nodes-single-eligible.json",
+            vec![],
+            Options::default(),
+            metrics.clone(),
+        )
+        .await;
+
+        let (_, _) = handle.next_request().await.expect("watch nodes not called");
+
+        assert_eq!(1, metrics.watch_restarts.get());
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_start_processes_all_pages_of_initial_list() {
+        let matchers = vec![Configuration {
+            taints: vec![Taint {
+                effect: "NoSchedule".to_string(),
+                key: "event".to_string(),
+                time_added: None,
+                value: None,
+            }],
+            conditions: vec![condition("VMEventScheduled", "True")],
+            ..Default::default()
+        }];
+        let mut handle = setup("list-nodes-page-1.json", matchers).await;
+
+        let (request, response) = handle
+            .next_request()
+            .await
+            .expect("list nodes not called for second page");
+        assert_eq!(request.method(), http::Method::GET);
+        assert_eq!(
+            request.uri().to_string(),
+            "/api/v1/nodes?&limit=500&continue=page-2"
+        );
+        response.send_response(
+            Response::builder()
+                .body(Body::from(
+                    get_test_file("list-nodes-page-2.json").into_bytes(),
+                ))
+                .unwrap(),
+        );
+
+        for node_name in [
+            "aks-apollo1-41950716-vmss000082",
+            "aks-apollo1-41950716-vmss000083",
+        ] {
+            let (request, response) = handle
+                .next_request()
+                .await
+                .unwrap_or_else(|| panic!("PUT node not called for {}", node_name));
+            assert_eq!(request.method(), http::Method::PUT);
+            assert_eq!(
+                request.uri().to_string(),
+                format!("/api/v1/nodes/{}?&fieldManager=tainter", node_name)
+            );
+            response.send_response(
+                Response::builder()
+                    .body(Body::from(
+                        get_test_file("node-put-success.json").into_bytes(),
+                    ))
+                    .unwrap(),
+            );
+        }
+
+        let (request, _) = handle.next_request().await.expect("watch nodes not called");
+        assert_eq!(request.method(), http::Method::GET);
+
+        assert!(logs_contain(
+            "Listed all nodes, starting sweep node_count=2"
+        ));
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_start_skips_cordoned_node_when_configured() {
+        let matchers = vec![Configuration {
+            taints: vec![Taint {
+                effect: "NoExecute".to_string(),
+                key: "event".to_string(),
+                time_added: None,
+                value: None,
+            }],
+            conditions: vec![condition("VMEventScheduled", "True")],
+            ..Default::default()
+        }];
+        let options = Options {
+            skip_cordoned_nodes: true,
+            ..Default::default()
+        };
+        let mut handle = setup_with_options(
+            "list-nodes-cordoned.json",
+            matchers,
+            options,
+            Metrics::new(),
+        )
+        .await;
+
+        let (request, _) = handle.next_request().await.expect("watch nodes not called");
+        assert_eq!(request.method(), http::Method::GET);
+        assert_eq!(
+            request.uri().to_string(),
+            "/api/v1/nodes?&watch=true&timeoutSeconds=290&\
+        allowWatchBookmarks=true&resourceVersion=test"
+        );
+
+        assert!(logs_contain(
+            r#"Skipping cordoned node node="aks-hephaestus1-41950716-vmss000082""#
+        ));
+        assert!(!logs_contain("Adding taints to node"));
+    }
+
+    #[test_case("list-nodes-control-plane.json" ; "control-plane role label")]
+    #[test_case("list-nodes-master.json" ; "master role label")]
+    #[tokio::test]
+    #[traced_test]
+    async fn test_start_skips_control_plane_node_when_protected(file: &str) {
+        let matchers = vec![Configuration {
+            taints: vec![Taint {
+                effect: "NoExecute".to_string(),
+                key: "event".to_string(),
+                time_added: None,
+                value: None,
+            }],
+            conditions: vec![condition("VMEventScheduled", "True")],
+            ..Default::default()
+        }];
+        let options = Options {
+            protect_control_plane: true,
+            ..Default::default()
+        };
+        let mut handle = setup_with_options(file, matchers, options, Metrics::new()).await;
+
+        let (request, _) = handle.next_request().await.expect("watch nodes not called");
+        assert_eq!(request.method(), http::Method::GET);
+        assert_eq!(
+            request.uri().to_string(),
+            "/api/v1/nodes?&watch=true&timeoutSeconds=290&\
+        allowWatchBookmarks=true&resourceVersion=test"
+        );
+
+        assert!(logs_contain(
+            r#"Skipping control plane node node="aks-athena1-41950716-vmss000082""#
+        ));
+        assert!(!logs_contain("Adding taints to node"));
+    }
+
+    #[tokio::test]
+    async fn test_start_taints_control_plane_node_when_not_protected() {
+        let matchers = vec![Configuration {
+            taints: vec![Taint {
+                effect: "NoExecute".to_string(),
+                key: "event".to_string(),
+                time_added: None,
+                value: None,
+            }],
+            conditions: vec![condition("VMEventScheduled", "True")],
+            ..Default::default()
+        }];
+        let mut handle = setup("list-nodes-control-plane.json", matchers).await;
+
+        let (request, _) = handle.next_request().await.expect("PUT node not called");
+        assert_eq!(request.method(), http::Method::PUT);
+        assert_eq!(
+            request.uri().to_string(),
+            "/api/v1/nodes/aks-athena1-41950716-vmss000082?&fieldManager=tainter"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_start_taints_worker_node_when_control_plane_is_protected() {
+        let matchers = vec![Configuration {
+            taints: vec![Taint {
+                effect: "NoExecute".to_string(),
+                key: "event".to_string(),
+                time_added: None,
+                value: None,
+            }],
+            conditions: vec![condition("VMEventScheduled", "True")],
+            ..Default::default()
+        }];
+        let options = Options {
+            protect_control_plane: true,
+            ..Default::default()
+        };
+        let mut handle = setup_with_options(
+            "list-nodes-single-eligible.json",
+            matchers,
+            options,
+            Metrics::new(),
+        )
+        .await;
+
+        let (request, _) = handle.next_request().await.expect("PUT node not called");
+        assert_eq!(request.method(), http::Method::PUT);
+        assert_eq!(
+            request.uri().to_string(),
+            "/api/v1/nodes/aks-zeus1-41950716-vmss000082?&fieldManager=tainter"
+        );
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_start_taints_cordoned_node_by_default() {
+        let matchers = vec![Configuration {
+            taints: vec![Taint {
+                effect: "NoExecute".to_string(),
+                key: "event".to_string(),
+                time_added: None,
+                value: None,
+            }],
+            conditions: vec![condition("VMEventScheduled", "True")],
+            ..Default::default()
+        }];
+        let mut handle = setup("list-nodes-cordoned.json", matchers).await;
+
+        let (request, _) = handle.next_request().await.expect("PUT node not called");
+        assert_eq!(request.method(), http::Method::PUT);
+        assert_eq!(
+            request.uri().to_string(),
+            "/api/v1/nodes/aks-hephaestus1-41950716-vmss000082?&fieldManager=tainter"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_start_labels_node_with_prefer_no_schedule_weight() {
+        let matchers = vec![Configuration {
+            taints: vec![Taint {
+                effect: "PreferNoSchedule".to_string(),
+                key: "event".to_string(),
+                time_added: None,
+                value: Some("scheduled".to_string()),
+            }],
+            conditions: vec![condition("VMEventScheduled", "True")],
+            weight: Some(40),
+            ..Default::default()
+        }];
+        let mut handle = setup("list-nodes-single-eligible.json", matchers).await;
+
+        let (request, _) = handle.next_request().await.expect("PUT node not called");
+        assert_eq!(request.method(), http::Method::PUT);
+        let node = node_from_body(request).await;
+        let taints = node.spec.unwrap().taints.unwrap();
+        let taint = taints.get(1).unwrap();
+        assert_eq!(taint.effect, "PreferNoSchedule");
+        assert_eq!(taint.key, "event");
+        let labels = node.metadata.labels.unwrap();
+        assert_eq!("40", labels.get(PREFER_WEIGHT_LABEL).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_start_does_not_label_node_with_weight_of_other_effects() {
+        let matchers = vec![Configuration {
+            taints: vec![Taint {
+                effect: "NoSchedule".to_string(),
+                key: "event".to_string(),
+                time_added: None,
+                value: Some("scheduled".to_string()),
+            }],
+            conditions: vec![condition("VMEventScheduled", "True")],
+            weight: Some(40),
+            ..Default::default()
+        }];
+        let mut handle = setup("list-nodes-single-eligible.json", matchers).await;
+
+        let (request, _) = handle.next_request().await.expect("PUT node not called");
+        let node = node_from_body(request).await;
+        let labels = node.metadata.labels.unwrap_or_default();
+        assert!(!labels.contains_key(PREFER_WEIGHT_LABEL));
+    }
+
+    #[test_case("zone-${label:agentpool}", MissingLabelPolicy::Skip, Some("zone-zeus1") ; "renders present label")]
+    #[test_case("${label:topology.kubernetes.io/zone}", MissingLabelPolicy::Skip, None ; "skips taint on missing label")]
+    #[test_case("${label:topology.kubernetes.io/zone}", MissingLabelPolicy::Fallback("unknown".to_string()), Some("unknown") ; "uses fallback on missing label")]
+    #[tokio::test]
+    async fn test_start_renders_node_label_into_taint_value(
+        value: &str,
+        missing_label_policy: MissingLabelPolicy,
+        expected: Option<&str>,
+    ) {
+        let matchers = vec![Configuration {
+            taints: vec![
+                Taint {
+                    effect: "NoSchedule".to_string(),
+                    key: "zone".to_string(),
+                    time_added: None,
+                    value: Some(value.to_string()),
+                },
+                Taint {
+                    effect: "NoSchedule".to_string(),
+                    key: "event".to_string(),
+                    time_added: None,
+                    value: None,
+                },
+            ],
+            conditions: vec![condition("VMEventScheduled", "True")],
+            missing_label_policy,
+            ..Default::default()
+        }];
+        let mut handle = setup("list-nodes-single-eligible.json", matchers).await;
+
+        let (request, _) = handle.next_request().await.expect("PUT node not called");
+        assert_eq!(request.method(), http::Method::PUT);
+        let node = node_from_body(request).await;
+        let taints = node.spec.unwrap().taints.unwrap();
+        let zone = taints.iter().find(|taint| taint.key == "zone");
+        assert_eq!(expected, zone.and_then(|taint| taint.value.as_deref()));
+        assert!(taints.iter().any(|taint| taint.key == "event"));
+    }
+
+    #[test_case("pool-${label:agentpool}/drain", Some("pool-zeus1/drain") ; "renders present label")]
+    #[test_case("zone-${label:topology.kubernetes.io/zone}/drain", None ; "skips taint on missing label")]
+    #[test_case("pool ${label:agentpool}", None ; "skips taint with invalid rendered key")]
+    #[tokio::test]
+    #[traced_test]
+    async fn test_start_renders_node_label_into_taint_key(key: &str, expected: Option<&str>) {
+        let matchers = vec![Configuration {
+            taints: vec![
+                Taint {
+                    effect: "NoSchedule".to_string(),
+                    key: key.to_string(),
+                    time_added: None,
+                    value: Some("true".to_string()),
+                },
+                Taint {
+                    effect: "NoSchedule".to_string(),
+                    key: "event".to_string(),
+                    time_added: None,
+                    value: None,
+                },
+            ],
+            conditions: vec![condition("VMEventScheduled", "True")],
+            ..Default::default()
+        }];
+        let mut handle = setup("list-nodes-single-eligible.json", matchers).await;
+
+        let (request, _) = handle.next_request().await.expect("PUT node not called");
+        assert_eq!(request.method(), http::Method::PUT);
+        let node = node_from_body(request).await;
+        let taints = node.spec.unwrap().taints.unwrap();
+        let rendered = taints
+            .iter()
+            .find(|taint| taint.value.as_deref() == Some("true"));
+        assert_eq!(expected, rendered.map(|taint| taint.key.as_str()));
+        assert!(taints.iter().any(|taint| taint.key == "event"));
+        assert_eq!(
+            key == "pool ${label:agentpool}",
+            logs_contain("Skipping taint because its rendered key is not a valid taint key")
+        );
+    }
+
+    #[test_case(&[], Some("detected-20240512T112110Z") ; "renders timestamp into key")]
+    #[test_case(&["detected-20240101T000000Z"], None ; "keeps taint with earlier timestamp")]
+    #[tokio::test]
+    async fn test_evaluate_matchers_renders_timestamp_into_key(
+        existing_keys: &[&str],
+        expected: Option<&str>,
+    ) {
+        let matchers = vec![Configuration {
+            taints: vec![Taint {
+                effect: "NoSchedule".to_string(),
+                key: "detected-${timestamp}".to_string(),
+                time_added: None,
+                value: None,
+            }],
+            conditions: vec![condition("VMEventScheduled", "True")],
+            ..Default::default()
+        }];
+        let taints: Vec<Taint> = existing_keys
+            .iter()
+            .map(|key| Taint {
+                effect: "NoSchedule".to_string(),
+                key: key.to_string(),
+                time_added: None,
+                value: None,
+            })
+            .collect();
+        let reconciler = reconciler_with_clock(Box::new(FixedClock(fixed_time())));
+        let list: kube::api::ObjectList<Node> =
+            serde_json::from_str(&get_test_file("list-nodes-single-eligible.json")).unwrap();
+        let node = list.items.first().unwrap();
+
+        let evaluation = reconciler.evaluate_matchers(
+            &matchers,
+            "aks-zeus1-41950716-vmss000082",
+            node.status.as_ref().unwrap(),
+            &ObjectMeta::default(),
+            &taints,
+            false,
+        );
+
+        assert_eq!(
+            expected,
+            evaluation
+                .taints_to_add
+                .first()
+                .map(|(taint, _)| taint.key.as_str())
+        );
+    }
+
+    #[test_case("pressure", true ; "name")]
+    #[test_case("tainter.io/pressure", true ; "prefixed name")]
+    #[test_case("zone-westeurope-1/drain", true ; "rendered prefix")]
+    #[test_case("a.b_c-D", true ; "name with punctuation")]
+    #[test_case("", false ; "empty")]
+    #[test_case("pool zeus1", false ; "whitespace")]
+    #[test_case("-pressure", false ; "name starting with punctuation")]
+    #[test_case("West-Europe/drain", false ; "uppercase prefix")]
+    #[test_case("tainter.io/", false ; "empty name")]
+    #[test_case("a/b/c", false ; "several slashes")]
+    fn test_is_valid_taint_key(key: &str, expected: bool) {
+        assert_eq!(expected, is_valid_taint_key(key));
+    }
+
+    #[test_case("plain", "plain" ; "leaves template without placeholders")]
+    #[test_case("${label:agentpool}/${label:env}", "zeus1/prod" ; "renders multiple placeholders")]
+    #[test_case("${capture}-${label:agentpool}", "${capture}-zeus1" ; "leaves capture placeholders")]
+    #[test_case("${label:agentpool", "${label:agentpool" ; "leaves unterminated placeholder")]
+    #[tokio::test]
+    async fn test_render_labels(template: &str, expected: &str) {
+        let reconciler = reconciler();
+        let labels = BTreeMap::from([
+            ("agentpool".to_string(), "zeus1".to_string()),
+            ("env".to_string(), "prod".to_string()),
+        ]);
+
+        let rendered = reconciler.render_labels(template, Some(&labels), None);
+
+        assert_eq!(Ok(expected.to_string()), rendered);
+    }
+
+    #[tokio::test]
+    async fn test_start_adds_all_taints_of_matcher() {
+        let matchers = vec![Configuration {
+            taints: vec![
+                Taint {
+                    effect: "NoSchedule".to_string(),
+                    key: "event".to_string(),
+                    time_added: None,
+                    value: Some("scheduled".to_string()),
+                },
+                Taint {
+                    effect: "NoExecute".to_string(),
+                    key: "event".to_string(),
+                    time_added: None,
+                    value: Some("scheduled".to_string()),
+                },
+            ],
+            conditions: vec![condition("VMEventScheduled", "True")],
+            ..Default::default()
+        }];
+        let mut handle = setup("list-nodes-single-eligible.json", matchers).await;
+
+        let (request, _) = handle.next_request().await.expect("PUT node not called");
+        assert_eq!(request.method(), http::Method::PUT);
+        assert_eq!(
+            request.uri().to_string(),
+            "/api/v1/nodes/aks-zeus1-41950716-vmss000082?&fieldManager=tainter"
+        );
+        let node = node_from_body(request).await;
+        let taints = node.spec.unwrap().taints.unwrap();
+        assert_eq!(taints.len(), 3);
+        let taint = taints.get(1).unwrap();
+        assert_eq!(taint.effect, "NoSchedule");
+        assert_eq!(taint.key, "event");
+        assert_eq!(taint.time_added, None);
+        let taint = taints.get(2).unwrap();
+        assert_eq!(taint.effect, "NoExecute");
+        assert_eq!(taint.key, "event");
+        assert!(taint.time_added.is_some());
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_start_does_not_change_time_added_of_persisting_taint() {
+        let matchers = vec![Configuration {
+            taints: vec![Taint {
+                effect: "NoExecute".to_string(),
+                key: "event".to_string(),
+                time_added: None,
+                value: None,
+            }],
+            conditions: vec![condition("VMEventScheduled", "True")],
+            ..Default::default()
+        }];
+        let mut handle = setup("list-nodes-single-eligible.json", matchers).await;
+
+        let (request, response) = handle.next_request().await.expect("PUT node not called");
+        assert_eq!(request.method(), http::Method::PUT);
+        let tainted_node = node_from_body(request).await;
+        let taint = tainted_node
+            .spec
+            .as_ref()
+            .unwrap()
+            .taints
+            .as_ref()
+            .unwrap()
+            .get(1)
+            .unwrap();
+        assert_eq!(taint.key, "event");
+        assert!(taint.time_added.is_some());
+        response.send_response(
+            Response::builder()
+                .body(Body::from(serde_json::to_vec(&tainted_node).unwrap()))
+                .unwrap(),
+        );
+
+        // The node is modified again while the taint persists, e.g. by a kubelet status update.
+        let (request, response) = handle.next_request().await.expect("watch nodes not called");
+        assert_eq!(request.method(), http::Method::GET);
+        response.send_response(
+            Response::builder()
+                .body(watch_event_body("MODIFIED", &tainted_node))
+                .unwrap(),
+        );
+
+        // Tainter must not update the node, which would otherwise be the next request.
+        let (request, _) = handle
+            .next_request()
+            .await
+            .expect("watch nodes not called again");
+        assert_eq!(request.method(), http::Method::GET);
+        assert!(request.uri().to_string().contains("watch=true"));
+
+        assert!(logs_contain(
+            r#"Node matches conditions but already has taint node="aks-zeus1-41950716-vmss000082" taint="event:NoExecute""#
+        ));
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_start_removes_taint_of_managed_matcher_when_conditions_clear() {
+        let matchers = vec![Configuration {
+            taints: vec![Taint {
+                effect: "NoSchedule".to_string(),
+                key: "event".to_string(),
+                time_added: None,
+                value: None,
+            }],
+            conditions: vec![condition("VMEventScheduled", "True")],
+            lifecycle: Lifecycle::Managed,
+            ..Default::default()
+        }];
+        let mut handle = setup("list-nodes-single-eligible.json", matchers).await;
+
+        // The condition appears, so the taint is added.
+        let (request, response) = handle.next_request().await.expect("PUT node not called");
+        assert_eq!(request.method(), http::Method::PUT);
+        let tainted_node = node_from_body(request).await;
+        let taints = tainted_node.spec.as_ref().unwrap().taints.as_ref().unwrap();
+        assert!(taints.iter().any(|taint| taint.key == "event"));
+        response.send_response(
+            Response::builder()
+                .body(Body::from(serde_json::to_vec(&tainted_node).unwrap()))
+                .unwrap(),
+        );
+
+        // The condition clears.
+        let mut cleared_node = tainted_node.clone();
+        for condition in cleared_node
+            .status
+            .as_mut()
+            .unwrap()
+            .conditions
+            .as_mut()
+            .unwrap()
+        {
+            if condition.type_ == "VMEventScheduled" {
+                condition.status = "False".to_string();
+            }
+        }
+        let (request, response) = handle.next_request().await.expect("watch nodes not called");
+        assert_eq!(request.method(), http::Method::GET);
+        response.send_response(
+            Response::builder()
+                .body(watch_event_body("MODIFIED", &cleared_node))
+                .unwrap(),
+        );
+
+        // The taint is removed.
+        let (request, _) = handle.next_request().await.expect("PUT node not called");
+        assert_eq!(request.method(), http::Method::PUT);
+        let untainted_node = node_from_body(request).await;
+        let taints = untainted_node.spec.unwrap().taints.unwrap();
+        assert!(!taints.iter().any(|taint| taint.key == "event"));
+        assert!(!untainted_node
+            .metadata
+            .annotations
+            .unwrap_or_default()
+            .contains_key(MANAGED_TAINTS_ANNOTATION));
+        assert!(logs_contain(
+            r#"Removing taints from node because the conditions of their managed matchers cleared node="aks-zeus1-41950716-vmss000082" taints="event:NoSchedule""#
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_evaluate_matchers_keeps_taint_that_another_matcher_adds() {
+        let matchers = vec![
+            Configuration {
+                taints: vec![Taint {
+                    effect: "NoSchedule".to_string(),
+                    key: "event".to_string(),
+                    time_added: None,
+                    value: None,
+                }],
+                conditions: vec![condition("NonExistentCondition", "True")],
+                lifecycle: Lifecycle::Managed,
+                ..Default::default()
+            },
+            Configuration {
+                taints: vec![Taint {
+                    effect: "NoSchedule".to_string(),
+                    key: "event".to_string(),
+                    time_added: None,
+                    value: None,
+                }],
+                conditions: vec![condition("VMEventScheduled", "True")],
+                ..Default::default()
+            },
+        ];
+        let reconciler = reconciler();
+        let list: kube::api::ObjectList<Node> =
+            serde_json::from_str(&get_test_file("list-nodes-single-eligible.json")).unwrap();
+        let node = list.items.first().unwrap();
+        let taints = vec![Taint {
+            effect: "NoSchedule".to_string(),
+            key: "event".to_string(),
+            time_added: None,
+            value: None,
+        }];
+
+        let evaluation = reconciler.evaluate_matchers(
+            &matchers,
+            "aks-zeus1-41950716-vmss000082",
+            node.status.as_ref().unwrap(),
+            &ObjectMeta::default(),
+            &taints,
+            false,
+        );
+
+        assert!(evaluation.taints_to_add.is_empty());
+        assert!(evaluation.taints_to_remove.is_empty());
+    }
+
+    #[test_case(&[] , true ; "no capacity requirements")]
+    #[test_case(&[("cpu", 8.0)], true ; "capacity equal to minimum")]
+    #[test_case(&[("cpu", 4.0), ("memory", 16.0 * 1024.0 * 1024.0 * 1024.0)], true ; "capacity above minimums")]
+    #[test_case(&[("cpu", 16.0)], false ; "capacity below minimum")]
+    #[test_case(&[("nvidia.com/gpu", 0.0)], false ; "resource missing from capacity")]
+    #[tokio::test]
+    async fn test_evaluate_matchers_capacity_requires(
+        capacity_requires: &[(&str, f64)],
+        eligible: bool,
+    ) {
+        let matchers = vec![Configuration {
+            taints: vec![Taint {
+                effect: "NoSchedule".to_string(),
+                key: "event".to_string(),
+                time_added: None,
+                value: None,
+            }],
+            conditions: vec![condition("VMEventScheduled", "True")],
+            capacity_requires: capacity_requires
+                .iter()
+                .map(|(resource, minimum)| (resource.to_string(), *minimum))
+                .collect(),
+            ..Default::default()
+        }];
+        let reconciler = reconciler();
+        let list: kube::api::ObjectList<Node> =
+            serde_json::from_str(&get_test_file("list-nodes-single-eligible.json")).unwrap();
+        let node = list.items.first().unwrap();
+
+        let evaluation = reconciler.evaluate_matchers(
+            &matchers,
+            "aks-zeus1-41950716-vmss000082",
+            node.status.as_ref().unwrap(),
+            &ObjectMeta::default(),
+            &[],
+            false,
+        );
+
+        assert_eq!(eligible, !evaluation.taints_to_add.is_empty());
+    }
+
+    #[test_case(&[("node.kubernetes.io/unreachable", "NoExecute")], true ; "external taint")]
+    #[test_case(&[("node.kubernetes.io/unreachable", "NoSchedule")], true ; "external taint with other matching effect")]
+    #[test_case(&[("node.kubernetes.io/unreachable", "PreferNoSchedule")], false ; "external taint with effect that does not match")]
+    #[test_case(&[("node.kubernetes.io/not-ready", "NoExecute")], false ; "other external taint")]
+    #[test_case(&[], false ; "no taints")]
+    #[tokio::test]
+    async fn test_evaluate_matchers_taint_conditions(taints: &[(&str, &str)], eligible: bool) {
+        let matchers = vec![Configuration {
+            taints: vec![Taint {
+                effect: "PreferNoSchedule".to_string(),
+                key: "unreachable-notify".to_string(),
+                time_added: None,
+                value: None,
+            }],
+            taint_conditions: vec![TaintCondition {
+                key: Regex::new(r"^node\.kubernetes\.io/unreachable$").unwrap(),
+                effect: Some(Regex::new("^(NoExecute|NoSchedule)$").unwrap()),
+                value: None,
+            }],
+            ..Default::default()
+        }];
+        let taints: Vec<Taint> = taints
+            .iter()
+            .map(|(key, effect)| Taint {
+                effect: effect.to_string(),
+                key: key.to_string(),
+                time_added: None,
+                value: None,
+            })
+            .collect();
+        let reconciler = reconciler();
+        let list: kube::api::ObjectList<Node> =
+            serde_json::from_str(&get_test_file("list-nodes-single-eligible.json")).unwrap();
+        let node = list.items.first().unwrap();
+
+        let evaluation = reconciler.evaluate_matchers(
+            &matchers,
+            "aks-zeus1-41950716-vmss000082",
+            node.status.as_ref().unwrap(),
+            &ObjectMeta::default(),
+            &taints,
+            false,
+        );
+
+        assert_eq!(eligible, !evaluation.taints_to_add.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_evaluate_matchers_taint_conditions_ignore_taints_of_matchers() {
+        // The matcher's taint condition matches any taint, including the matcher's own taint,
+        // which would otherwise keep the matcher eligible because of its own taint.
+        let matchers = vec![Configuration {
+            taints: vec![Taint {
+                effect: "NoSchedule".to_string(),
+                key: "tainted".to_string(),
+                time_added: None,
+                value: Some("true".to_string()),
+            }],
+            taint_conditions: vec![TaintCondition {
+                key: Regex::new(".*").unwrap(),
+                effect: None,
+                value: Some(Regex::new("true").unwrap()),
+            }],
+            lifecycle: Lifecycle::Managed,
+            ..Default::default()
+        }];
+        let taints = vec![Taint {
+            effect: "NoSchedule".to_string(),
+            key: "tainted".to_string(),
+            time_added: None,
+            value: Some("true".to_string()),
+        }];
+        let (mock_service, _) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+        let reconciler = Reconciler::new(
+            Client::new(mock_service, "default"),
+            matchers,
+            Options::default(),
+            Metrics::new(),
+            Box::new(UtcClock),
+        );
+        let list: kube::api::ObjectList<Node> =
+            serde_json::from_str(&get_test_file("list-nodes-single-eligible.json")).unwrap();
+        let node = list.items.first().unwrap();
+
+        let evaluation = reconciler.evaluate_matchers(
+            &reconciler.matchers,
+            "aks-zeus1-41950716-vmss000082",
+            node.status.as_ref().unwrap(),
+            &ObjectMeta::default(),
+            &taints,
+            false,
+        );
+
+        assert!(evaluation.taints_to_add.is_empty());
+        assert_eq!(taints, evaluation.taints_to_remove);
+    }
+
+    // The fixed time is Sunday 2024-05-12T11:21:10Z.
+    #[test_case("0 0 11 * * Sun", 1, true ; "inside window")]
+    #[test_case("0 21 11 * * *", 1, true ; "at start of window")]
+    #[test_case("0 0 10 * * *", 1, false ; "after end of window")]
+    #[test_case("0 0 12 * * *", 1, false ; "before start of window")]
+    #[test_case("0 0 11 * * Sat", 24, false ; "window on another day")]
+    #[test_case("0 0 22 * * Sat", 24, true ; "window spanning midnight")]
+    #[tokio::test]
+    #[traced_test]
+    async fn test_evaluate_matchers_schedule(cron: &str, hours: i64, eligible: bool) {
+        let matchers = vec![Configuration {
+            taints: vec![Taint {
+                effect: "NoSchedule".to_string(),
+                key: "event".to_string(),
+                time_added: None,
+                value: None,
+            }],
+            conditions: vec![condition("VMEventScheduled", "True")],
+            schedule: Some(Schedule {
+                cron: cron::Schedule::from_str(cron).unwrap(),
+                duration: chrono::Duration::hours(hours),
+            }),
+            ..Default::default()
+        }];
+        let reconciler = reconciler_with_clock(Box::new(FixedClock(fixed_time())));
+        let list: kube::api::ObjectList<Node> =
+            serde_json::from_str(&get_test_file("list-nodes-single-eligible.json")).unwrap();
+        let node = list.items.first().unwrap();
+
+        let evaluation = reconciler.evaluate_matchers(
+            &matchers,
+            "aks-zeus1-41950716-vmss000082",
+            node.status.as_ref().unwrap(),
+            &ObjectMeta::default(),
+            &[],
+            false,
+        );
+
+        assert_eq!(eligible, !evaluation.taints_to_add.is_empty());
+        assert_eq!(
+            !eligible,
+            logs_contain("Deferring matcher until its schedule's next window")
+        );
+    }
+
+    #[test_case(Some("aks-zeus1-41950716-vmss000082"), "NoExecute", false ; "self node")]
+    #[test_case(Some("aks-zeus1-41950716-vmss000082"), "NoSchedule", true ; "no schedule on self node")]
+    #[test_case(Some("aks-zeus1-41950716-vmss000083"), "NoExecute", true ; "other node")]
+    #[test_case(None, "NoExecute", true ; "not protected")]
+    #[tokio::test]
+    #[traced_test]
+    async fn test_evaluate_matchers_self_node_name(
+        self_node_name: Option<&str>,
+        effect: &str,
+        applied: bool,
+    ) {
+        let matchers = vec![Configuration {
+            taints: vec![Taint {
+                effect: effect.to_string(),
+                key: "event".to_string(),
+                time_added: None,
+                value: None,
+            }],
+            conditions: vec![condition("VMEventScheduled", "True")],
+            ..Default::default()
+        }];
+        let (mock_service, _) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+        let options = Options {
+            self_node_name: self_node_name.map(str::to_string),
+            ..Default::default()
+        };
+        let reconciler = Reconciler::new(
+            Client::new(mock_service, "default"),
+            vec![],
+            options,
+            Metrics::new(),
+            Box::new(UtcClock),
+        );
+        let list: kube::api::ObjectList<Node> =
+            serde_json::from_str(&get_test_file("list-nodes-single-eligible.json")).unwrap();
+        let status = list.items.first().unwrap().status.clone().unwrap();
+
+        let evaluation = reconciler.evaluate_matchers(
+            &matchers,
+            "aks-zeus1-41950716-vmss000082",
+            &status,
+            &ObjectMeta::default(),
+            &[],
+            false,
+        );
+
+        assert_eq!(applied, !evaluation.taints_to_add.is_empty());
+        assert_eq!(
+            !applied,
+            logs_contain("Skipping NoExecute taint because the node hosts Tainter")
+        );
+    }
+
+    #[test_case(false, "False", "NoSchedule", true ; "not required")]
+    #[test_case(true, "True", "NoSchedule", true ; "ready node")]
+    #[test_case(true, "False", "NoSchedule", false ; "not ready node")]
+    #[test_case(true, "Unknown", "PreferNoSchedule", false ; "unknown readiness")]
+    #[test_case(true, "False", "NoExecute", true ; "no execute exemption")]
+    #[tokio::test]
+    #[traced_test]
+    async fn test_evaluate_matchers_require_node_ready(
+        require_node_ready: bool,
+        ready: &str,
+        effect: &str,
+        applied: bool,
+    ) {
+        let matchers = vec![Configuration {
+            taints: vec![Taint {
+                effect: effect.to_string(),
+                key: "event".to_string(),
+                time_added: None,
+                value: None,
+            }],
+            conditions: vec![condition("VMEventScheduled", "True")],
+            ..Default::default()
+        }];
+        let (mock_service, _) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+        let options = Options {
+            require_node_ready,
+            ..Default::default()
+        };
+        let reconciler = Reconciler::new(
+            Client::new(mock_service, "default"),
+            vec![],
+            options,
+            Metrics::new(),
+            Box::new(UtcClock),
+        );
+        let list: kube::api::ObjectList<Node> =
+            serde_json::from_str(&get_test_file("list-nodes-single-eligible.json")).unwrap();
+        let mut status = list.items.first().unwrap().status.clone().unwrap();
+        for condition in status.conditions.iter_mut().flatten() {
+            if condition.type_ == "Ready" {
+                condition.status = ready.to_string();
+            }
+        }
+
+        let evaluation = reconciler.evaluate_matchers(
+            &matchers,
+            "aks-zeus1-41950716-vmss000082",
+            &status,
+            &ObjectMeta::default(),
+            &[],
+            false,
+        );
+
+        assert_eq!(applied, !evaluation.taints_to_add.is_empty());
+        assert_eq!(
+            !applied,
+            logs_contain("Deferring taint until the node is ready")
+        );
+    }
+
+    #[test_case(false, &["overheating", "event"] ; "evaluates matchers in order of priority")]
+    #[test_case(true, &["overheating"] ; "short circuit skips matchers of lower priority")]
+    #[tokio::test]
+    #[traced_test]
+    async fn test_evaluate_matchers_priority(short_circuit: bool, expected: &[&str]) {
+        let matcher = |key: &str, effect: &str, type_: &str, priority: i32| Configuration {
+            name: Some(key.to_string()),
+            taints: vec![Taint {
+                effect: effect.to_string(),
+                key: key.to_string(),
+                time_added: None,
+                value: None,
+            }],
+            conditions: vec![condition(type_, "True")],
+            priority,
+            ..Default::default()
+        };
+        let matchers = vec![
+            matcher("event", "NoSchedule", "VMEventScheduled", 0),
+            matcher("overheating", "NoExecute", "VMEventScheduled", 10),
+            // Never matches, so it doesn't short-circuit the evaluation of the other matchers.
+            matcher("unused", "NoExecute", "NonExistentCondition", 20),
+        ];
+        let (mock_service, _) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+        let options = Options {
+            short_circuit,
+            ..Default::default()
+        };
+        let reconciler = Reconciler::new(
+            Client::new(mock_service, "default"),
+            vec![],
+            options,
+            Metrics::new(),
+            Box::new(UtcClock),
+        );
+        let list: kube::api::ObjectList<Node> =
+            serde_json::from_str(&get_test_file("list-nodes-single-eligible.json")).unwrap();
+        let node = list.items.first().unwrap();
+
+        let evaluation = reconciler.evaluate_matchers(
+            &matchers,
+            "aks-zeus1-41950716-vmss000082",
+            node.status.as_ref().unwrap(),
+            &ObjectMeta::default(),
+            &[],
+            false,
+        );
+
+        let keys: Vec<&str> = evaluation
+            .taints_to_add
+            .iter()
+            .map(|(taint, _)| taint.key.as_str())
+            .collect();
+        assert_eq!(expected, keys);
+        assert_eq!(
+            short_circuit,
+            logs_contain(
+                r#"Skipping matcher because a matcher of higher precedence applies node="aks-zeus1-41950716-vmss000082" matcher="event" applied_matcher="overheating""#
+            )
+        );
+    }
+
+    #[test_case(Some(0), None, Some(60), true ; "brand-new node within max age")]
+    #[test_case(Some(60 * 24 * 30), None, Some(60), false ; "old node beyond max age")]
+    #[test_case(Some(60), None, Some(60), true ; "node exactly at max age")]
+    #[test_case(Some(0), Some(60), None, false ; "brand-new node below min age")]
+    #[test_case(Some(60 * 24 * 30), Some(60), None, true ; "old node above min age")]
+    #[test_case(None, None, Some(60), false ; "node without creation time")]
+    #[test_case(None, None, None, true ; "matcher without node age range")]
+    #[tokio::test]
+    #[traced_test]
+    async fn test_evaluate_matchers_node_age(
+        minutes_since_creation: Option<i64>,
+        node_age_min: Option<i64>,
+        node_age_max: Option<i64>,
+        expected: bool,
+    ) {
+        let reconciler = reconciler_with_clock(Box::new(FixedClock(fixed_time())));
+        let matchers = vec![Configuration {
+            taints: vec![Taint {
+                effect: "NoSchedule".to_string(),
+                key: "bootstrapping".to_string(),
+                time_added: None,
+                value: None,
+            }],
+            conditions: vec![condition("Ready", "False")],
+            node_age_min: node_age_min.map(chrono::Duration::minutes),
+            node_age_max: node_age_max.map(chrono::Duration::minutes),
+            ..Default::default()
+        }];
+        let list: kube::api::ObjectList<Node> =
+            serde_json::from_str(&get_test_file("list-nodes-single-eligible.json")).unwrap();
+        let node = list.items.first().unwrap();
+        let metadata = ObjectMeta {
+            creation_timestamp: minutes_since_creation
+                .map(|minutes| Time(fixed_time() - chrono::Duration::minutes(minutes))),
+            ..Default::default()
+        };
+
+        let evaluation = reconciler.evaluate_matchers(
+            &matchers,
+            "aks-zeus1-41950716-vmss000082",
+            node.status.as_ref().unwrap(),
+            &metadata,
+            &[],
+            false,
+        );
+
+        assert_eq!(expected, !evaluation.taints_to_add.is_empty());
+    }
+
+    #[test_case(&[("agentpool", "zeus1")], true ; "node missing required label")]
+    #[test_case(&[("agentpool", "zeus1"), ("cost-center", "")], false ; "node with empty required label")]
+    #[test_case(&[("agentpool", "zeus1"), ("cost-center", "4711")], false ; "node with all required labels")]
+    #[tokio::test]
+    #[traced_test]
+    async fn test_evaluate_matchers_required_labels(labels: &[(&str, &str)], eligible: bool) {
+        let matchers = vec![Configuration {
+            taints: vec![Taint {
+                effect: "NoSchedule".to_string(),
+                key: "noncompliant".to_string(),
+                time_added: None,
+                value: None,
+            }],
+            required_labels: vec!["agentpool".to_string(), "cost-center".to_string()],
+            ..Default::default()
+        }];
+        let metadata = ObjectMeta {
+            labels: Some(
+                labels
+                    .iter()
+                    .map(|(key, value)| (key.to_string(), value.to_string()))
+                    .collect(),
+            ),
+            ..Default::default()
+        };
+
+        let status = NodeStatus::default();
+
+        let evaluation = reconciler().evaluate_matchers(
+            &matchers,
+            "aks-zeus1-41950716-vmss000082",
+            &status,
+            &metadata,
+            &[],
+            false,
+        );
+
+        assert_eq!(eligible, !evaluation.taints_to_add.is_empty());
+        assert_eq!(
+            eligible,
+            logs_contain(
+                r#"Node is missing required label node="aks-zeus1-41950716-vmss000082" label="cost-center""#
+            )
+        );
+    }
+
+    #[test_case(&["PcieBusError", "PcieBusError"], false ; "two matching reasons")]
+    #[test_case(&["PcieBusError", "PcieBusError", "PcieBusError"], true ; "three matching reasons")]
+    #[test_case(&["PcieBusError", "PcieBusError", "PcieLinkDown"], false ; "three matching but different reasons")]
+    #[test_case(&["PcieBusError", "PcieBusError", "KernelDeadlock"], false ; "reason that does not match")]
+    #[tokio::test]
+    #[traced_test]
+    async fn test_evaluate_matchers_reason_quorum(reasons: &[&str], eligible: bool) {
+        let matchers = vec![Configuration {
+            taints: vec![Taint {
+                effect: "NoSchedule".to_string(),
+                key: "pcie".to_string(),
+                time_added: None,
+                value: None,
+            }],
+            conditions: vec![condition("Ready", "True")],
+            reason_quorum: Some(ReasonQuorum {
+                reason: Regex::new("^Pcie").unwrap(),
+                count: 3,
+            }),
+            ..Default::default()
+        }];
+        let mut conditions = vec![NodeCondition {
+            status: "True".to_string(),
+            type_: "Ready".to_string(),
+            ..Default::default()
+        }];
+        for (index, reason) in reasons.iter().enumerate() {
+            conditions.push(NodeCondition {
+                reason: Some(reason.to_string()),
+                status: "True".to_string(),
+                type_: format!("GpuProblem{}", index),
+                ..Default::default()
+            });
+        }
+        let status = NodeStatus {
+            conditions: Some(conditions),
+            ..Default::default()
+        };
+
+        let evaluation = reconciler().evaluate_matchers(
+            &matchers,
+            "aks-zeus1-41950716-vmss000082",
+            &status,
+            &ObjectMeta::default(),
+            &[],
+            false,
+        );
+
+        assert_eq!(eligible, !evaluation.taints_to_add.is_empty());
+        assert_eq!(
+            eligible,
+            logs_contain("Node conditions reach reason quorum")
+        );
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_start_logs_within_reconcile_node_span() {
+        let matchers = vec![Configuration {
+            taints: vec![Taint {
+                effect: "NoSchedule".to_string(),
+                key: "unused".to_string(),
+                time_added: None,
+                value: None,
+            }],
+            conditions: vec![condition("NonExistentCondition", "True")],
+            ..Default::default()
+        }];
+        let mut handle = setup("list-nodes-single-eligible.json", matchers).await;
+
+        let (_, _) = handle.next_request().await.expect("watch nodes not called");
+
+        assert!(logs_contain(
+            r#"reconcile_node{node="aks-zeus1-41950716-vmss000082"}: tainter::reconciler: Processing node"#
+        ));
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_start_annotates_node_instead_of_tainting_in_report_mode() {
+        let matchers = vec![Configuration {
+            mode: Mode::Report,
+            taints: vec![Taint {
+                effect: "NoExecute".to_string(),
+                key: "event".to_string(),
+                time_added: None,
+                value: Some("scheduled".to_string()),
+            }],
+            conditions: vec![condition("VMEventScheduled", "True")],
+            ..Default::default()
+        }];
+        let mut handle = setup("list-nodes-single-eligible.json", matchers).await;
+
+        let (request, _) = handle.next_request().await.expect("PUT node not called");
+        assert_eq!(request.method(), http::Method::PUT);
+        assert_eq!(
+            request.uri().to_string(),
+            "/api/v1/nodes/aks-zeus1-41950716-vmss000082?&fieldManager=tainter"
+        );
+        let node = node_from_body(request).await;
+        let annotations = node.metadata.annotations.unwrap();
+        assert_eq!(
+            "event=scheduled:NoExecute",
+            annotations.get(WOULD_TAINT_ANNOTATION).unwrap()
+        );
+        // Existing annotations are preserved.
+        assert_eq!(
+            "true",
+            annotations
+                .get("volumes.kubernetes.io/controller-managed-attach-detach")
+                .unwrap()
+        );
+        let taints = node.spec.unwrap().taints.unwrap();
+        assert_eq!(taints.len(), 1);
+        assert_eq!(
+            taints.first().unwrap().key,
+            "kubernetes.azure.com/scalesetpriority"
+        );
+        assert!(!logs_contain("Adding taints to node"));
+    }
+
+    #[tokio::test]
+    async fn test_start_does_not_annotate_node_again_in_report_mode() {
+        let matchers = vec![Configuration {
+            mode: Mode::Report,
+            taints: vec![Taint {
+                effect: "NoExecute".to_string(),
+                key: "event".to_string(),
+                time_added: None,
+                value: Some("scheduled".to_string()),
+            }],
+            conditions: vec![condition("VMEventScheduled", "True")],
+            ..Default::default()
+        }];
+        let mut handle = setup("list-nodes-would-taint-annotated.json", matchers).await;
+
+        let (request, _) = handle.next_request().await.expect("watch nodes not called");
+        assert_eq!(request.method(), http::Method::GET);
+        assert!(request.uri().to_string().contains("watch=true"));
+    }
+
+    #[test_case(Some("VMEventScheduled"), true ; "matches when reason matches")]
+    #[test_case(Some("Kaput"), false ; "does not match when reason does not match")]
+    #[test_case(None, true ; "matches when no reason is configured")]
+    #[tokio::test]
+    async fn test_conditions_match_reason(reason: Option<&str>, expected: bool) {
+        let reconciler = reconciler();
+        let condition = Condition {
+            reason: reason.map(|reason| Regex::new(reason).unwrap()),
+            ..condition("VMEventScheduled", "True")
+        };
+        let node_condition = NodeCondition {
+            reason: Some("VMEventScheduled".to_string()),
+            status: "True".to_string(),
+            type_: "VMEventScheduled".to_string(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            expected,
+            reconciler.conditions_match(&condition, &node_condition)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_conditions_match_reason_absent_on_node_condition() {
+        let reconciler = reconciler();
+        let condition = Condition {
+            reason: Some(Regex::new(".*").unwrap()),
+            ..condition("VMEventScheduled", "True")
+        };
+        let node_condition = NodeCondition {
+            reason: None,
+            status: "True".to_string(),
+            type_: "VMEventScheduled".to_string(),
+            ..Default::default()
+        };
+
+        assert!(!reconciler.conditions_match(&condition, &node_condition));
+    }
+
+    #[test_case(Some("(?i)scheduled event"), Some("VM has scheduled event"), true ; "matches when message matches")]
+    #[test_case(Some("reboot"), Some("VM has scheduled event"), false ; "does not match when message does not match")]
+    #[test_case(Some(".*"), None, false ; "does not match when message is absent")]
+    #[test_case(None, Some("VM has scheduled event"), true ; "matches when no message is configured")]
+    #[test_case(None, None, true ; "matches when no message is configured and message is absent")]
+    #[tokio::test]
+    async fn test_conditions_match_message(
+        pattern: Option<&str>,
+        message: Option<&str>,
+        expected: bool,
+    ) {
+        let reconciler = reconciler();
+        let condition = Condition {
+            message: pattern.map(|pattern| Regex::new(pattern).unwrap()),
+            ..condition("VMEventScheduled", "True")
+        };
+        let node_condition = NodeCondition {
+            message: message.map(|message| message.to_string()),
+            status: "True".to_string(),
+            type_: "VMEventScheduled".to_string(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            expected,
+            reconciler.conditions_match(&condition, &node_condition)
+        );
+    }
+
+    #[test_case(Some(chrono::Duration::minutes(1)), true ; "matches recent transition")]
+    #[test_case(Some(chrono::Duration::minutes(5)), true ; "matches transition exactly max_age ago")]
+    #[test_case(Some(chrono::Duration::minutes(5) + chrono::Duration::seconds(1)), false ; "does not match transition just over max_age ago")]
+    #[test_case(Some(chrono::Duration::minutes(10)), false ; "does not match stale transition")]
+    #[test_case(None, false ; "does not match missing transition time")]
+    #[tokio::test]
+    async fn test_conditions_match_max_age(
+        since_transition: Option<chrono::Duration>,
+        expected: bool,
+    ) {
+        let reconciler = reconciler_with_clock(Box::new(FixedClock(fixed_time())));
+        let condition = Condition {
+            max_age: Some(chrono::Duration::minutes(5)),
+            ..condition("Ready", "False")
+        };
+        let node_condition = NodeCondition {
+            last_transition_time: since_transition
+                .map(|since_transition| Time(fixed_time() - since_transition)),
+            status: "False".to_string(),
+            type_: "Ready".to_string(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            expected,
+            reconciler.conditions_match(&condition, &node_condition)
+        );
+    }
+
+    #[test_case(Heartbeat::Fresh(chrono::Duration::minutes(2)), Some(chrono::Duration::seconds(30)), true ; "fresh matches recent heartbeat")]
+    #[test_case(Heartbeat::Fresh(chrono::Duration::minutes(2)), Some(chrono::Duration::minutes(2)), true ; "fresh matches heartbeat exactly max age ago")]
+    #[test_case(Heartbeat::Fresh(chrono::Duration::minutes(2)), Some(chrono::Duration::minutes(10)), false ; "fresh does not match stale heartbeat")]
+    #[test_case(Heartbeat::Fresh(chrono::Duration::minutes(2)), None, false ; "fresh does not match missing heartbeat")]
+    #[test_case(Heartbeat::Stale(chrono::Duration::minutes(2)), Some(chrono::Duration::seconds(30)), false ; "stale does not match recent heartbeat")]
+    #[test_case(Heartbeat::Stale(chrono::Duration::minutes(2)), Some(chrono::Duration::minutes(2)), false ; "stale does not match heartbeat exactly max age ago")]
+    #[test_case(Heartbeat::Stale(chrono::Duration::minutes(2)), Some(chrono::Duration::minutes(10)), true ; "stale matches stale heartbeat")]
+    #[test_case(Heartbeat::Stale(chrono::Duration::minutes(2)), None, false ; "stale does not match missing heartbeat")]
+    #[tokio::test]
+    async fn test_conditions_match_heartbeat(
+        heartbeat: Heartbeat,
+        since_heartbeat: Option<chrono::Duration>,
+        expected: bool,
+    ) {
+        let reconciler = reconciler_with_clock(Box::new(FixedClock(fixed_time())));
+        // The status still says that the node is Ready although its kubelet went silent.
+        let condition = Condition {
+            heartbeat: Some(heartbeat),
+            ..condition("Ready", "True")
+        };
+        let node_condition = NodeCondition {
+            last_heartbeat_time: since_heartbeat
+                .map(|since_heartbeat| Time(fixed_time() - since_heartbeat)),
+            status: "True".to_string(),
+            type_: "Ready".to_string(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            expected,
+            reconciler.conditions_match(&condition, &node_condition)
+        );
+    }
+
+    #[test_case("True", true ; "matches status in set")]
+    #[test_case("Unknown", true ; "matches other status in set")]
+    #[test_case("False", false ; "does not match status not in set")]
+    #[test_case("TrueEnough", false ; "does not match status that contains a status in set")]
+    #[tokio::test]
+    async fn test_conditions_match_status_in(status: &str, expected: bool) {
+        let reconciler = reconciler();
+        let condition = Condition {
+            status: Status::In(vec!["True".to_string(), "Unknown".to_string()]),
+            ..condition("MemoryPressure", "")
+        };
+        let node_condition = NodeCondition {
+            status: status.to_string(),
+            type_: "MemoryPressure".to_string(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            expected,
+            reconciler.conditions_match(&condition, &node_condition)
+        );
+    }
+
+    #[test_case("Ready", true ; "matches equal type")]
+    #[test_case("NotReady", false ; "does not match type that contains literal")]
+    #[test_case("Ready.", false ; "does not match type that is matched by literal as regex")]
+    #[tokio::test]
+    async fn test_matching_conditions_literal(type_: &str, expected: bool) {
+        let reconciler = reconciler();
+        // The type set prunes node conditions with the literal as an escaped, anchored pattern.
+        let matcher = Configuration {
+            conditions: vec![Condition {
+                type_: Pattern::Literal("Ready".to_string()),
+                status: Status::Pattern(Pattern::Literal("False".to_string())),
+                ..condition("", "")
+            }],
+            ..Default::default()
+        };
+        let node_conditions = vec![NodeCondition {
+            status: "False".to_string(),
+            type_: type_.to_string(),
+            ..Default::default()
+        }];
+
+        assert_eq!(
+            expected,
+            reconciler
+                .matching_conditions("aks-zeus1-41950716-vmss000082", &node_conditions, &matcher)
+                .is_some()
+        );
+    }
+
+    #[test_case("MemoryPressure", true ; "matches first type")]
+    #[test_case("DiskPressure", true ; "matches second type")]
+    #[test_case("PIDPressure", false ; "does not match other type")]
+    #[tokio::test]
+    async fn test_matching_conditions_types(type_: &str, expected: bool) {
+        let reconciler = reconciler();
+        // Conditions with several types combine them into a single pattern that the type set
+        // prunes node conditions with like any other.
+        let matcher = Configuration {
+            conditions: vec![Condition {
+                type_: Pattern::Regex(Regex::new("^MemoryPressure$|^DiskPressure$").unwrap()),
+                ..condition("", "True")
+            }],
+            ..Default::default()
+        };
+        let node_conditions = vec![
+            NodeCondition {
+                status: "False".to_string(),
+                type_: "Ready".to_string(),
+                ..Default::default()
+            },
+            NodeCondition {
+                status: "True".to_string(),
+                type_: type_.to_string(),
+                ..Default::default()
+            },
+        ];
+
+        assert_eq!(
+            expected,
+            reconciler
+                .matching_conditions("aks-zeus1-41950716-vmss000082", &node_conditions, &matcher)
+                .is_some()
+        );
+    }
+
+    #[test_case("False", true ; "matches false")]
+    #[test_case("Unknown", true ; "matches unknown")]
+    #[test_case("True", false ; "does not match true")]
+    #[tokio::test]
+    async fn test_conditions_match_status_not(status: &str, expected: bool) {
+        let reconciler = reconciler();
+        let condition = Condition {
+            status: Status::NotPattern(Pattern::Regex(Regex::new("^True$").unwrap())),
+            ..condition("Ready", "")
+        };
+        let node_condition = NodeCondition {
+            status: status.to_string(),
+            type_: "Ready".to_string(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            expected,
+            reconciler.conditions_match(&condition, &node_condition)
+        );
+    }
+
+    #[test_case(Normalize::Off, "True ", "^True$", false ; "does not match padded status without normalization")]
+    #[test_case(Normalize::Trim, "True ", "^True$", true ; "matches padded status when trimmed")]
+    #[test_case(Normalize::Trim, " TRUE\t", "^True$", false ; "does not match mixed case status when only trimmed")]
+    #[test_case(Normalize::TrimLowercase, " TRUE\t", "^true$", true ; "matches padded mixed case status when lowercased")]
+    #[tokio::test]
+    async fn test_conditions_match_normalize(
+        normalize: Normalize,
+        status: &str,
+        pattern: &str,
+        expected: bool,
+    ) {
+        let reconciler = reconciler();
+        let condition = Condition {
+            normalize,
+            ..condition("(?i)^gpuhealthy$", pattern)
+        };
+        let node_condition = NodeCondition {
+            status: status.to_string(),
+            type_: "GpuHealthy".to_string(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            expected,
+            reconciler.conditions_match(&condition, &node_condition)
+        );
+    }
+
+    // Matchers with many conditions whose types overlap in different ways, including normalized
+    // and case-insensitive ones, and node conditions with padded and unrelated types.
+    fn synthetic_matchers(count: usize) -> Vec<Configuration> {
+        (0..count)
+            .map(|index| Configuration {
+                conditions: vec![
+                    condition(&format!("^Problem{}$", index % 37), "True|Unknown"),
+                    Condition {
+                        normalize: Normalize::Trim,
+                        ..condition(&format!("^Padded{}$", index % 11), "True")
+                    },
+                    condition(&format!("(?i)device{}", index % 5), "^False$"),
+                    condition("Problem1[0-9]", "True"),
+                ],
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    fn synthetic_node_conditions() -> Vec<NodeCondition> {
+        (0..50)
+            .flat_map(|index| {
+                let status = if index % 3 == 0 { "Unknown" } else { "True" };
+                [
+                    NodeCondition {
+                        status: status.to_string(),
+                        type_: format!("Problem{}", index),
+                        ..Default::default()
+                    },
+                    NodeCondition {
+                        status: "True".to_string(),
+                        type_: format!(" Padded{} ", index % 7),
+                        ..Default::default()
+                    },
+                    NodeCondition {
+                        status: "False".to_string(),
+                        type_: format!("DEVICE{}", index % 4),
+                        ..Default::default()
+                    },
+                ]
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_matching_conditions_agrees_with_matching_each_condition() {
+        let reconciler = reconciler();
+        let node_conditions = synthetic_node_conditions();
+
+        for matcher in synthetic_matchers(200) {
+            // Every condition is matched against every node condition individually.
+            let expected: Option<Vec<&NodeCondition>> = matcher
+                .conditions
+                .iter()
+                .map(|condition| {
+                    node_conditions.iter().find(|node_condition| {
+                        reconciler.conditions_match(condition, node_condition)
+                    })
+                })
+                .collect();
+
+            assert_eq!(
+                expected,
+                reconciler.matching_conditions("synthetic", &node_conditions, &matcher)
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_matching_conditions_agrees_without_type_set() {
+        let reconciler = reconciler();
+        let node_conditions = synthetic_node_conditions();
+
+        for matcher in synthetic_matchers(50) {
+            let expected = reconciler.matching_conditions("synthetic", &node_conditions, &matcher);
+            // A set that can't be compiled disables pruning.
+            let unpruned = Configuration {
+                conditions: matcher.conditions,
+                type_set: TypeSet(OnceLock::from(None)),
+                ..Default::default()
+            };
+
+            assert_eq!(
+                expected,
+                reconciler.matching_conditions("synthetic", &node_conditions, &unpruned)
+            );
+        }
+    }
+
+    // Not a precise benchmark, but guards against matching becoming pathologically slow for
+    // clusters with many matchers and node conditions.
+    #[tokio::test]
+    async fn test_matching_conditions_scales_to_many_matchers() {
+        let reconciler = reconciler();
+        let node_conditions = synthetic_node_conditions();
+        let matchers = synthetic_matchers(1000);
+
+        let start = std::time::Instant::now();
+        let mut eligible = 0;
+        for _ in 0..10 {
+            eligible += matchers
+                .iter()
+                .filter_map(|matcher| {
+                    reconciler.matching_conditions("synthetic", &node_conditions, matcher)
+                })
+                .count();
+        }
+        let elapsed = start.elapsed();
+
+        assert!(eligible > 0);
+        assert!(
+            elapsed < std::time::Duration::from_secs(30),
+            "matching took {:?}",
+            elapsed
+        );
+    }
+
+    #[tokio::test]
+    async fn test_start_matches_status_in_set() {
+        let matchers = vec![Configuration {
+            taints: vec![Taint {
+                effect: "NoSchedule".to_string(),
+                key: "not-ready".to_string(),
+                time_added: None,
+                value: None,
+            }],
+            conditions: vec![Condition {
+                status: Status::In(vec!["False".to_string(), "Unknown".to_string()]),
+                ..condition("Ready", "")
+            }],
+            ..Default::default()
+        }];
+        let mut handle = setup("list-nodes-single-eligible.json", matchers).await;
+
+        let (request, _) = handle.next_request().await.expect("PUT node not called");
+        assert_eq!(request.method(), http::Method::PUT);
+        assert_eq!(
+            request.uri().to_string(),
+            "/api/v1/nodes/aks-zeus1-41950716-vmss000082?&fieldManager=tainter"
+        );
+    }
+
+    fn condition(type_: &str, status: &str) -> Condition {
+        Condition {
+            type_: Pattern::Regex(Regex::new(type_).unwrap()),
+            status: Status::Pattern(Pattern::Regex(Regex::new(status).unwrap())),
+            reason: None,
+            message: None,
+            max_age: None,
+            heartbeat: None,
+            normalize: Normalize::Off,
+        }
+    }
+
+    // test_case runs each case by calling the test function, so the logs_contain that traced_test
+    // defines in the generated case is not in scope in the test function. The case's span is still
+    // current though, which identifies the case's logs in the same way.
+    fn logs_contain(val: &str) -> bool {
+        let span = tracing::Span::current();
+        let scope = span
+            .metadata()
+            .expect("test case should be annotated with traced_test")
+            .name();
+        tracing_test::internal::logs_with_scope_contain(scope, val)
+    }
+
+    // A reconciler for tests that exercise matching logic without making any requests. Must be
+    // called from within a Tokio runtime.
+    fn reconciler() -> Reconciler {
+        reconciler_with_clock(Box::new(UtcClock))
+    }
+
+    fn reconciler_with_clock(clock: Box<dyn Clock>) -> Reconciler {
+        let (mock_service, _) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+        let client = Client::new(mock_service, "default");
+
+        Reconciler::new(client, vec![], Options::default(), Metrics::new(), clock)
+    }
+
+    // The events with which the watcher lists the nodes when it (re)starts.
+    fn relist(nodes: Vec<Node>) -> Vec<Result<watcher::Event<Node>, watcher::Error>> {
+        std::iter::once(watcher::Event::Init)
+            .chain(nodes.into_iter().map(watcher::Event::InitApply))
+            .chain(std::iter::once(watcher::Event::InitDone))
+            .map(Ok)
+            .collect()
+    }
+
+    fn fixed_time() -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2024-05-12T11:21:10Z")
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn get_file_content(path: PathBuf) -> String {
+        fs::read_to_string(path).unwrap()
+    }
+
+    fn get_test_file(name: &str) -> String {
+        get_file_content(
+            Path::new(".")
+                .join("src")
+                .join("reconciler")
+                .join("testfiles")
+                .join(name),
+        )
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_start_cascades_to_matchers_that_become_eligible_after_update() {
         let matchers = vec![
             Configuration {
-                taint: Taint {
-                    effect: "NoExecute".to_string(),
-                    key: "pressure".to_string(),
+                taints: vec![Taint {
+                    effect: "PreferNoSchedule".to_string(),
+                    key: "event".to_string(),
                     time_added: None,
-                    value: Some("memory".to_string()),
-                },
-                conditions: vec![Condition {
-                    type_: Regex::new("OutOfMemory").unwrap(),
-                    status: Regex::new("True").unwrap(),
+                    value: Some("scheduled".to_string()),
                 }],
+                conditions: vec![condition("VMEventScheduled", "True")],
+                weight: Some(40),
+                ..Default::default()
             },
+            // Only eligible once the first matcher's update has labelled the node with its weight.
             Configuration {
-                taint: Taint {
+                taints: vec![Taint {
                     effect: "NoSchedule".to_string(),
-                    key: "network-partition".to_string(),
+                    key: "weighted".to_string(),
                     time_added: None,
-                    value: None,
-                },
-                conditions: vec![
-                    Condition {
-                        type_: Regex::new("NetworkInterfaceCard").unwrap(),
-                        status: Regex::new("Kaput|Ruined").unwrap(),
-                    },
-                    Condition {
-                        type_: Regex::new("PrivateLink").unwrap(),
-                        status: Regex::new("Severed").unwrap(),
-                    },
-                ],
+                    value: Some("${label:tainter.io/prefer-weight}".to_string()),
+                }],
+                conditions: vec![condition("VMEventScheduled", "True")],
+                ..Default::default()
             },
         ];
-        let mut handle = setup("list-nodes-multiple-eligible-regex.json", matchers).await;
+        let options = Options {
+            cascade_max_depth: Some(2),
+            ..Default::default()
+        };
+        let mut handle = setup_with_options(
+            "list-nodes-single-eligible.json",
+            matchers,
+            options,
+            Metrics::new(),
+        )
+        .await;
 
-        let (request, response) = handle
-            .next_request()
-            .await
-            .expect("PUT node not called for aks-artemis1-41950716-vmss000082");
+        let (request, response) = handle.next_request().await.expect("PUT node not called");
         assert_eq!(request.method(), http::Method::PUT);
+        let labelled_node = node_from_body(request).await;
+        let taints = labelled_node
+            .spec
+            .as_ref()
+            .unwrap()
+            .taints
+            .as_ref()
+            .unwrap();
+        assert!(!taints.iter().any(|taint| taint.key == "weighted"));
+        response.send_response(
+            Response::builder()
+                .body(Body::from(serde_json::to_vec(&labelled_node).unwrap()))
+                .unwrap(),
+        );
+
+        let (request, response) = handle.next_request().await.expect("GET node not called");
+        assert_eq!(request.method(), http::Method::GET);
         assert_eq!(
             request.uri().to_string(),
-            "/api/v1/nodes/aks-artemis1-41950716-vmss000082?&fieldManager=tainter"
+            "/api/v1/nodes/aks-zeus1-41950716-vmss000082"
+        );
+        response.send_response(
+            Response::builder()
+                .body(Body::from(serde_json::to_vec(&labelled_node).unwrap()))
+                .unwrap(),
         );
-        let node = node_from_body(request).await;
-        let taints = node.spec.unwrap().taints.unwrap();
-        assert_eq!(taints.len(), 2);
-        let taint = taints.get(1).unwrap();
-        let expected = Taint {
-            effect: "NoExecute".to_string(),
-            key: "pressure".to_string(),
-            time_added: Some(Time(Utc::now())),
-            value: Some("memory".to_string()),
-        };
-        assert_eq!(taint.effect, expected.effect);
-        assert_eq!(taint.key, expected.key);
-        assert_eq!(taint.value, expected.value);
-        assert!(within_duration(
-            taint.time_added.as_ref().unwrap().0,
-            expected.time_added.as_ref().unwrap().0,
-            chrono::Duration::seconds(5),
-        ));
 
+        let (request, response) = handle.next_request().await.expect("PUT node not called");
+        assert_eq!(request.method(), http::Method::PUT);
+        let cascaded_node = node_from_body(request).await;
+        let taints = cascaded_node
+            .spec
+            .as_ref()
+            .unwrap()
+            .taints
+            .as_ref()
+            .unwrap();
+        let taint = taints.iter().find(|taint| taint.key == "weighted").unwrap();
+        assert_eq!(Some("40".to_string()), taint.value);
         response.send_response(
             Response::builder()
-                .body(Body::from(
-                    get_test_file("node-put-success.json").into_bytes(),
-                ))
+                .body(Body::from(serde_json::to_vec(&cascaded_node).unwrap()))
+                .unwrap(),
+        );
+
+        // The node has all of its taints after the second update, so re-evaluating it once more
+        // stops the cascade.
+        let (request, response) = handle.next_request().await.expect("GET node not called");
+        assert_eq!(request.method(), http::Method::GET);
+        response.send_response(
+            Response::builder()
+                .body(Body::from(serde_json::to_vec(&cascaded_node).unwrap()))
                 .unwrap(),
         );
 
-        let (request, response) = handle
-            .next_request()
-            .await
-            .expect("PUT node not called for aks-poseidon1-41950716-vmss000082");
-        assert_eq!(request.method(), http::Method::PUT);
+        let (request, _) = handle.next_request().await.expect("watch nodes not called");
+        assert_eq!(request.method(), http::Method::GET);
+        assert!(request.uri().to_string().contains("watch=true"));
+        assert!(logs_contain("Re-evaluating node after adding taints"));
+    }
+
+    #[tokio::test]
+    async fn test_self_test_counts_eligible_nodes_per_matcher() {
+        let (mock_service, mut handle) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+        let client = Client::new(mock_service, "default");
+        let taint = |key: &str| Taint {
+            effect: "NoSchedule".to_string(),
+            key: key.to_string(),
+            time_added: None,
+            value: None,
+        };
+        let matchers = vec![
+            Configuration {
+                taints: vec![taint("oom")],
+                conditions: vec![condition("OutOfMemory", "True")],
+                ..Default::default()
+            },
+            Configuration {
+                taints: vec![taint("disk")],
+                conditions: vec![condition("DiskPressure", "True")],
+                name: Some("disk-pressure".to_string()),
+                enabled: false,
+                ..Default::default()
+            },
+            Configuration {
+                taints: vec![taint("event")],
+                conditions: vec![condition("VMEventScheduled", "True")],
+                ..Default::default()
+            },
+        ];
+        let reconciler = Reconciler::new(
+            client,
+            matchers,
+            Options::default(),
+            Metrics::new(),
+            Box::new(UtcClock),
+        );
+
+        tokio::spawn(async move {
+            let (request, response) = handle.next_request().await.expect("list nodes not called");
+            assert_eq!(request.method(), http::Method::GET);
+            let body = get_test_file("list-nodes-multiple-eligible-regex.json");
+            response.send_response(
+                Response::builder()
+                    .body(Body::from(body.into_bytes()))
+                    .unwrap(),
+            );
+        });
+
+        let summary = reconciler.self_test().await.unwrap();
+
+        assert_eq!(4, summary.nodes);
         assert_eq!(
-            request.uri().to_string(),
-            "/api/v1/nodes/aks-poseidon1-41950716-vmss000082?&fieldManager=tainter"
+            "Nodes: 4\n\
+            Matcher oom: 2 eligible node(s)\n\
+            Matcher disk-pressure: 1 eligible node(s) (disabled)\n\
+            Matcher event: 0 eligible node(s)\n",
+            summary.to_string()
         );
-        let node = node_from_body(request).await;
-        let taints = node.spec.unwrap().taints.unwrap();
-        assert_eq!(taints.len(), 3);
-        let taint = taints.get(1).unwrap();
-        let expected = Taint {
-            effect: "NoExecute".to_string(),
-            key: "pressure".to_string(),
-            time_added: Some(Time(Utc::now())),
-            value: Some("memory".to_string()),
-        };
-        assert_eq!(taint.effect, expected.effect);
-        assert_eq!(taint.key, expected.key);
-        assert_eq!(taint.value, expected.value);
-        assert!(within_duration(
-            taint.time_added.as_ref().unwrap().0,
-            expected.time_added.as_ref().unwrap().0,
-            chrono::Duration::seconds(5),
-        ));
+    }
 
-        let taint = taints.get(2).unwrap();
-        let expected = Taint {
+    #[tokio::test]
+    async fn test_explain_identifies_failing_conditions() {
+        let (mock_service, mut handle) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+        let client = Client::new(mock_service, "default");
+        let taint = |key: &str| Taint {
             effect: "NoSchedule".to_string(),
-            key: "network-partition".to_string(),
+            key: key.to_string(),
             time_added: None,
             value: None,
         };
-        assert_eq!(taint.effect, expected.effect);
-        assert_eq!(taint.key, expected.key);
-        assert_eq!(taint.value, expected.value);
-        assert_eq!(taint.time_added, expected.time_added);
-
-        response.send_response(
-            Response::builder()
-                .body(Body::from(
-                    get_test_file("node-put-success.json").into_bytes(),
-                ))
-                .unwrap(),
+        let matchers = vec![
+            Configuration {
+                taints: vec![taint("event")],
+                conditions: vec![condition("VMEventScheduled", "True")],
+                ..Default::default()
+            },
+            Configuration {
+                taints: vec![taint("not-ready")],
+                conditions: vec![
+                    condition("^Ready$", "^True$"),
+                    condition("OutOfMemory", "True"),
+                ],
+                enabled: false,
+                ..Default::default()
+            },
+        ];
+        let reconciler = Reconciler::new(
+            client,
+            matchers,
+            Options::default(),
+            Metrics::new(),
+            Box::new(UtcClock),
         );
 
-        let (request, _) = handle.next_request().await.expect("watch nodes not called");
-        assert_eq!(request.method(), http::Method::GET);
+        tokio::spawn(async move {
+            let (request, response) = handle.next_request().await.expect("get node not called");
+            assert_eq!(request.method(), http::Method::GET);
+            assert_eq!(
+                request.uri().to_string(),
+                "/api/v1/nodes/aks-zeus1-41950716-vmss000082"
+            );
+            let list: serde_json::Value =
+                serde_json::from_str(&get_test_file("list-nodes-single-eligible.json")).unwrap();
+            let node = &list["items"][0];
+            response.send_response(
+                Response::builder()
+                    .body(Body::from(node.to_string().into_bytes()))
+                    .unwrap(),
+            );
+        });
+
+        let explanation = reconciler
+            .explain("aks-zeus1-41950716-vmss000082")
+            .await
+            .unwrap();
+
+        assert_eq!(None, explanation.skipped);
+        assert_eq!(2, explanation.matchers.len());
+        let event = &explanation.matchers[0];
+        assert!(event.eligible);
+        assert!(event.reasons.is_empty());
+        let not_ready = &explanation.matchers[1];
+        assert!(!not_ready.eligible);
         assert_eq!(
-            request.uri().to_string(),
-            "/api/v1/nodes?&watch=true&timeoutSeconds=290&\
-        allowWatchBookmarks=true&resourceVersion=test"
+            vec![
+                "matcher is disabled",
+                "condition type Ready matched but status False does not match ^True$",
+                "no node condition has a type that matches OutOfMemory",
+            ],
+            not_ready.reasons
         );
-
-        assert!(logs_contain(
-            r#"Processing node node_name="aks-ares1-41950716-vmss000082""#
-        ));
-        assert!(logs_contain(
-            r#"Processing node node_name="aks-artemis1-41950716-vmss000082""#
-        ));
-        assert!(logs_contain(
-            r#"Adding taints to node node="aks-artemis1-41950716-vmss000082" taints="[Taint { effect: \"NoExecute\", key: \"pressure\""#
-        ));
-        assert!(logs_contain(
-            r#"Successfully added taints to node node="aks-artemis1-41950716-vmss000082" taints="[Taint { effect: \"NoExecute\", key: \"pressure\""#
-        ));
-        assert!(logs_contain(
-            r#"Node matches condition node="aks-artemis1-41950716-vmss000082" node_condition="NodeCondition { last_heartbeat_time: Some(Time(2024-05-12T11:21:10Z)), last_transition_time: Some(Time(2024-05-07T08:32:09Z)), message: Some(\"The VM has no surplus memory\"), reason: Some(\"NoSurplusMemory\"), status: \"True\", type_: \"OutOfMemory\" }" condition="Condition { type_: Regex(\"OutOfMemory\"), status: Regex(\"True\") }""#
-        ));
-        assert!(logs_contain(
-            r#"Processing node node_name="aks-athena1-41950716-vmss000082""#
-        ));
-        assert!(logs_contain(
-            r#"Processing node node_name="aks-poseidon1-41950716-vmss000082""#
-        ));
     }
 
     #[tokio::test]
-    #[traced_test]
-    async fn test_start_processes_node_and_logs_error_if_update_fails() {
-        let matchers = vec![Configuration {
-            taint: Taint {
-                effect: "NoExecute".to_string(),
+    async fn test_explain_evaluates_schedule_stale_conditions_and_transitions() {
+        let (mock_service, mut handle) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+        let client = Client::new(mock_service, "default");
+        let matcher = || Configuration {
+            taints: vec![Taint {
+                effect: "NoSchedule".to_string(),
                 key: "event".to_string(),
                 time_added: None,
                 value: None,
-            },
-            conditions: vec![Condition {
-                type_: Regex::new("VMEventScheduled").unwrap(),
-                status: Regex::new("True").unwrap(),
             }],
-        }];
-        let mut handle = setup("list-nodes-single-eligible.json", matchers).await;
-
-        let (request, response) = handle.next_request().await.expect("PUT node not called");
-        assert_eq!(request.method(), http::Method::PUT);
-        assert_eq!(
-            request.uri().to_string(),
-            "/api/v1/nodes/aks-zeus1-41950716-vmss000082?&fieldManager=tainter"
+            conditions: vec![condition("VMEventScheduled", "True")],
+            ..Default::default()
+        };
+        let matchers = vec![
+            // The fixed time is 11:21, after the end of the window.
+            Configuration {
+                schedule: Some(Schedule {
+                    cron: cron::Schedule::from_str("0 0 10 * * *").unwrap(),
+                    duration: chrono::Duration::hours(1),
+                }),
+                ..matcher()
+            },
+            Configuration {
+                on_transition_only: true,
+                ..matcher()
+            },
+        ];
+        let options = Options {
+            stale_condition_tolerance: Some(chrono::Duration::hours(1)),
+            ..Default::default()
+        };
+        let reconciler = Reconciler::new(
+            client,
+            matchers,
+            options,
+            Metrics::new(),
+            Box::new(FixedClock(fixed_time())),
         );
+        // The node's generation changed days after its condition transitioned.
+        reconciler.observe_generation("aks-zeus1-41950716-vmss000082", Some(1));
+        reconciler.observe_generation("aks-zeus1-41950716-vmss000082", Some(2));
 
-        let node_put_response_body = get_test_file("node-put-invalid-response.json");
-
-        response.send_response(
-            Response::builder()
-                .body(Body::from(node_put_response_body.into_bytes()))
-                .unwrap(),
-        );
+        tokio::spawn(async move {
+            let (_, response) = handle.next_request().await.expect("get node not called");
+            let list: serde_json::Value =
+                serde_json::from_str(&get_test_file("list-nodes-single-eligible.json")).unwrap();
+            response.send_response(
+                Response::builder()
+                    .body(Body::from(list["items"][0].to_string().into_bytes()))
+                    .unwrap(),
+            );
+        });
 
-        let (_, _) = handle.next_request().await.expect("watch nodes not called");
+        let explanation = reconciler
+            .explain("aks-zeus1-41950716-vmss000082")
+            .await
+            .unwrap();
 
-        assert!(logs_contain(
-            r#"Error adding taints to node error="Error deserializing response" node="aks-zeus1-41950716-vmss000082" taints="[Taint { effect: \"NoExecute\", key: \"event\""#
-        ))
+        let stale =
+            "condition type VMEventScheduled transitioned before the node's generation changed";
+        assert_eq!(
+            vec!["current time is outside of the matcher's schedule", stale],
+            explanation.matchers[0].reasons
+        );
+        assert_eq!(
+            vec![
+                stale,
+                "matcher only adds taints when its conditions transition, which they did not",
+            ],
+            explanation.matchers[1].reasons
+        );
+        assert!(!explanation.matchers[1].eligible);
     }
 
-    #[tokio::test]
-    #[traced_test]
-    async fn test_start_adds_taint_only_if_node_does_not_already_have_it() {
-        let matchers = vec![Configuration {
-            taint: Taint {
+    fn graceful_evict_matchers() -> Vec<Configuration> {
+        vec![Configuration {
+            taints: vec![Taint {
                 effect: "NoExecute".to_string(),
-                key: "node.kubernetes.io/out-of-service".to_string(),
+                key: "event".to_string(),
                 time_added: None,
                 value: None,
-            },
-            conditions: vec![Condition {
-                type_: Regex::new("Ready").unwrap(),
-                status: Regex::new("False|Unknown").unwrap(),
             }],
-        }];
-        let mut handle = setup("list-nodes-eligible-and-has-taint.json", matchers).await;
+            conditions: vec![condition("VMEventScheduled", "True")],
+            graceful_evict: true,
+            ..Default::default()
+        }]
+    }
 
-        let (request, _) = handle.next_request().await.expect("watch nodes not called");
-        assert_eq!(request.method(), http::Method::GET);
+    #[tokio::test]
+    #[traced_test]
+    async fn test_start_adds_no_schedule_taint_before_evicting_pods() {
+        let mut handle = setup("list-nodes-single-eligible.json", graceful_evict_matchers()).await;
+
+        // No pods are evicted before the node has the NoSchedule taint.
+        let (request, _) = handle.next_request().await.expect("PUT node not called");
+        assert_eq!(request.method(), http::Method::PUT);
+        let node = node_from_body(request).await;
+        let taints = node.spec.unwrap().taints.unwrap();
+        let effects: Vec<&str> = taints
+            .iter()
+            .filter(|taint| taint.key == "event")
+            .map(|taint| taint.effect.as_str())
+            .collect();
+        assert_eq!(vec!["NoSchedule"], effects);
         assert_eq!(
-            request.uri().to_string(),
-            "/api/v1/nodes?&watch=true&timeoutSeconds=290&\
-        allowWatchBookmarks=true&resourceVersion=test"
+            Some(&"event".to_string()),
+            node.metadata
+                .annotations
+                .unwrap()
+                .get(EVICTING_TAINTS_ANNOTATION)
         );
-
         assert!(logs_contain(
-            r#"Node matches conditions but already has taint node="aks-artemis1-41950716-vmss000082" taint="node.kubernetes.io/out-of-service:NoExecute"#
-        ))
+            "Adding NoSchedule taints to node before evicting its pods"
+        ));
     }
 
     #[tokio::test]
-    #[traced_test]
-    async fn test_start_gracefully_handles_conflict_error() {
-        let matchers = vec![Configuration {
-            taint: Taint {
+    async fn test_start_writes_no_schedule_taint_with_field_manager_of_its_matcher() {
+        let mut matchers = vec![Configuration {
+            taints: vec![Taint {
                 effect: "NoSchedule".to_string(),
                 key: "not-ready".to_string(),
                 time_added: None,
                 value: None,
-            },
-            conditions: vec![Condition {
-                type_: Regex::new("Ready").unwrap(),
-                status: Regex::new("False|Unknown").unwrap(),
             }],
+            conditions: vec![condition("Ready", "False")],
+            ..Default::default()
         }];
+        matchers.extend(
+            graceful_evict_matchers()
+                .into_iter()
+                .map(|matcher| Configuration {
+                    field_manager: Some("tainter-event".to_string()),
+                    ..matcher
+                }),
+        );
         let mut handle = setup("list-nodes-single-eligible.json", matchers).await;
 
-        let (request, response) = handle.next_request().await.expect("PUT node not called");
+        for (field_manager, key) in [("tainter", "not-ready"), ("tainter-event", "event")] {
+            let (request, response) = handle.next_request().await.expect("PUT node not called");
+            assert_eq!(request.method(), http::Method::PUT);
+            assert_eq!(
+                request.uri().to_string(),
+                format!(
+                    "/api/v1/nodes/aks-zeus1-41950716-vmss000082?&fieldManager={field_manager}"
+                )
+            );
+            let taints = node_from_body(request).await.spec.unwrap().taints.unwrap();
+            assert!(taints
+                .iter()
+                .any(|taint| taint.key == key && taint.effect == "NoSchedule"));
+            response.send_response(
+                Response::builder()
+                    .body(Body::from(
+                        get_test_file("node-put-success.json").into_bytes(),
+                    ))
+                    .unwrap(),
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_start_removes_no_schedule_taint_once_conditions_clear_before_eviction() {
+        // The matcher only adds its taints, but the NoSchedule taint added before evicting the
+        // pods is not one of them.
+        let matchers = graceful_evict_matchers()
+            .into_iter()
+            .map(|matcher| Configuration {
+                conditions: vec![condition("VMEventScheduled", "False")],
+                ..matcher
+            })
+            .collect();
+        let mut handle = setup("list-nodes-evicting.json", matchers).await;
+
+        let (request, _) = handle.next_request().await.expect("PUT node not called");
         assert_eq!(request.method(), http::Method::PUT);
-        assert_eq!(
-            request.uri().to_string(),
-            "/api/v1/nodes/aks-zeus1-41950716-vmss000082?&fieldManager=tainter"
-        );
+        let node = node_from_body(request).await;
+        let taints = node.spec.unwrap().taints.unwrap();
+        assert!(!taints.iter().any(|taint| taint.key == "event"));
+        let annotations = node.metadata.annotations.unwrap();
+        assert!(!annotations.contains_key(EVICTING_TAINTS_ANNOTATION));
+        assert!(!annotations.contains_key(MANAGED_TAINTS_ANNOTATION));
+    }
 
-        let node_put_response_body = get_test_file("node-put-conflict-response.json");
+    #[test_case(201, r#"{"apiVersion":"v1","kind":"Status","metadata":{},"status":"Success","code":201}"#, "Evicted pod from node before adding taints" ; "eviction succeeds")]
+    #[test_case(429, r#"{"apiVersion":"v1","kind":"Status","metadata":{},"status":"Failure","message":"Cannot evict pod as it would violate the pod's disruption budget.","reason":"TooManyRequests","code":429}"#, "Error evicting pod from node, retrying on the node's next event" ; "eviction violates pod disruption budget")]
+    #[tokio::test]
+    #[traced_test]
+    async fn test_start_evicts_pods_and_defers_no_execute_taint(
+        eviction_status: u16,
+        eviction_body: &str,
+        expected_log: &str,
+    ) {
+        let mut handle = setup("list-nodes-evicting.json", graceful_evict_matchers()).await;
 
+        let (request, response) = handle.next_request().await.expect("list pods not called");
+        assert_eq!(request.method(), http::Method::GET);
+        assert_eq!(request.uri().path(), "/api/v1/pods");
+        assert!(request
+            .uri()
+            .query()
+            .unwrap()
+            .contains("fieldSelector=spec.nodeName%3Daks-zeus1-41950716-vmss000082"));
         response.send_response(
             Response::builder()
-                .status(409)
-                .body(Body::from(node_put_response_body.into_bytes()))
+                .body(Body::from(get_test_file("list-pods.json").into_bytes()))
                 .unwrap(),
         );
 
-        let (_, _) = handle.next_request().await.expect("watch nodes not called");
+        // Only the pod that neither tolerates the taint nor is a mirror pod or has terminated is
+        // evicted.
+        let (request, response) = handle.next_request().await.expect("evict pod not called");
+        assert_eq!(request.method(), http::Method::POST);
+        assert_eq!(
+            request.uri().path(),
+            "/api/v1/namespaces/default/pods/web-0/eviction"
+        );
+        response.send_response(
+            Response::builder()
+                .status(eviction_status)
+                .body(Body::from(eviction_body.to_string().into_bytes()))
+                .unwrap(),
+        );
 
+        // The pod was still on the node, so the NoExecute taint waits for the node's next event
+        // rather than evicting the pod abruptly.
+        let (request, _) = handle.next_request().await.expect("watch nodes not called");
+        assert_eq!(request.method(), http::Method::GET);
+        assert_eq!(request.uri().path(), "/api/v1/nodes");
+        assert!(logs_contain(expected_log));
         assert!(logs_contain(
-            r#"Received conflict error when trying to add taints to node error="ApiError: Operation cannot be fulfilled on nodes \"aks-zeus1-41950716-vmss000082\": the object has been modified; please apply your changes to the latest version and try again: Conflict (ErrorResponse { status: \"Failure\", message: \"Operation cannot be fulfilled on nodes \\\"aks-zeus1-41950716-vmss000082\\\": the object has been modified; please apply your changes to the latest version and try again\", reason: \"Conflict\", code: 409 })" node="aks-zeus1-41950716-vmss000082" taints="[Taint { effect: \"NoSchedule\", key: \"not-ready\", time_added: None, value: None }]"#
+            "Deferring NoExecute taints until the node's pods are evicted"
         ));
-        assert!(!logs_contain("Error adding taint to node"))
     }
 
-    fn get_file_content(path: PathBuf) -> String {
-        fs::read_to_string(path).unwrap()
+    #[tokio::test]
+    async fn test_start_adds_no_execute_taint_once_pods_are_evicted() {
+        let mut handle = setup("list-nodes-evicting.json", graceful_evict_matchers()).await;
+
+        let (request, response) = handle.next_request().await.expect("list pods not called");
+        assert_eq!(request.method(), http::Method::GET);
+        assert_eq!(request.uri().path(), "/api/v1/pods");
+        response.send_response(
+            Response::builder()
+                .body(Body::from(
+                    r#"{"apiVersion":"v1","kind":"PodList","metadata":{},"items":[]}"#
+                        .as_bytes()
+                        .to_vec(),
+                ))
+                .unwrap(),
+        );
+
+        let (request, _) = handle.next_request().await.expect("PUT node not called");
+        assert_eq!(request.method(), http::Method::PUT);
+        let node = node_from_body(request).await;
+        let taints = node.spec.unwrap().taints.unwrap();
+        let effects: Vec<&str> = taints
+            .iter()
+            .filter(|taint| taint.key == "event")
+            .map(|taint| taint.effect.as_str())
+            .collect();
+        assert_eq!(vec!["NoExecute"], effects);
+        assert!(!node
+            .metadata
+            .annotations
+            .unwrap()
+            .contains_key(EVICTING_TAINTS_ANNOTATION));
     }
 
-    fn get_test_file(name: &str) -> String {
-        get_file_content(
-            Path::new(".")
-                .join("src")
-                .join("reconciler")
-                .join("testfiles")
-                .join(name),
-        )
+    #[test_case(Some("event"), Some("Exists"), None, Some("NoExecute"), true ; "exists with key and effect")]
+    #[test_case(None, Some("Exists"), None, None, true ; "exists without key tolerates all taints")]
+    #[test_case(Some("event"), Some("Equal"), Some("scheduled"), None, true ; "equal with value")]
+    #[test_case(Some("event"), None, Some("scheduled"), None, true ; "equal by default")]
+    #[test_case(Some("event"), Some("Equal"), Some("other"), None, false ; "equal with other value")]
+    #[test_case(Some("other"), Some("Exists"), None, None, false ; "exists with other key")]
+    #[test_case(Some("event"), Some("Exists"), None, Some("NoSchedule"), false ; "other effect")]
+    fn test_tolerates(
+        key: Option<&str>,
+        operator: Option<&str>,
+        value: Option<&str>,
+        effect: Option<&str>,
+        expected: bool,
+    ) {
+        let toleration = Toleration {
+            key: key.map(String::from),
+            operator: operator.map(String::from),
+            value: value.map(String::from),
+            effect: effect.map(String::from),
+            toleration_seconds: None,
+        };
+        let taint = Taint {
+            effect: "NoExecute".to_string(),
+            key: "event".to_string(),
+            time_added: None,
+            value: Some("scheduled".to_string()),
+        };
+
+        assert_eq!(expected, tolerates(&toleration, &taint));
+    }
+
+    // A watch response body with a single event of the given type, e.g. ADDED or MODIFIED.
+    fn watch_event_body(event_type: &str, node: &Node) -> Body {
+        let event = serde_json::json!({
+            "type": event_type,
+            "object": node,
+        });
+
+        Body::from(format!("{}\n", event).into_bytes())
     }
 
     async fn node_from_body(request: Request<Body>) -> Node {
@@ -578,8 +9581,7 @@ mod tests {
         let from = target - duration;
         let to = target + duration;
 
-        let within_duration = from <= time && time <= to;
-        within_duration
+        from <= time && time <= to
     }
 
     #[tokio::test]
@@ -590,18 +9592,23 @@ mod tests {
         let client = Client::new(mock_service, "default");
 
         let matchers = vec![Configuration {
-            taint: Taint {
+            taints: vec![Taint {
                 effect: "NoExecute".to_string(),
                 key: "bird".to_string(),
                 time_added: None,
                 value: Some("flamingo".to_string()),
-            },
-            conditions: vec![Condition {
-                type_: Regex::new("animal").unwrap(),
-                status: Regex::new("(?i)flamingo").unwrap(),
             }],
+            conditions: vec![condition("animal", "(?i)flamingo")],
+            ..Default::default()
         }];
-        let reconciler = Reconciler::new(client, matchers);
+        let metrics = Metrics::new();
+        let reconciler = Reconciler::new(
+            client,
+            matchers,
+            Options::default(),
+            metrics.clone(),
+            Box::new(UtcClock),
+        );
 
         tokio::spawn(async move {
             reconciler.start().await;
@@ -626,6 +9633,14 @@ mod tests {
 
         assert!(logs_contain(
             r#"error="failed to perform initial object list: ServiceError: some connection error""#
-        ))
+        ));
+        assert_eq!(
+            1,
+            metrics
+                .watch_errors
+                .with_label_values(&["initial_list"])
+                .get()
+        );
+        assert_eq!(0, metrics.watch_restarts.get());
     }
 }
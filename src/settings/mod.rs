@@ -1,18 +1,42 @@
 use std::borrow::Cow;
+use std::collections::BTreeMap;
 use std::str::FromStr;
+use std::time::Duration;
 
-use config::{Config, ConfigError};
+use config::{Config, ConfigError, Environment};
+use k8s_openapi::serde_json;
 use regex::Regex;
 use serde::de::Error;
-use serde::{Deserialize, Deserializer};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use strum_macros::{Display, EnumString};
 use thiserror::Error;
 use validator::{Validate, ValidationError, ValidationErrors};
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Validate, Debug)]
 pub struct Server {
+    // If false, Tainter runs the reconciler without serving any endpoints.
+    #[serde(default = "default_server_enabled")]
+    pub enabled: bool,
     pub host: String,
     pub port: u16,
+    // The server only serves health, metrics and other operational endpoints, so a single worker
+    // thread suffices.
+    #[serde(default = "default_server_workers")]
+    #[validate(range(min = 1))]
+    pub workers: usize,
+    // The admin endpoints, i.e. pausing, resuming and ad-hoc taints, decide whether and how Tainter
+    // changes nodes, so they are only served if enabled, and only to callers that the cluster's
+    // RBAC allows to update nodes.
+    #[serde(default)]
+    pub admin_endpoints: bool,
+}
+
+fn default_server_workers() -> usize {
+    1
+}
+
+fn default_server_enabled() -> bool {
+    true
 }
 
 // https://serde.rs/field-attrs.html#deserialize_with.
@@ -26,42 +50,663 @@ where
     tracing::Level::from_str(s.as_str()).map_err(Error::custom)
 }
 
-#[derive(Deserialize, Debug)]
+// Durations are written in a human-friendly format such as "5m" or "1h 30m".
+// See https://docs.rs/humantime/latest/humantime/fn.parse_duration.html.
+fn duration_from_string<'de, D>(deserializer: D) -> Result<Duration, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s: String = Deserialize::deserialize(deserializer)?;
+
+    humantime::parse_duration(s.as_str()).map_err(Error::custom)
+}
+
+fn optional_duration_from_string<'de, D>(deserializer: D) -> Result<Option<Duration>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s: Option<String> = Deserialize::deserialize(deserializer)?;
+
+    s.map(|s| humantime::parse_duration(s.as_str()).map_err(Error::custom))
+        .transpose()
+}
+
+// Settings are serialized in the format that they are deserialized from, so that the effective
+// configuration can be printed.
+fn tracing_level_to_string<S>(level: &tracing::Level, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_str(level.as_str().to_lowercase().as_str())
+}
+
+fn duration_to_string<S>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_str(humantime::format_duration(*duration).to_string().as_str())
+}
+
+fn optional_duration_to_string<S>(
+    duration: &Option<Duration>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match duration {
+        Some(duration) => duration_to_string(duration, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+fn default_with_current_span() -> bool {
+    true
+}
+
+#[derive(Serialize, Deserialize, Validate, Debug)]
 pub struct Log {
-    #[serde(deserialize_with = "tracing_level_from_string")]
+    #[serde(
+        deserialize_with = "tracing_level_from_string",
+        serialize_with = "tracing_level_to_string"
+    )]
     pub max_level: tracing::Level,
+    // Include the current span, e.g. the node being reconciled, in each log line.
+    #[serde(default = "default_with_current_span")]
+    pub with_current_span: bool,
+    // If set, logs are also appended to the file at this path. The directory must exist.
+    pub file: Option<String>,
+    // Aggregate the logs of nodes processed during event storms.
+    #[validate(nested)]
+    pub aggregation: Option<LogAggregation>,
+}
+
+#[derive(Serialize, Deserialize, Validate, Debug)]
+pub struct LogAggregation {
+    // The number of nodes processed within the window above which logs are aggregated.
+    #[validate(range(min = 1))]
+    pub threshold: usize,
+    #[serde(
+        deserialize_with = "duration_from_string",
+        serialize_with = "duration_to_string"
+    )]
+    #[validate(custom(function = "validate_positive_duration"))]
+    pub window: Duration,
+}
+
+// Settings of the Kubernetes client. Unset values fall back to the client's defaults.
+#[derive(Serialize, Deserialize, Validate, Debug, Default)]
+pub struct Client {
+    // Timeout for establishing a connection to the API server.
+    #[serde(
+        default,
+        deserialize_with = "optional_duration_from_string",
+        serialize_with = "optional_duration_to_string"
+    )]
+    #[validate(custom(function = "validate_positive_duration"))]
+    pub connect_timeout: Option<Duration>,
+    // Timeout for reading a response from the API server.
+    #[serde(
+        default,
+        deserialize_with = "optional_duration_from_string",
+        serialize_with = "optional_duration_to_string"
+    )]
+    #[validate(custom(function = "validate_positive_duration"))]
+    pub read_timeout: Option<Duration>,
+    // URL of an HTTP proxy to reach the API server through. Falls back to the HTTPS_PROXY
+    // environment variable if not set.
+    #[validate(url)]
+    pub proxy_url: Option<String>,
+    // Path of the service account token. Defaults to the in-cluster token path.
+    pub token_file: Option<String>,
+    // Path of the PEM-encoded certificate authority of the API server. Defaults to the in-cluster
+    // certificate authority.
+    pub ca_cert_file: Option<String>,
+    // Limits the rate at which Tainter updates nodes.
+    #[validate(nested)]
+    pub rate_limit: Option<RateLimit>,
+    // How many times creating the client is retried at startup, e.g. while the API server is
+    // still starting during cluster bootstrap. Defaults to 5.
+    pub init_retries: Option<u32>,
+    // The delay before the first retry, which doubles with each further retry. Defaults to 1s.
+    #[serde(
+        default,
+        deserialize_with = "optional_duration_from_string",
+        serialize_with = "optional_duration_to_string"
+    )]
+    #[validate(custom(function = "validate_positive_duration"))]
+    pub init_retry_delay: Option<Duration>,
+}
+
+#[derive(Serialize, Deserialize, Validate, Debug)]
+pub struct RateLimit {
+    // The sustained number of node updates per second.
+    #[validate(range(exclusive_min = 0.0))]
+    pub qps: f64,
+    // The number of node updates that may be issued at once before qps applies.
+    #[validate(range(min = 1))]
+    pub burst: u32,
 }
 
-#[derive(Deserialize, Validate, Debug)]
+#[derive(Serialize, Deserialize, Validate, Debug)]
+#[validate(schema(function = "validate_profile_names"))]
 pub struct Settings {
+    #[validate(nested)]
     pub server: Server,
+    #[validate(nested)]
     pub log: Log,
+    #[serde(default)]
+    #[validate(nested)]
+    pub client: Client,
+    #[validate(nested)]
+    pub reconciler: Reconciler,
+    #[validate(nested)]
+    pub shadow_reconciler: Option<ShadowReconciler>,
+    // Groups of matchers that are reconciled independently of the reconciler and each other.
+    #[serde(default)]
+    #[validate(nested)]
+    pub profiles: Vec<Profile>,
+    #[validate(nested)]
+    pub webhook: Option<Webhook>,
+    #[validate(nested)]
+    pub decision_log: Option<DecisionLog>,
+}
+
+// An external webhook, e.g. of incident tooling, that each taint that Tainter adds or removes is
+// posted to.
+#[derive(Serialize, Deserialize, Validate, Debug)]
+pub struct Webhook {
+    #[validate(url)]
+    pub url: String,
+    // Timeout of each delivery attempt.
+    #[serde(
+        default = "default_webhook_timeout",
+        deserialize_with = "duration_from_string",
+        serialize_with = "duration_to_string"
+    )]
+    #[validate(custom(function = "validate_positive_duration"))]
+    pub timeout: Duration,
+    // How many times a failed delivery is retried before the event is dropped.
+    #[serde(default = "default_webhook_max_retries")]
+    pub max_retries: u32,
+    #[serde(
+        default = "default_webhook_retry_delay",
+        deserialize_with = "duration_from_string",
+        serialize_with = "duration_to_string"
+    )]
+    pub retry_delay: Duration,
+}
+
+// Where a decision record is written for each taint that Tainter adds or removes.
+#[derive(Serialize, Deserialize, Validate, Debug)]
+pub struct DecisionLog {
+    // The file that the records are appended to. Records are written to stdout if unset.
+    #[validate(length(min = 1))]
+    pub path: Option<String>,
+}
+
+fn default_webhook_timeout() -> Duration {
+    Duration::from_secs(5)
+}
+
+fn default_webhook_max_retries() -> u32 {
+    3
+}
+
+fn default_webhook_retry_delay() -> Duration {
+    Duration::from_secs(1)
+}
+
+// A reconciler with its own matchers and settings that only reconciles the nodes that its node
+// selector selects.
+#[derive(Serialize, Deserialize, Validate, Debug)]
+pub struct Profile {
+    // Identifies the profile in logs and endpoints. Must be unique. The name suffixes the
+    // profile's managed taints annotation, so it must be a valid part of an annotation name.
+    #[validate(length(min = 1, max = 48), custom(function = "validate_profile_name"))]
+    pub name: String,
+    // Disabled profiles are kept in configuration but never started.
+    #[serde(default = "default_profile_enabled")]
+    pub enabled: bool,
+    // Equality-based label selector of the profile's nodes, e.g. "agentpool=gpu". All nodes if
+    // not set.
+    #[validate(custom(function = "validate_label_selector"))]
+    pub node_selector: Option<String>,
     #[validate(nested)]
     pub reconciler: Reconciler,
 }
 
-#[derive(Deserialize, Validate, Debug)]
+fn default_profile_enabled() -> bool {
+    true
+}
+
+// Candidate matchers that are evaluated alongside the reconciler's matchers but never applied.
+#[derive(Serialize, Deserialize, Validate, Debug)]
+pub struct ShadowReconciler {
+    #[validate(nested)]
+    pub matchers: Vec<Matcher>,
+}
+
+#[derive(Serialize, Deserialize, Validate, Debug)]
 pub struct Reconciler {
     #[validate(nested)]
     pub matchers: Vec<Matcher>,
+    // Unlimited if not set.
+    #[validate(range(min = 1))]
+    pub max_nodes_per_sweep: Option<usize>,
+    // Maximum duration of a sweep over all nodes. Unlimited if not set.
+    #[serde(
+        default,
+        deserialize_with = "optional_duration_from_string",
+        serialize_with = "optional_duration_to_string"
+    )]
+    #[validate(custom(function = "validate_positive_duration"))]
+    pub sweep_deadline: Option<Duration>,
+    // Only taint nodes that at least this many matchers match.
+    #[validate(range(min = 1))]
+    pub min_matching_matchers: Option<usize>,
+    // How many times a node is re-fetched and re-evaluated after taints were added to it, so that
+    // matchers that only become eligible through the changes of other matchers apply right away.
+    #[validate(range(min = 1))]
+    pub cascade_max_depth: Option<usize>,
+    // Suspend matchers that more than this percentage of nodes are eligible for during a sweep.
+    #[validate(range(min = 1, max = 99))]
+    pub systemic_guard_percentage: Option<u8>,
+    // Maximum random delay before the first list of nodes, which staggers replicas that start at
+    // the same time.
+    #[serde(
+        default,
+        deserialize_with = "optional_duration_from_string",
+        serialize_with = "optional_duration_to_string"
+    )]
+    #[validate(custom(function = "validate_positive_duration"))]
+    pub startup_jitter: Option<Duration>,
+    // Delay before the nodes that are relisted after the watch fell too far behind are processed.
+    #[serde(
+        default,
+        deserialize_with = "optional_duration_from_string",
+        serialize_with = "optional_duration_to_string"
+    )]
+    #[validate(custom(function = "validate_positive_duration"))]
+    pub relist_settle: Option<Duration>,
+    // The minimum delay after an error of the node watch before Tainter continues watching.
+    #[serde(
+        default = "default_error_retry_delay",
+        deserialize_with = "duration_from_string",
+        serialize_with = "duration_to_string"
+    )]
+    pub error_retry_delay: Duration,
+    // Skip matchers whose conditions transitioned more than this duration before the node's
+    // generation changed.
+    #[serde(
+        default,
+        deserialize_with = "optional_duration_from_string",
+        serialize_with = "optional_duration_to_string"
+    )]
+    pub stale_condition_tolerance: Option<Duration>,
+    // Skip nodes that are cordoned, i.e. have spec.unschedulable set to true.
+    #[serde(default)]
+    pub skip_cordoned_nodes: bool,
+    // Defer NoSchedule and PreferNoSchedule taints until the node's Ready condition is True.
+    #[serde(default)]
+    pub require_node_ready: bool,
+    // Skip nodes with a control plane role label. Enabled unless explicitly disabled.
+    #[serde(default = "default_protect_control_plane")]
+    pub protect_control_plane: bool,
+    // Skip NoExecute taints on the node that hosts Tainter, whose name is read from the NODE_NAME
+    // environment variable.
+    #[serde(default)]
+    pub protect_self: bool,
+    // Publish a TainterManaged condition on nodes that Tainter adds taints to.
+    #[serde(default)]
+    pub publish_condition: bool,
+    // Remove taints that Tainter added but that no matcher adds any more during sweeps.
+    #[serde(default)]
+    pub prune_orphaned_taints: bool,
+    // Interval on which orphaned taints are also removed from all nodes, regardless of sweeps.
+    #[serde(
+        default,
+        deserialize_with = "optional_duration_from_string",
+        serialize_with = "optional_duration_to_string"
+    )]
+    #[validate(custom(function = "validate_positive_duration"))]
+    pub prune_interval: Option<Duration>,
+    // Key of the label whose value groups nodes that are tainted all-or-nothing during sweeps.
+    #[validate(length(min = 1))]
+    pub group_by: Option<String>,
+    #[serde(default)]
+    pub effect_downgrade: EffectDowngrade,
+    #[serde(default)]
+    pub update_strategy: UpdateStrategy,
+    #[serde(default)]
+    pub apply_strategy: ApplyStrategy,
+    // Skip the remaining matchers once a matcher in taint mode applies to a node.
+    #[serde(default)]
+    pub short_circuit: bool,
+    // Key of an annotation that records the last taints that Tainter added to a node.
+    #[validate(length(min = 1))]
+    pub last_action_annotation: Option<String>,
+    // Defer all node updates until at least this many nodes are observed.
+    #[validate(range(min = 1))]
+    pub min_nodes_before_acting: Option<usize>,
+    // Minimum interval between updates of the same node.
+    #[serde(
+        default,
+        deserialize_with = "optional_duration_from_string",
+        serialize_with = "optional_duration_to_string"
+    )]
+    #[validate(custom(function = "validate_positive_duration"))]
+    pub node_write_cooldown: Option<Duration>,
+    #[serde(default)]
+    #[validate(nested)]
+    pub disruption_budgets: Vec<DisruptionBudget>,
+    #[validate(nested)]
+    pub rollout: Option<Rollout>,
 }
 
-#[derive(Deserialize, Validate, Debug)]
+// Gradually rolls out taints to a growing percentage of the eligible nodes.
+#[derive(Serialize, Deserialize, Validate, Debug)]
+pub struct Rollout {
+    // The percentage of nodes that are selected when Tainter starts.
+    #[validate(range(max = 100))]
+    pub initial_percentage: u8,
+    // The duration over which the percentage grows linearly to 100.
+    #[serde(
+        deserialize_with = "duration_from_string",
+        serialize_with = "duration_to_string"
+    )]
+    #[validate(custom(function = "validate_positive_duration"))]
+    pub ramp_duration: Duration,
+}
+
+// Limits the number of nodes in a group that have a NoExecute taint added by Tainter at once.
+#[derive(Serialize, Deserialize, Validate, Debug)]
+#[validate(schema(function = "validate_max_tainted"))]
+pub struct DisruptionBudget {
+    // Equality-based label selector of the nodes in the group, e.g. "agentpool=zeus,env=prod".
+    #[validate(custom(function = "validate_label_selector"))]
+    pub selector: String,
+    pub max_tainted: Option<usize>,
+    #[validate(range(max = 100))]
+    pub max_tainted_percentage: Option<u8>,
+}
+
+impl DisruptionBudget {
+    // Returns the labels of the selector. The selector must have been validated.
+    pub fn labels(&self) -> BTreeMap<String, String> {
+        self.selector
+            .split(',')
+            .filter_map(|requirement| requirement.split_once('='))
+            .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+            .collect()
+    }
+}
+
+fn default_matcher_enabled() -> bool {
+    true
+}
+
+fn default_protect_control_plane() -> bool {
+    true
+}
+
+fn default_error_retry_delay() -> Duration {
+    Duration::from_millis(100)
+}
+
+#[derive(Serialize, Deserialize, Validate, Debug)]
+#[validate(schema(function = "validate_taints"))]
+#[validate(schema(function = "validate_effect_mapping"))]
+#[validate(schema(function = "validate_templates"))]
+#[validate(schema(function = "validate_weight"))]
+#[validate(schema(function = "validate_missing_label_fallback"))]
+#[validate(schema(function = "validate_capacity_requires"))]
+#[validate(schema(function = "validate_conditions"))]
+#[validate(schema(function = "validate_node_age"))]
 pub struct Matcher {
+    // Human-friendly name that identifies the matcher in metrics and logs. Defaults to the
+    // matcher's taint keys.
+    #[validate(length(min = 1))]
+    pub name: Option<String>,
+    // Disabled matchers are kept in configuration but never taint nodes.
+    #[serde(default = "default_matcher_enabled")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub mode: MatcherMode,
+    // Whether the matcher also removes its taints from nodes whose conditions no longer match.
+    #[serde(default)]
+    pub lifecycle: Lifecycle,
+    // A matcher must have at least one taint, configured with either taint, taints or both.
     #[validate(nested)]
-    pub taint: Taint,
+    pub taint: Option<Taint>,
+    #[serde(default)]
+    #[validate(nested)]
+    pub taints: Vec<Taint>,
+    #[serde(default)]
     #[validate(nested)]
     pub conditions: Vec<Condition>,
+    // Shorthand for a condition that matches nodes with any of the kubelet pressure signals, i.e.
+    // whose condition of one of the signals' types is True. Expanded into conditions when
+    // settings are loaded.
+    #[serde(default)]
+    pub pressure_signals: Vec<PressureSignal>,
+    // Taints that the node must already have, e.g. taints of other controllers. Taints that
+    // Tainter's matchers add never match.
+    #[serde(default)]
+    #[validate(nested)]
+    pub taint_conditions: Vec<TaintCondition>,
+    #[serde(default)]
+    #[validate(nested)]
+    pub effect_mapping: Vec<EffectMapping>,
+    // Soft-preference weight published in the tainter.io/prefer-weight label when the matcher adds
+    // a PreferNoSchedule taint.
+    #[validate(range(min = 1, max = 100))]
+    pub weight: Option<u8>,
+    // What to do with a taint whose key or value has a ${label:<key>} placeholder for a label that
+    // the node does not have.
+    #[serde(default)]
+    pub missing_label_policy: MissingLabelPolicy,
+    // Replaces placeholders of missing labels if missing_label_policy is Fallback.
+    #[validate(length(min = 1))]
+    pub missing_label_fallback: Option<String>,
+    // Minimum quantities of resources, e.g. nvidia.com/gpu = "1", that the node's capacity must
+    // have for the matcher to apply. A quantity of "0" only requires the resource to be present.
+    #[serde(default)]
+    pub capacity_requires: BTreeMap<String, String>,
+    // Evict the node's pods that don't tolerate the matcher's NoExecute taints through the
+    // Eviction API, which respects pod disruption budgets, before adding the taints.
+    #[serde(default)]
+    pub graceful_evict: bool,
+    // Only add the matcher's taints during the schedule's windows, e.g. approved maintenance
+    // windows. Taints of nodes that match outside the windows are deferred until the next window.
+    #[validate(nested)]
+    pub schedule: Option<Schedule>,
+    // Only apply the matcher if at least count node conditions report the same reason that
+    // matches the reason regular expression, e.g. for correlated failures.
+    #[validate(nested)]
+    pub reason_quorum: Option<ReasonQuorum>,
+    // Matchers are evaluated in order of descending priority. Defaults to 0.
+    #[serde(default)]
+    pub priority: i32,
+    // Only apply the matcher to nodes that are missing at least one of these labels.
+    #[serde(default)]
+    #[validate(custom(function = "validate_required_labels"))]
+    pub required_labels: Vec<String>,
+    // Only apply the matcher to nodes that were created at least this long ago.
+    #[serde(
+        default,
+        deserialize_with = "optional_duration_from_string",
+        serialize_with = "optional_duration_to_string"
+    )]
+    pub node_age_min: Option<Duration>,
+    // Only apply the matcher to nodes that were created at most this long ago.
+    #[serde(
+        default,
+        deserialize_with = "optional_duration_from_string",
+        serialize_with = "optional_duration_to_string"
+    )]
+    pub node_age_max: Option<Duration>,
+    // Field manager that the matcher's taints are written under instead of Tainter's.
+    #[validate(length(min = 1, max = 128))]
+    pub field_manager: Option<String>,
+    // Only add the matcher's taints when one of its matching conditions changed its status.
+    #[serde(default)]
+    pub on_transition_only: bool,
+}
+
+// Recurring windows that start at the times of the cron expression and last for the duration.
+#[derive(Serialize, Deserialize, Validate, Debug)]
+pub struct Schedule {
+    // Cron expression with seconds, e.g. "0 0 2 * * Sat,Sun", that is evaluated in UTC.
+    #[validate(custom(function = "validate_cron"))]
+    pub cron: String,
+    #[serde(
+        deserialize_with = "duration_from_string",
+        serialize_with = "duration_to_string"
+    )]
+    #[validate(custom(function = "validate_positive_duration"))]
+    pub duration: Duration,
+}
+
+#[derive(Serialize, Deserialize, Validate, Debug)]
+pub struct ReasonQuorum {
+    #[validate(custom(function = "validate_regex"))]
+    pub reason: String,
+    #[validate(range(min = 1))]
+    pub count: usize,
+}
+
+impl Matcher {
+    pub fn all_taints(&self) -> impl Iterator<Item = &Taint> {
+        self.taint.iter().chain(self.taints.iter())
+    }
+
+    fn expand_pressure_signals(&mut self) {
+        if self.pressure_signals.is_empty() {
+            return;
+        }
+
+        let types: Vec<String> = self
+            .pressure_signals
+            .iter()
+            .map(|signal| signal.to_string())
+            .collect();
+        self.conditions.push(Condition {
+            type_: types.join("|"),
+            types: vec![],
+            status: Some("True".to_string()),
+            status_not: None,
+            status_in: None,
+            polarity: None,
+            reason: None,
+            message: None,
+            max_age: None,
+            max_heartbeat_age: None,
+            match_stale_heartbeat: false,
+            full_match: true,
+            exact_literals: false,
+            normalize: Normalize::Off,
+        });
+    }
+}
+
+// Node conditions that the kubelet sets to True when the node runs low on a resource.
+#[derive(Debug, PartialEq, Serialize, Deserialize, Display)]
+pub enum PressureSignal {
+    MemoryPressure,
+    DiskPressure,
+    PIDPressure,
+}
+
+// Maps a severity captured by the "severity" capture group of a condition's status regular
+// expression to the effect of the matcher's taint.
+#[derive(Serialize, Deserialize, Validate, Debug)]
+pub struct EffectMapping {
+    #[validate(length(min = 1))]
+    pub severity: String,
+    pub effect: TaintEffect,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, Default)]
+pub enum MissingLabelPolicy {
+    // Don't add the taint.
+    #[default]
+    Skip,
+    // Replace the placeholder with missing_label_fallback.
+    Fallback,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, Default)]
+pub enum Lifecycle {
+    // Only add the matcher's taints to eligible nodes.
+    #[default]
+    AddOnly,
+    // Also remove the matcher's taints from nodes that are no longer eligible.
+    Managed,
 }
 
-#[derive(Debug, PartialEq, Deserialize, EnumString, Display)]
+#[derive(Debug, PartialEq, Serialize, Deserialize, Default)]
+pub enum MatcherMode {
+    // Add the matcher's taints to eligible nodes.
+    #[default]
+    Taint,
+    // Annotate eligible nodes with the taints that would have been added instead of adding them.
+    Report,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, Default)]
+pub enum UpdateStrategy {
+    // Replace the whole node.
+    #[default]
+    Replace,
+    // Patch only the node's taints, labels and annotations, conditional on its resource version.
+    JsonPatch,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, Default)]
+pub enum ApplyStrategy {
+    // Add the taints of all matchers in a single update of the node.
+    #[default]
+    Batch,
+    // Add the taints of each matcher in a separate update of the node.
+    PerMatcher,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, Default)]
+pub enum EffectDowngrade {
+    // Never replace a taint that Tainter manages with a less severe effect.
+    #[default]
+    Forbid,
+    // Replace a taint that Tainter manages with a less severe effect.
+    Allow,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub enum Polarity {
+    // The condition is bad when it is true, e.g. MemoryPressure.
+    Positive,
+    // The condition is bad when it is not true, e.g. Ready.
+    Negative,
+}
+
+impl Polarity {
+    // Returns the statuses of a condition with this polarity that indicate a problem.
+    pub fn statuses(&self) -> Vec<String> {
+        match self {
+            Polarity::Positive => vec!["True".to_string()],
+            Polarity::Negative => vec!["False".to_string(), "Unknown".to_string()],
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, EnumString, Display)]
 pub enum TaintEffect {
     NoSchedule,
     PreferNoSchedule,
     NoExecute,
 }
 
-#[derive(Deserialize, Validate, Debug)]
+#[derive(Serialize, Deserialize, Validate, Debug)]
 pub struct Taint {
     pub effect: TaintEffect,
     #[validate(length(min = 1))]
@@ -70,13 +715,89 @@ pub struct Taint {
     pub value: String, // TODO it should probably be possible to configure a taint with no value.
 }
 
-#[derive(Deserialize, Validate, Debug)]
+#[derive(Serialize, Deserialize, Validate, Debug)]
+#[validate(schema(function = "validate_condition_status"))]
+#[validate(schema(function = "validate_condition_heartbeat"))]
+#[validate(schema(function = "validate_condition_type"))]
 pub struct Condition {
-    #[serde(rename = "type")]
+    #[serde(rename = "type", default)]
     #[validate(custom(function = "validate_regex"))]
     pub type_: String,
+    // Shorthand for a type that matches any of these regular expressions, which share the status
+    // and other fields of the condition. Mutually exclusive with type.
+    #[serde(default)]
+    pub types: Vec<String>,
+    #[validate(custom(function = "validate_regex"))]
+    pub status: Option<String>,
+    // Regular expression that the status must not match. Mutually exclusive with status.
     #[validate(custom(function = "validate_regex"))]
-    pub status: String,
+    pub status_not: Option<String>,
+    // Exact set of statuses to match. Mutually exclusive with status.
+    #[validate(length(min = 1))]
+    pub status_in: Option<Vec<String>>,
+    // Shorthand for the statuses of a well-known condition type. Mutually exclusive with status,
+    // status_not and status_in.
+    pub polarity: Option<Polarity>,
+    // If set, the reason of the node condition must also match.
+    #[validate(custom(function = "validate_regex"))]
+    pub reason: Option<String>,
+    // If set, the message of the node condition must also match.
+    #[validate(custom(function = "validate_regex"))]
+    pub message: Option<String>,
+    // If set, the node condition must have transitioned within this duration.
+    #[serde(
+        default,
+        deserialize_with = "optional_duration_from_string",
+        serialize_with = "optional_duration_to_string"
+    )]
+    pub max_age: Option<Duration>,
+    // If set, the node condition's last heartbeat must be at most this old.
+    #[serde(
+        default,
+        deserialize_with = "optional_duration_from_string",
+        serialize_with = "optional_duration_to_string"
+    )]
+    pub max_heartbeat_age: Option<Duration>,
+    // If true, the node condition's last heartbeat must instead be older than max_heartbeat_age,
+    // e.g. to taint nodes whose kubelet went silent.
+    #[serde(default)]
+    pub match_stale_heartbeat: bool,
+    // If true, the type and status regular expressions must match the entire string rather than
+    // any substring of it.
+    #[serde(default)]
+    pub full_match: bool,
+    // If true, type and status patterns without regex metacharacters must equal the entire string
+    // rather than match any substring of it.
+    #[serde(default)]
+    pub exact_literals: bool,
+    // Normalizes the type and status of the node condition before they are matched, e.g. to
+    // tolerate a status of "True " or "TRUE".
+    #[serde(default)]
+    pub normalize: Normalize,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, Default)]
+pub enum Normalize {
+    // Match the type and status as they are.
+    #[default]
+    Off,
+    // Trim surrounding whitespace.
+    Trim,
+    // Trim surrounding whitespace and lowercase. Patterns must be lowercase to match.
+    TrimLowercase,
+}
+
+#[derive(Serialize, Deserialize, Validate, Debug)]
+pub struct TaintCondition {
+    // Regular expression that the key of the taint must match.
+    #[validate(custom(function = "validate_regex"))]
+    pub key: String,
+    // If set, the effect of the taint must also match.
+    #[validate(custom(function = "validate_regex"))]
+    pub effect: Option<String>,
+    // If set, the value of the taint must also match.
+    #[validate(custom(function = "validate_regex"))]
+    pub value: Option<String>,
 }
 
 fn validate_regex(value: &str) -> Result<(), ValidationError> {
@@ -94,6 +815,361 @@ fn validate_regex(value: &str) -> Result<(), ValidationError> {
     Ok(())
 }
 
+fn validate_cron(value: &str) -> Result<(), ValidationError> {
+    if let Err(err) = cron::Schedule::from_str(value) {
+        let msg = format!("invalid cron expression {}: {}", value, err);
+        return Err(ValidationError {
+            code: Default::default(),
+            message: Some(Cow::from(msg)),
+            params: Default::default(),
+        });
+    }
+
+    Ok(())
+}
+
+fn validate_taints(matcher: &Matcher) -> Result<(), ValidationError> {
+    if matcher.all_taints().next().is_none() {
+        return Err(ValidationError {
+            code: Default::default(),
+            message: Some(Cow::from("matcher must have at least one taint")),
+            params: Default::default(),
+        });
+    }
+
+    Ok(())
+}
+
+fn validate_weight(matcher: &Matcher) -> Result<(), ValidationError> {
+    if matcher.weight.is_none() {
+        return Ok(());
+    }
+
+    let prefers_no_schedule = matcher
+        .all_taints()
+        .map(|taint| &taint.effect)
+        .chain(matcher.effect_mapping.iter().map(|mapping| &mapping.effect))
+        .any(|effect| *effect == TaintEffect::PreferNoSchedule);
+    if !prefers_no_schedule {
+        return Err(ValidationError {
+            code: Default::default(),
+            message: Some(Cow::from(
+                "weight requires a taint or effect mapping with effect PreferNoSchedule",
+            )),
+            params: Default::default(),
+        });
+    }
+
+    Ok(())
+}
+
+fn validate_missing_label_fallback(matcher: &Matcher) -> Result<(), ValidationError> {
+    let is_fallback = matcher.missing_label_policy == MissingLabelPolicy::Fallback;
+    if is_fallback != matcher.missing_label_fallback.is_some() {
+        return Err(ValidationError {
+            code: Default::default(),
+            message: Some(Cow::from(
+                "missing_label_fallback must be set if and only if missing_label_policy is Fallback",
+            )),
+            params: Default::default(),
+        });
+    }
+
+    Ok(())
+}
+
+fn validate_capacity_requires(matcher: &Matcher) -> Result<(), ValidationError> {
+    let invalid = matcher
+        .capacity_requires
+        .iter()
+        .find(|(_, quantity)| crate::quantity::parse(quantity).is_none());
+
+    if let Some((resource, quantity)) = invalid {
+        let msg =
+            format!("invalid quantity {quantity} for resource {resource} in capacity_requires");
+        return Err(ValidationError {
+            code: Default::default(),
+            message: Some(Cow::from(msg)),
+            params: Default::default(),
+        });
+    }
+
+    Ok(())
+}
+
+fn validate_required_labels(labels: &[String]) -> Result<(), ValidationError> {
+    if labels.iter().any(|label| label.is_empty()) {
+        return Err(ValidationError {
+            code: Default::default(),
+            message: Some(Cow::from("required label must not be empty")),
+            params: Default::default(),
+        });
+    }
+
+    Ok(())
+}
+
+fn validate_node_age(matcher: &Matcher) -> Result<(), ValidationError> {
+    if let (Some(min), Some(max)) = (matcher.node_age_min, matcher.node_age_max) {
+        if min > max {
+            return Err(ValidationError {
+                code: Default::default(),
+                message: Some(Cow::from(
+                    "node_age_min must not be greater than node_age_max",
+                )),
+                params: Default::default(),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_conditions(matcher: &Matcher) -> Result<(), ValidationError> {
+    if matcher.conditions.is_empty()
+        && matcher.pressure_signals.is_empty()
+        && matcher.taint_conditions.is_empty()
+        && matcher.reason_quorum.is_none()
+        && matcher.required_labels.is_empty()
+    {
+        return Err(ValidationError {
+            code: Default::default(),
+            message: Some(Cow::from(
+                "matcher must have at least one condition, pressure signal, taint condition, reason quorum or required label",
+            )),
+            params: Default::default(),
+        });
+    }
+
+    Ok(())
+}
+
+fn validate_condition_status(condition: &Condition) -> Result<(), ValidationError> {
+    let statuses = [
+        condition.status.is_some(),
+        condition.status_not.is_some(),
+        condition.status_in.is_some(),
+        condition.polarity.is_some(),
+    ];
+    if statuses.iter().filter(|is_set| **is_set).count() != 1 {
+        return Err(ValidationError {
+            code: Default::default(),
+            message: Some(Cow::from(
+                "condition must have exactly one of status, status_not, status_in and polarity",
+            )),
+            params: Default::default(),
+        });
+    }
+
+    Ok(())
+}
+
+fn validate_condition_type(condition: &Condition) -> Result<(), ValidationError> {
+    if condition.type_.is_empty() == condition.types.is_empty() {
+        return Err(ValidationError {
+            code: Default::default(),
+            message: Some(Cow::from(
+                "condition must have exactly one of type and types",
+            )),
+            params: Default::default(),
+        });
+    }
+
+    condition
+        .types
+        .iter()
+        .try_for_each(|type_| validate_regex(type_))
+}
+
+fn validate_condition_heartbeat(condition: &Condition) -> Result<(), ValidationError> {
+    if condition.match_stale_heartbeat && condition.max_heartbeat_age.is_none() {
+        return Err(ValidationError {
+            code: Default::default(),
+            message: Some(Cow::from(
+                "match_stale_heartbeat requires max_heartbeat_age",
+            )),
+            params: Default::default(),
+        });
+    }
+
+    if condition.max_heartbeat_age == Some(Duration::ZERO) {
+        return Err(ValidationError {
+            code: Default::default(),
+            message: Some(Cow::from("max_heartbeat_age must be positive")),
+            params: Default::default(),
+        });
+    }
+
+    Ok(())
+}
+
+fn validate_profile_names(settings: &Settings) -> Result<(), ValidationError> {
+    let mut names = std::collections::BTreeSet::new();
+    if let Some(profile) = settings
+        .profiles
+        .iter()
+        .find(|profile| !names.insert(profile.name.as_str()))
+    {
+        let msg = format!("profile name {} is not unique", profile.name);
+        return Err(ValidationError {
+            code: Default::default(),
+            message: Some(Cow::from(msg)),
+            params: Default::default(),
+        });
+    }
+
+    Ok(())
+}
+
+fn validate_profile_name(name: &str) -> Result<(), ValidationError> {
+    let alphanumeric = |c: Option<char>| c.is_some_and(|c| c.is_ascii_alphanumeric());
+    let valid = alphanumeric(name.chars().next())
+        && alphanumeric(name.chars().last())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+
+    if !valid {
+        return Err(ValidationError {
+            code: Default::default(),
+            message: Some(Cow::from(
+                "profile name must consist of alphanumeric characters, '-', '_' or '.', and must start and end with an alphanumeric character",
+            )),
+            params: Default::default(),
+        });
+    }
+
+    Ok(())
+}
+
+fn validate_label_selector(value: &str) -> Result<(), ValidationError> {
+    let valid = value.split(',').all(|requirement| {
+        requirement
+            .split_once('=')
+            .is_some_and(|(key, _)| !key.trim().is_empty())
+    });
+
+    if !valid {
+        return Err(ValidationError {
+            code: Default::default(),
+            message: Some(Cow::from(
+                "selector must be a comma-separated list of key=value labels",
+            )),
+            params: Default::default(),
+        });
+    }
+
+    Ok(())
+}
+
+fn validate_max_tainted(budget: &DisruptionBudget) -> Result<(), ValidationError> {
+    if budget.max_tainted.is_some() == budget.max_tainted_percentage.is_some() {
+        return Err(ValidationError {
+            code: Default::default(),
+            message: Some(Cow::from(
+                "disruption budget must have exactly one of max_tainted and max_tainted_percentage",
+            )),
+            params: Default::default(),
+        });
+    }
+
+    Ok(())
+}
+
+// Taint keys and values can contain ${name} placeholders, which are replaced by the value of the
+// capture group with that name in one of the matcher's condition regular expressions.
+fn validate_templates(matcher: &Matcher) -> Result<(), ValidationError> {
+    let placeholder = Regex::new(r"\$\{(\w+)\}").expect("placeholder regex should be valid");
+
+    let capture_names: Vec<String> = matcher
+        .conditions
+        .iter()
+        .flat_map(|condition| {
+            [
+                Some(&condition.type_),
+                condition.status.as_ref(),
+                condition.reason.as_ref(),
+                condition.message.as_ref(),
+            ]
+        })
+        .flatten()
+        .filter_map(|pattern| Regex::new(pattern).ok())
+        .flat_map(|regex| {
+            regex
+                .capture_names()
+                .flatten()
+                .map(String::from)
+                .collect::<Vec<String>>()
+        })
+        .collect();
+
+    for taint in matcher.all_taints() {
+        for template in [&taint.key, &taint.value] {
+            for captures in placeholder.captures_iter(template) {
+                let name = &captures[1];
+                // The time at which the taint is added.
+                if name == "timestamp" {
+                    continue;
+                }
+                if !capture_names
+                    .iter()
+                    .any(|capture_name| capture_name == name)
+                {
+                    return Err(ValidationError {
+                        code: Default::default(),
+                        message: Some(Cow::from(format!(
+                            "taint placeholder {} does not match a named capture group of a condition",
+                            name
+                        ))),
+                        params: Default::default(),
+                    });
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_effect_mapping(matcher: &Matcher) -> Result<(), ValidationError> {
+    if matcher.effect_mapping.is_empty() {
+        return Ok(());
+    }
+
+    let has_severity_group = matcher.conditions.iter().any(|condition| {
+        let Some(status) = condition.status.as_deref() else {
+            return false;
+        };
+        Regex::new(status)
+            .map(|regex| regex.capture_names().any(|name| name == Some("severity")))
+            .unwrap_or(false)
+    });
+
+    if !has_severity_group {
+        return Err(ValidationError {
+            code: Default::default(),
+            message: Some(Cow::from(
+                "effect_mapping requires a condition status with a capture group named severity",
+            )),
+            params: Default::default(),
+        });
+    }
+
+    Ok(())
+}
+
+fn validate_positive_duration(value: &Duration) -> Result<(), ValidationError> {
+    if value.is_zero() {
+        return Err(ValidationError {
+            code: Default::default(),
+            message: Some(Cow::from("duration must be positive")),
+            params: Default::default(),
+        });
+    }
+
+    Ok(())
+}
+
 #[derive(Error, Debug)]
 pub enum NewSettingsError {
     #[error("error reading settings file {0}")]
@@ -102,26 +1178,67 @@ pub enum NewSettingsError {
     Validate(#[from] ValidationErrors),
 }
 
+#[derive(Error, Debug)]
+#[error("error serializing settings {0}")]
+pub struct PrintSettingsError(#[from] serde_json::Error);
+
 impl Settings {
+    // Reads settings from the file at path. Environment variables with the TAINTER prefix override
+    // the file's settings, e.g. TAINTER__SERVER__PORT=9090 overrides server.port.
     pub fn new(path: &str) -> Result<Self, NewSettingsError> {
+        Self::with_environment(path, Environment::with_prefix("TAINTER").separator("__"))
+    }
+
+    fn with_environment(path: &str, environment: Environment) -> Result<Self, NewSettingsError> {
         let config = Config::builder()
             .add_source(config::File::with_name(path))
+            .add_source(environment)
             .build()?;
 
-        let settings = config.try_deserialize::<Settings>()?;
+        let mut settings = config.try_deserialize::<Settings>()?;
+        let profile_matchers = settings
+            .profiles
+            .iter_mut()
+            .flat_map(|profile| profile.reconciler.matchers.iter_mut());
+        for matcher in settings
+            .reconciler
+            .matchers
+            .iter_mut()
+            .chain(profile_matchers)
+        {
+            matcher.expand_pressure_signals();
+        }
 
         settings.validate()?;
 
         Ok(settings)
     }
+
+    // The effective settings after defaults and environment overrides were applied, as JSON.
+    pub fn to_json(&self) -> Result<String, PrintSettingsError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::BTreeMap;
+    use std::time::Duration;
     use test_case::test_case;
 
+    use crate::settings::EffectDowngrade;
+    use crate::settings::Lifecycle;
+    use crate::settings::MatcherMode;
+    use crate::settings::MissingLabelPolicy;
+    use crate::settings::Normalize;
+    use crate::settings::Polarity;
+    use crate::settings::PressureSignal;
     use crate::settings::Settings;
+    use crate::settings::Taint;
     use crate::settings::TaintEffect;
+    use crate::settings::{ApplyStrategy, UpdateStrategy};
+    use config::Environment;
+    use k8s_openapi::serde_json;
 
     // https://github.com/frondeus/test-case/wiki.
     #[test_case("invalid path", "error reading settings file configuration file \"invalid path\" not found" ; "returns error on invalid path")]
@@ -131,8 +1248,61 @@ mod tests {
     #[test_case("src/settings/testfiles/invalid_taint_effect.toml", "error reading settings file enum TaintEffect does not have variant constructor Nope" ; "returns error on invalid taint effect")]
     #[test_case("src/settings/testfiles/empty_taint_value.toml", "error validating settings reconciler.matchers[0].taint.value: Validation error: length" ; "returns error on empty taint value")]
     #[test_case("src/settings/testfiles/empty_taint_key.toml", "error validating settings reconciler.matchers[0].taint.key: Validation error: length" ; "returns error on empty taint key")]
+    #[test_case("src/settings/testfiles/zero_max_nodes_per_sweep.toml", "error validating settings reconciler.max_nodes_per_sweep: Validation error: range" ; "returns error on zero max_nodes_per_sweep")]
+    #[test_case("src/settings/testfiles/zero_min_matching_matchers.toml", "error validating settings reconciler.min_matching_matchers: Validation error: range" ; "returns error on zero min_matching_matchers")]
+    #[test_case("src/settings/testfiles/duplicate_profile_names.toml", "profile name hardware is not unique" ; "returns error on duplicate profile names")]
+    #[test_case("src/settings/testfiles/invalid_profile_name.toml", "error validating settings profiles[0].name: profile name must consist of alphanumeric characters, '-', '_' or '.', and must start and end with an alphanumeric character" ; "returns error on invalid profile name")]
+    #[test_case("src/settings/testfiles/no_conditions.toml", "matcher must have at least one condition, pressure signal, taint condition, reason quorum or required label" ; "returns error on matcher without conditions")]
+    #[test_case("src/settings/testfiles/empty_required_label.toml", "error validating settings reconciler.matchers[0].required_labels: required label must not be empty" ; "returns error on empty required label")]
+    #[test_case("src/settings/testfiles/zero_reason_quorum_count.toml", "error validating settings reconciler.matchers[0].reason_quorum.count: Validation error: range" ; "returns error on zero reason quorum count")]
+    #[test_case("src/settings/testfiles/invalid_taint_condition_key_regex.toml", "error validating settings reconciler.matchers[0].taint_conditions[0].key: regex parse error:\n    node(\n        ^\nerror: unclosed group " ; "returns error on invalid taint condition key regex")]
+    #[test_case("src/settings/testfiles/systemic_guard_percentage_out_of_range.toml", "error validating settings reconciler.systemic_guard_percentage: Validation error: range" ; "returns error on systemic_guard_percentage out of range")]
+    #[test_case("src/settings/testfiles/zero_cascade_max_depth.toml", "error validating settings reconciler.cascade_max_depth: Validation error: range" ; "returns error on zero cascade_max_depth")]
+    #[test_case("src/settings/testfiles/weight_without_prefer_no_schedule.toml", "weight requires a taint or effect mapping with effect PreferNoSchedule" ; "returns error on weight without PreferNoSchedule")]
+    #[test_case("src/settings/testfiles/invalid_schedule_cron.toml", "error validating settings reconciler.matchers[0].schedule.cron: invalid cron expression 0 0 25 * * *" ; "returns error on invalid schedule cron")]
+    #[test_case("src/settings/testfiles/zero_schedule_duration.toml", "error validating settings reconciler.matchers[0].schedule.duration: duration must be positive" ; "returns error on zero schedule duration")]
+    #[test_case("src/settings/testfiles/invalid_capacity_requires.toml", "invalid quantity 1Gb for resource nvidia.com/gpu in capacity_requires" ; "returns error on invalid capacity_requires quantity")]
+    #[test_case("src/settings/testfiles/empty_group_by.toml", "error validating settings reconciler.group_by: Validation error: length" ; "returns error on empty group_by")]
+    #[test_case("src/settings/testfiles/empty_matcher_name.toml", "error validating settings reconciler.matchers[0].name: Validation error: length" ; "returns error on empty matcher name")]
+    #[test_case("src/settings/testfiles/weight_out_of_range.toml", "error validating settings reconciler.matchers[0].weight: Validation error: range" ; "returns error on weight out of range")]
+    #[test_case("src/settings/testfiles/missing_label_fallback_without_policy.toml", "missing_label_fallback must be set if and only if missing_label_policy is Fallback" ; "returns error on missing label fallback without policy")]
+    #[test_case("src/settings/testfiles/missing_label_policy_without_fallback.toml", "missing_label_fallback must be set if and only if missing_label_policy is Fallback" ; "returns error on missing label policy without fallback")]
+    #[test_case("src/settings/testfiles/effect_mapping_without_severity_group.toml", "effect_mapping requires a condition status with a capture group named severity" ; "returns error on effect mapping without severity capture group")]
+    #[test_case("src/settings/testfiles/invalid_condition_reason_regex.toml", "error validating settings reconciler.matchers[0].conditions[0].reason: regex parse error:\n    foo(bar\n       ^\nerror: unclosed group " ; "returns error on invalid condition reason regex")]
+    #[test_case("src/settings/testfiles/invalid_condition_message_regex.toml", "error validating settings reconciler.matchers[0].conditions[0].message: regex parse error:\n    foo(bar\n       ^\nerror: unclosed group " ; "returns error on invalid condition message regex")]
+    #[test_case("src/settings/testfiles/invalid_condition_max_age.toml", "error reading settings file expected number at 0" ; "returns error on invalid condition max_age")]
+    #[test_case("src/settings/testfiles/no_taints.toml", "matcher must have at least one taint" ; "returns error on matcher without taints")]
+    #[test_case("src/settings/testfiles/empty_taints_key.toml", "error validating settings reconciler.matchers[0].taints[1].key: Validation error: length" ; "returns error on empty key in taints")]
+    #[test_case("src/settings/testfiles/zero_client_connect_timeout.toml", "error validating settings client.connect_timeout: duration must be positive" ; "returns error on zero client connect_timeout")]
     #[test_case("src/settings/testfiles/invalid_condition_type_regex.toml", "error validating settings reconciler.matchers[0].conditions[0].type_: regex parse error:\n    foo(bar\n       ^\nerror: unclosed group reconciler.matchers[0].conditions[1].type_: regex parse error:\n    marco(polo\n         ^\nerror: unclosed group " ; "returns error on invalid condition type regex")]
     #[test_case("src/settings/testfiles/invalid_condition_status_regex.toml", "error validating settings reconciler.matchers[0].conditions[0].status: regex parse error:\n    foo(bar\n       ^\nerror: unclosed group reconciler.matchers[0].conditions[1].status: regex parse error:\n    marco(polo\n         ^\nerror: unclosed group " ; "returns error on invalid condition status regex")]
+    #[test_case("src/settings/testfiles/condition_status_and_status_in.toml", "condition must have exactly one of status, status_not, status_in and polarity" ; "returns error on condition with both status and status_in")]
+    #[test_case("src/settings/testfiles/condition_type_and_types.toml", "condition must have exactly one of type and types" ; "returns error on condition with both type and types")]
+    #[test_case("src/settings/testfiles/condition_without_type.toml", "condition must have exactly one of type and types" ; "returns error on condition without type")]
+    #[test_case("src/settings/testfiles/invalid_condition_types_regex.toml", "regex parse error" ; "returns error on invalid condition types regex")]
+    #[test_case("src/settings/testfiles/condition_status_and_status_not.toml", "condition must have exactly one of status, status_not, status_in and polarity" ; "returns error on condition with both status and status_not")]
+    #[test_case("src/settings/testfiles/invalid_condition_status_not_regex.toml", "error validating settings reconciler.matchers[0].conditions[0].status_not: regex parse error:\n    foo(bar\n       ^\nerror: unclosed group " ; "returns error on invalid condition status_not regex")]
+    #[test_case("src/settings/testfiles/condition_status_and_polarity.toml", "condition must have exactly one of status, status_not, status_in and polarity" ; "returns error on condition with both status and polarity")]
+    #[test_case("src/settings/testfiles/stale_heartbeat_without_max_heartbeat_age.toml", "match_stale_heartbeat requires max_heartbeat_age" ; "returns error on match_stale_heartbeat without max_heartbeat_age")]
+    #[test_case("src/settings/testfiles/zero_max_heartbeat_age.toml", "max_heartbeat_age must be positive" ; "returns error on zero max_heartbeat_age")]
+    #[test_case("src/settings/testfiles/condition_without_status.toml", "condition must have exactly one of status, status_not, status_in and polarity" ; "returns error on condition without status, status_not, status_in or polarity")]
+    #[test_case("src/settings/testfiles/empty_condition_status_in.toml", "error validating settings reconciler.matchers[0].conditions[0].status_in: Validation error: length" ; "returns error on empty condition status_in")]
+    #[test_case("src/settings/testfiles/invalid_webhook_url.toml", "error validating settings webhook.url: Validation error: url" ; "returns error on invalid webhook url")]
+    #[test_case("src/settings/testfiles/zero_webhook_timeout.toml", "error validating settings webhook.timeout: duration must be positive" ; "returns error on zero webhook timeout")]
+    #[test_case("src/settings/testfiles/invalid_client_proxy_url.toml", "error validating settings client.proxy_url: Validation error: url" ; "returns error on invalid client proxy_url")]
+    #[test_case("src/settings/testfiles/zero_log_aggregation_threshold.toml", "error validating settings log.aggregation.threshold: Validation error: range" ; "returns error on zero log aggregation threshold")]
+    #[test_case("src/settings/testfiles/zero_log_aggregation_window.toml", "error validating settings log.aggregation.window: duration must be positive" ; "returns error on zero log aggregation window")]
+    #[test_case("src/settings/testfiles/zero_client_rate_limit_qps.toml", "error validating settings client.rate_limit.qps: Validation error: range" ; "returns error on zero client rate_limit qps")]
+    #[test_case("src/settings/testfiles/zero_client_rate_limit_burst.toml", "error validating settings client.rate_limit.burst: Validation error: range" ; "returns error on zero client rate_limit burst")]
+    #[test_case("src/settings/testfiles/empty_shadow_taint_key.toml", "error validating settings shadow_reconciler.matchers[0].taint.key: Validation error: length" ; "returns error on empty shadow matcher taint key")]
+    #[test_case("src/settings/testfiles/disruption_budget_without_max.toml", "disruption budget must have exactly one of max_tainted and max_tainted_percentage" ; "returns error on disruption budget without max")]
+    #[test_case("src/settings/testfiles/invalid_disruption_budget_selector.toml", "error validating settings reconciler.disruption_budgets[0].selector: selector must be a comma-separated list of key=value labels" ; "returns error on invalid disruption budget selector")]
+    #[test_case("src/settings/testfiles/rollout_initial_percentage_out_of_range.toml", "error validating settings reconciler.rollout.initial_percentage: Validation error: range" ; "returns error on rollout initial_percentage out of range")]
+    #[test_case("src/settings/testfiles/zero_rollout_ramp_duration.toml", "error validating settings reconciler.rollout.ramp_duration: duration must be positive" ; "returns error on zero rollout ramp_duration")]
+    #[test_case("src/settings/testfiles/node_age_min_above_max.toml", "node_age_min must not be greater than node_age_max" ; "returns error on node_age_min above node_age_max")]
+    #[test_case("src/settings/testfiles/zero_startup_jitter.toml", "error validating settings reconciler.startup_jitter: duration must be positive" ; "returns error on zero startup_jitter")]
+    #[test_case("src/settings/testfiles/invalid_effect_downgrade.toml", "error reading settings file enum EffectDowngrade does not have variant constructor Sometimes" ; "returns error on invalid effect_downgrade")]
+    #[test_case("src/settings/testfiles/taint_template_without_capture.toml", "taint placeholder rack does not match a named capture group of a condition" ; "returns error on taint placeholder without capture group")]
     fn new_tests(path: &str, expected_error: &str) {
         let res = Settings::new(path);
         assert!(res.is_err());
@@ -146,18 +1316,632 @@ mod tests {
         let settings = res.unwrap();
         assert_eq!("0.0.0.0", settings.server.host);
         assert_eq!(8080, settings.server.port);
+        assert_eq!(1, settings.server.workers);
+        assert!(settings.server.enabled);
+        assert!(!settings.server.admin_endpoints);
         assert_eq!(tracing::Level::INFO, settings.log.max_level);
+        assert!(settings.log.with_current_span);
+        assert_eq!(None, settings.log.file);
+        assert!(settings.log.aggregation.is_none());
+        assert_eq!(None, settings.client.connect_timeout);
+        assert_eq!(None, settings.client.read_timeout);
+        assert_eq!(None, settings.client.proxy_url);
+        assert!(settings.client.rate_limit.is_none());
+        assert_eq!(None, settings.client.token_file);
+        assert_eq!(None, settings.client.ca_cert_file);
         assert_eq!(1, settings.reconciler.matchers.len());
-        let matcher = settings.reconciler.matchers.get(0).unwrap();
-        assert_eq!(TaintEffect::NoExecute, matcher.taint.effect);
-        assert_eq!("pressure", matcher.taint.key);
-        assert_eq!("memory", matcher.taint.value);
+        assert_eq!(None, settings.reconciler.max_nodes_per_sweep);
+        assert_eq!(None, settings.reconciler.sweep_deadline);
+        assert_eq!(None, settings.reconciler.min_matching_matchers);
+        assert_eq!(None, settings.reconciler.cascade_max_depth);
+        assert_eq!(None, settings.reconciler.systemic_guard_percentage);
+        assert!(settings.reconciler.protect_control_plane);
+        assert!(!settings.reconciler.protect_self);
+        assert_eq!(
+            Duration::from_millis(100),
+            settings.reconciler.error_retry_delay
+        );
+        assert_eq!(None, settings.reconciler.startup_jitter);
+        assert_eq!(None, settings.reconciler.relist_settle);
+        assert_eq!(None, settings.reconciler.stale_condition_tolerance);
+        assert!(!settings.reconciler.skip_cordoned_nodes);
+        assert!(!settings.reconciler.require_node_ready);
+        assert!(!settings.reconciler.publish_condition);
+        assert!(!settings.reconciler.prune_orphaned_taints);
+        assert_eq!(None, settings.reconciler.prune_interval);
+        assert_eq!(None, settings.reconciler.group_by);
+        assert_eq!(
+            EffectDowngrade::Forbid,
+            settings.reconciler.effect_downgrade
+        );
+        assert_eq!(UpdateStrategy::Replace, settings.reconciler.update_strategy);
+        assert_eq!(ApplyStrategy::Batch, settings.reconciler.apply_strategy);
+        assert!(!settings.reconciler.short_circuit);
+        assert!(settings.reconciler.last_action_annotation.is_none());
+        assert_eq!(None, settings.reconciler.min_nodes_before_acting);
+        assert_eq!(None, settings.reconciler.node_write_cooldown);
+        assert!(settings.shadow_reconciler.is_none());
+        assert!(settings.profiles.is_empty());
+        assert!(settings.decision_log.is_none());
+        assert!(settings.reconciler.disruption_budgets.is_empty());
+        assert!(settings.reconciler.rollout.is_none());
+        let matcher = settings.reconciler.matchers.first().unwrap();
+        assert!(matcher.enabled);
+        assert_eq!(MatcherMode::Taint, matcher.mode);
+        let taint = matcher.taint.as_ref().unwrap();
+        assert_eq!(TaintEffect::NoExecute, taint.effect);
+        assert_eq!("pressure", taint.key);
+        assert_eq!("memory", taint.value);
+        assert!(matcher.taints.is_empty());
         assert_eq!(2, matcher.conditions.len());
-        let condition = matcher.conditions.get(0).unwrap();
+        let condition = matcher.conditions.first().unwrap();
         assert_eq!("NetworkInterfaceCard", condition.type_);
-        assert_eq!("Kaput|Ruined", condition.status);
+        assert_eq!(Some("Kaput|Ruined".to_string()), condition.status);
+        assert_eq!(None, condition.status_in);
+        assert_eq!(None, condition.polarity);
+        assert!(!condition.full_match);
+        assert!(!condition.exact_literals);
+        assert_eq!(Normalize::Off, condition.normalize);
+        assert_eq!(Some("NicFailure".to_string()), condition.reason);
+        assert_eq!(None, condition.message);
+        assert_eq!(Some(Duration::from_secs(300)), condition.max_age);
+        assert_eq!(None, condition.max_heartbeat_age);
+        assert!(!condition.match_stale_heartbeat);
         let condition = matcher.conditions.get(1).unwrap();
         assert_eq!("PrivateLink", condition.type_);
-        assert_eq!("severed", condition.status);
+        assert_eq!(Some("severed".to_string()), condition.status);
+        assert_eq!(None, condition.reason);
+        assert_eq!(Some("(?i)link severed".to_string()), condition.message);
+        assert_eq!(None, condition.max_age);
+        assert!(matcher.effect_mapping.is_empty());
+    }
+
+    #[test]
+    fn new_returns_settings_with_multiple_taints() {
+        let res = Settings::new("src/settings/testfiles/multiple_taints.toml");
+        assert!(res.is_ok());
+        let settings = res.unwrap();
+        let matcher = settings.reconciler.matchers.first().unwrap();
+        assert!(matcher.taint.is_some());
+        assert_eq!(2, matcher.taints.len());
+        let taints: Vec<&Taint> = matcher.all_taints().collect();
+        assert_eq!(3, taints.len());
+        assert_eq!("pressure", taints.first().unwrap().key);
+        assert_eq!(TaintEffect::NoSchedule, taints.get(1).unwrap().effect);
+        assert_eq!("network", taints.get(1).unwrap().key);
+        assert_eq!(TaintEffect::NoExecute, taints.get(2).unwrap().effect);
+        assert_eq!("network", taints.get(2).unwrap().key);
+    }
+
+    #[test]
+    fn new_returns_settings_with_status_in() {
+        let res = Settings::new("src/settings/testfiles/status_in.toml");
+        assert!(res.is_ok());
+        let settings = res.unwrap();
+        let condition = settings
+            .reconciler
+            .matchers
+            .first()
+            .unwrap()
+            .conditions
+            .first()
+            .unwrap();
+        assert_eq!(None, condition.status);
+        assert!(condition.full_match);
+        assert_eq!(
+            Some(vec!["False".to_string(), "Unknown".to_string()]),
+            condition.status_in
+        );
+    }
+
+    #[test]
+    fn new_returns_settings_with_shadow_reconciler() {
+        let res = Settings::new("src/settings/testfiles/shadow_reconciler.toml");
+        assert!(res.is_ok());
+        let settings = res.unwrap();
+        assert_eq!(1, settings.reconciler.matchers.len());
+        let shadow_reconciler = settings.shadow_reconciler.unwrap();
+        assert_eq!(1, shadow_reconciler.matchers.len());
+        let matcher = shadow_reconciler.matchers.first().unwrap();
+        assert_eq!("not-ready", matcher.taint.as_ref().unwrap().key);
+        assert_eq!(
+            Some(vec!["False".to_string(), "Unknown".to_string()]),
+            matcher.conditions.first().unwrap().status_in
+        );
+    }
+
+    #[test]
+    fn new_returns_settings_with_disruption_budgets() {
+        let res = Settings::new("src/settings/testfiles/disruption_budgets.toml");
+        assert!(res.is_ok());
+        let settings = res.unwrap();
+        let budgets = settings.reconciler.disruption_budgets;
+        assert_eq!(2, budgets.len());
+        let budget = budgets.first().unwrap();
+        assert_eq!(
+            BTreeMap::from([
+                ("agentpool".to_string(), "zeus1".to_string()),
+                ("env".to_string(), "prod".to_string())
+            ]),
+            budget.labels()
+        );
+        assert_eq!(Some(1), budget.max_tainted);
+        assert_eq!(None, budget.max_tainted_percentage);
+        let budget = budgets.get(1).unwrap();
+        assert_eq!(Some(25), budget.max_tainted_percentage);
+    }
+
+    #[test]
+    fn new_returns_settings_with_condition_types() {
+        let res = Settings::new("src/settings/testfiles/condition_types.toml");
+        assert!(res.is_ok());
+        let settings = res.unwrap();
+        let condition = settings
+            .reconciler
+            .matchers
+            .first()
+            .unwrap()
+            .conditions
+            .first()
+            .unwrap();
+        assert_eq!("", condition.type_);
+        assert_eq!(vec!["MemoryPressure", "DiskPressure"], condition.types);
+    }
+
+    #[test]
+    fn new_returns_settings_with_status_not() {
+        let res = Settings::new("src/settings/testfiles/status_not.toml");
+        assert!(res.is_ok());
+        let settings = res.unwrap();
+        let condition = settings
+            .reconciler
+            .matchers
+            .first()
+            .unwrap()
+            .conditions
+            .first()
+            .unwrap();
+        assert_eq!(None, condition.status);
+        assert_eq!(Some("True".to_string()), condition.status_not);
+        assert!(condition.exact_literals);
+    }
+
+    #[test]
+    fn new_returns_settings_with_polarity() {
+        let res = Settings::new("src/settings/testfiles/polarity.toml");
+        assert!(res.is_ok());
+        let settings = res.unwrap();
+        let conditions = &settings.reconciler.matchers.first().unwrap().conditions;
+        assert_eq!(
+            Some(Polarity::Negative),
+            conditions.first().unwrap().polarity
+        );
+        assert_eq!(None, conditions.first().unwrap().status);
+        assert_eq!(
+            Some(Polarity::Positive),
+            conditions.get(1).unwrap().polarity
+        );
+    }
+
+    #[test]
+    fn new_returns_settings_with_taint_conditions() {
+        let res = Settings::new("src/settings/testfiles/taint_conditions.toml");
+        assert!(res.is_ok());
+        let settings = res.unwrap();
+        let matcher = settings.reconciler.matchers.first().unwrap();
+        assert!(matcher.conditions.is_empty());
+        let taint_condition = matcher.taint_conditions.first().unwrap();
+        assert_eq!("^node\\.kubernetes\\.io/unreachable$", taint_condition.key);
+        assert_eq!(Some("NoExecute".to_string()), taint_condition.effect);
+        assert_eq!(None, taint_condition.value);
+    }
+
+    #[test]
+    fn new_returns_settings_with_reason_quorum() {
+        let res = Settings::new("src/settings/testfiles/reason_quorum.toml");
+        assert!(res.is_ok());
+        let settings = res.unwrap();
+        let matcher = settings.reconciler.matchers.first().unwrap();
+        assert!(matcher.conditions.is_empty());
+        let reason_quorum = matcher.reason_quorum.as_ref().unwrap();
+        assert_eq!("^Pcie", reason_quorum.reason);
+        assert_eq!(3, reason_quorum.count);
+    }
+
+    #[test]
+    fn new_returns_settings_with_required_labels() {
+        let res = Settings::new("src/settings/testfiles/required_labels.toml");
+        assert!(res.is_ok());
+        let settings = res.unwrap();
+        let matcher = settings.reconciler.matchers.first().unwrap();
+        assert!(matcher.conditions.is_empty());
+        assert_eq!(
+            vec!["cost-center".to_string(), "team".to_string()],
+            matcher.required_labels
+        );
+    }
+
+    #[test]
+    fn new_returns_settings_with_node_age() {
+        let res = Settings::new("src/settings/testfiles/node_age.toml");
+        assert!(res.is_ok());
+        let settings = res.unwrap();
+        let matcher = settings.reconciler.matchers.first().unwrap();
+        assert_eq!(Some(Duration::from_secs(60)), matcher.node_age_min);
+        assert_eq!(Some(Duration::from_secs(15 * 60)), matcher.node_age_max);
+    }
+
+    #[test]
+    fn new_returns_settings_with_rollout() {
+        let res = Settings::new("src/settings/testfiles/rollout.toml");
+        assert!(res.is_ok());
+        let settings = res.unwrap();
+        let rollout = settings.reconciler.rollout.unwrap();
+        assert_eq!(10, rollout.initial_percentage);
+        assert_eq!(Duration::from_secs(2 * 60 * 60), rollout.ramp_duration);
+    }
+
+    #[test]
+    fn new_returns_settings_with_normalize() {
+        let res = Settings::new("src/settings/testfiles/normalize.toml");
+        assert!(res.is_ok());
+        let settings = res.unwrap();
+        let conditions = &settings.reconciler.matchers.first().unwrap().conditions;
+        assert_eq!(
+            Normalize::TrimLowercase,
+            conditions.first().unwrap().normalize
+        );
+        assert_eq!(Normalize::Trim, conditions.get(1).unwrap().normalize);
+    }
+
+    #[test]
+    fn new_returns_settings_with_heartbeat() {
+        let res = Settings::new("src/settings/testfiles/heartbeat.toml");
+        assert!(res.is_ok());
+        let settings = res.unwrap();
+        let matchers = &settings.reconciler.matchers;
+        let condition = matchers.first().unwrap().conditions.first().unwrap();
+        assert_eq!(Some(Duration::from_secs(120)), condition.max_heartbeat_age);
+        assert!(!condition.match_stale_heartbeat);
+        let condition = matchers.get(1).unwrap().conditions.first().unwrap();
+        assert_eq!(Some(Duration::from_secs(120)), condition.max_heartbeat_age);
+        assert!(condition.match_stale_heartbeat);
+    }
+
+    #[test_case(Polarity::Positive, vec!["True"] ; "positive polarity matches true")]
+    #[test_case(Polarity::Negative, vec!["False", "Unknown"] ; "negative polarity matches false and unknown")]
+    fn polarity_statuses(polarity: Polarity, expected: Vec<&str>) {
+        assert_eq!(expected, polarity.statuses());
+    }
+
+    #[test]
+    fn new_returns_settings_with_taint_templates() {
+        let res = Settings::new("src/settings/testfiles/taint_templates.toml");
+        assert!(res.is_ok());
+        let settings = res.unwrap();
+        let taint = settings
+            .reconciler
+            .matchers
+            .first()
+            .unwrap()
+            .taint
+            .as_ref()
+            .unwrap();
+        assert_eq!("hardware-${component}", taint.key);
+        assert_eq!("${zone}-${timestamp}", taint.value);
+    }
+
+    #[test]
+    fn new_returns_settings_with_matcher_name() {
+        let res = Settings::new("src/settings/testfiles/matcher_name.toml");
+        assert!(res.is_ok());
+        let settings = res.unwrap();
+        let matcher = settings.reconciler.matchers.first().unwrap();
+        assert_eq!(Some("nic-failure".to_string()), matcher.name);
+        assert_eq!(None, settings.reconciler.matchers.get(1).unwrap().name);
+    }
+
+    #[test]
+    fn new_returns_settings_with_weight() {
+        let res = Settings::new("src/settings/testfiles/weight.toml");
+        assert!(res.is_ok());
+        let settings = res.unwrap();
+        let matcher = settings.reconciler.matchers.first().unwrap();
+        assert_eq!(Some(40), matcher.weight);
+    }
+
+    #[test]
+    fn new_returns_settings_with_profiles() {
+        let res = Settings::new("src/settings/testfiles/profiles.toml");
+        assert!(res.is_ok());
+        let settings = res.unwrap();
+        assert!(settings.reconciler.matchers.is_empty());
+        assert_eq!(3, settings.profiles.len());
+        let hardware = settings.profiles.first().unwrap();
+        assert_eq!("hardware", hardware.name);
+        assert!(hardware.enabled);
+        assert_eq!(Some("agentpool=zeus1".to_string()), hardware.node_selector);
+        assert!(hardware.reconciler.skip_cordoned_nodes);
+        assert_eq!(1, hardware.reconciler.matchers.len());
+        let disabled = settings.profiles.get(2).unwrap();
+        assert!(!disabled.enabled);
+        assert_eq!(None, disabled.node_selector);
+    }
+
+    #[test]
+    fn new_expands_pressure_signals_into_condition() {
+        let res = Settings::new("src/settings/testfiles/pressure_signals.toml");
+        assert!(res.is_ok());
+        let settings = res.unwrap();
+        let matcher = settings.reconciler.matchers.first().unwrap();
+        assert_eq!(
+            vec![PressureSignal::MemoryPressure, PressureSignal::PIDPressure],
+            matcher.pressure_signals
+        );
+        assert_eq!(1, matcher.conditions.len());
+        let condition = matcher.conditions.first().unwrap();
+        assert_eq!("MemoryPressure|PIDPressure", condition.type_);
+        assert_eq!(Some("True".to_string()), condition.status);
+        assert!(condition.full_match);
+    }
+
+    #[test]
+    fn new_returns_settings_with_capacity_requires() {
+        let res = Settings::new("src/settings/testfiles/capacity_requires.toml");
+        assert!(res.is_ok());
+        let settings = res.unwrap();
+        let matcher = settings.reconciler.matchers.first().unwrap();
+        assert_eq!(
+            BTreeMap::from([
+                ("ephemeral-storage".to_string(), "0".to_string()),
+                ("nvidia.com/gpu".to_string(), "1".to_string()),
+            ]),
+            matcher.capacity_requires
+        );
+    }
+
+    #[test]
+    fn new_returns_settings_with_missing_label_fallback() {
+        let res = Settings::new("src/settings/testfiles/label_template.toml");
+        assert!(res.is_ok());
+        let settings = res.unwrap();
+        let matcher = settings.reconciler.matchers.first().unwrap();
+        assert_eq!(
+            "${label:topology.kubernetes.io/zone}",
+            matcher.taint.as_ref().unwrap().value
+        );
+        assert_eq!(MissingLabelPolicy::Fallback, matcher.missing_label_policy);
+        assert_eq!(Some("unknown".to_string()), matcher.missing_label_fallback);
+    }
+
+    #[test]
+    fn new_returns_settings_with_effect_mapping() {
+        let res = Settings::new("src/settings/testfiles/effect_mapping.toml");
+        assert!(res.is_ok());
+        let settings = res.unwrap();
+        let matcher = settings.reconciler.matchers.first().unwrap();
+        assert_eq!(2, matcher.effect_mapping.len());
+        let mapping = matcher.effect_mapping.first().unwrap();
+        assert_eq!("Warning", mapping.severity);
+        assert_eq!(TaintEffect::PreferNoSchedule, mapping.effect);
+        let mapping = matcher.effect_mapping.get(1).unwrap();
+        assert_eq!("Critical", mapping.severity);
+        assert_eq!(TaintEffect::NoExecute, mapping.effect);
+    }
+
+    #[test]
+    fn new_returns_settings_with_client_timeouts() {
+        let res = Settings::new("src/settings/testfiles/client.toml");
+        assert!(res.is_ok());
+        let settings = res.unwrap();
+        assert_eq!(
+            Some(Duration::from_secs(5)),
+            settings.client.connect_timeout
+        );
+        assert_eq!(Some(Duration::from_secs(90)), settings.client.read_timeout);
+        assert_eq!(
+            Some("/var/log/tainter/tainter.log".to_string()),
+            settings.log.file
+        );
+        let aggregation = settings.log.aggregation.unwrap();
+        assert_eq!(100, aggregation.threshold);
+        assert_eq!(Duration::from_secs(60), aggregation.window);
+        assert_eq!(
+            Some("http://proxy.internal:3128".to_string()),
+            settings.client.proxy_url
+        );
+        assert_eq!(
+            Some("/var/run/secrets/tainter/token".to_string()),
+            settings.client.token_file
+        );
+        assert_eq!(
+            Some("/var/run/secrets/tainter/ca.crt".to_string()),
+            settings.client.ca_cert_file
+        );
+        assert_eq!(Some(10), settings.client.init_retries);
+        assert_eq!(
+            Some(Duration::from_millis(500)),
+            settings.client.init_retry_delay
+        );
+        let rate_limit = settings.client.rate_limit.unwrap();
+        assert_eq!(2.5, rate_limit.qps);
+        assert_eq!(10, rate_limit.burst);
+    }
+
+    #[test]
+    fn to_json_prints_settings_with_environment_overrides_and_defaults() {
+        let variables = [
+            ("TAINTER__SERVER__PORT", "9090"),
+            ("TAINTER__LOG__MAX_LEVEL", "debug"),
+        ];
+        let environment = Environment::with_prefix("TAINTER")
+            .separator("__")
+            .source(Some(
+                variables
+                    .iter()
+                    .map(|(key, value)| (key.to_string(), value.to_string()))
+                    .collect(),
+            ));
+        let settings =
+            Settings::with_environment("src/settings/testfiles/valid.toml", environment).unwrap();
+
+        let json: serde_json::Value = serde_json::from_str(&settings.to_json().unwrap()).unwrap();
+        // The file sets port 8080 and max_level info, which the environment overrides.
+        assert_eq!(9090, json["server"]["port"]);
+        assert_eq!("debug", json["log"]["max_level"]);
+        assert_eq!("0.0.0.0", json["server"]["host"]);
+        assert_eq!(1, json["server"]["workers"]);
+        assert_eq!("100ms", json["reconciler"]["error_retry_delay"]);
+        assert_eq!(
+            "NetworkInterfaceCard",
+            json["reconciler"]["matchers"][0]["conditions"][0]["type"]
+        );
+    }
+
+    #[test]
+    fn new_returns_settings_with_webhook() {
+        let res = Settings::new("src/settings/testfiles/webhook.toml");
+        assert!(res.is_ok());
+        let settings = res.unwrap();
+        let webhook = settings.webhook.unwrap();
+        assert_eq!("https://incidents.internal/tainter", webhook.url);
+        assert_eq!(Duration::from_secs(5), webhook.timeout);
+        assert_eq!(5, webhook.max_retries);
+        assert_eq!(Duration::from_millis(500), webhook.retry_delay);
+    }
+
+    #[test]
+    fn new_returns_settings_with_decision_log() {
+        let res = Settings::new("src/settings/testfiles/decision_log.toml");
+        assert!(res.is_ok());
+        let settings = res.unwrap();
+        assert_eq!(
+            Some("/var/log/tainter/decisions.ndjson".to_string()),
+            settings.decision_log.unwrap().path
+        );
+    }
+
+    #[test]
+    fn new_returns_settings_with_disabled_matcher() {
+        let res = Settings::new("src/settings/testfiles/disabled_matcher.toml");
+        assert!(res.is_ok());
+        let settings = res.unwrap();
+        assert_eq!(2, settings.reconciler.matchers.len());
+        assert!(!settings.reconciler.matchers.first().unwrap().enabled);
+        assert_eq!(
+            MatcherMode::Report,
+            settings.reconciler.matchers.get(1).unwrap().mode
+        );
+        assert!(settings.reconciler.matchers.get(1).unwrap().enabled);
+        assert_eq!(
+            Lifecycle::AddOnly,
+            settings.reconciler.matchers.first().unwrap().lifecycle
+        );
+        assert_eq!(
+            Lifecycle::Managed,
+            settings.reconciler.matchers.get(1).unwrap().lifecycle
+        );
+        assert!(!settings.reconciler.matchers.first().unwrap().graceful_evict);
+        assert!(settings.reconciler.matchers.get(1).unwrap().graceful_evict);
+        assert!(settings
+            .reconciler
+            .matchers
+            .first()
+            .unwrap()
+            .schedule
+            .is_none());
+        let schedule = settings
+            .reconciler
+            .matchers
+            .get(1)
+            .unwrap()
+            .schedule
+            .as_ref();
+        assert_eq!("0 0 2 * * Sat,Sun", schedule.unwrap().cron);
+        assert_eq!(Duration::from_secs(4 * 60 * 60), schedule.unwrap().duration);
+        assert!(settings.reconciler.skip_cordoned_nodes);
+        assert!(settings.reconciler.require_node_ready);
+        assert!(settings.reconciler.publish_condition);
+        assert!(settings.reconciler.prune_orphaned_taints);
+        assert_eq!(
+            Some(Duration::from_secs(900)),
+            settings.reconciler.prune_interval
+        );
+        assert_eq!(
+            Some("topology.kubernetes.io/zone".to_string()),
+            settings.reconciler.group_by
+        );
+        assert_eq!(
+            Some(Duration::from_secs(30)),
+            settings.reconciler.stale_condition_tolerance
+        );
+        assert_eq!(Some(2), settings.reconciler.min_matching_matchers);
+        assert_eq!(Some(3), settings.reconciler.cascade_max_depth);
+        assert_eq!(Some(80), settings.reconciler.systemic_guard_percentage);
+        assert!(!settings.reconciler.protect_control_plane);
+        assert!(settings.reconciler.protect_self);
+        assert_eq!(
+            Duration::from_secs(2),
+            settings.reconciler.error_retry_delay
+        );
+        assert_eq!(EffectDowngrade::Allow, settings.reconciler.effect_downgrade);
+        assert_eq!(
+            UpdateStrategy::JsonPatch,
+            settings.reconciler.update_strategy
+        );
+        assert_eq!(
+            ApplyStrategy::PerMatcher,
+            settings.reconciler.apply_strategy
+        );
+        assert!(settings.reconciler.short_circuit);
+        assert_eq!(
+            Some("tainter.io/last-action".to_string()),
+            settings.reconciler.last_action_annotation
+        );
+        assert_eq!(Some(3), settings.reconciler.min_nodes_before_acting);
+        assert!(!settings.server.enabled);
+        assert!(settings.server.admin_endpoints);
+        assert_eq!(
+            Some(Duration::from_secs(30)),
+            settings.reconciler.node_write_cooldown
+        );
+        assert_eq!(0, settings.reconciler.matchers.first().unwrap().priority);
+        assert_eq!(10, settings.reconciler.matchers.get(1).unwrap().priority);
+        assert_eq!(
+            None,
+            settings.reconciler.matchers.first().unwrap().field_manager
+        );
+        assert_eq!(
+            Some("tainter-report".to_string()),
+            settings.reconciler.matchers.get(1).unwrap().field_manager
+        );
+        assert!(
+            !settings
+                .reconciler
+                .matchers
+                .first()
+                .unwrap()
+                .on_transition_only
+        );
+        assert!(
+            settings
+                .reconciler
+                .matchers
+                .get(1)
+                .unwrap()
+                .on_transition_only
+        );
+        assert_eq!(
+            Some(Duration::from_secs(10)),
+            settings.reconciler.startup_jitter
+        );
+        assert_eq!(
+            Some(Duration::from_secs(3)),
+            settings.reconciler.relist_settle
+        );
+        assert_eq!(
+            Some(Duration::from_secs(120)),
+            settings.reconciler.sweep_deadline
+        );
     }
 }
@@ -1,88 +1,2053 @@
-use crate::reconciler::{Condition, Configuration, Reconciler};
+use crate::authorizer::{Authorizer, Decision};
+use crate::client::RateLimiter;
+use crate::clock::{Clock, UtcClock};
+use crate::decision_log::DecisionLog;
+use crate::metrics::Metrics;
+use crate::quantity;
+use crate::reconciler::{
+    is_valid_taint_key, AdHocChange, ApplyStrategy, Condition, Configuration, DisruptionBudget,
+    EffectDowngrade, Heartbeat, Lifecycle, LogAggregation, MaxTainted, MissingLabelPolicy, Mode,
+    Normalize, Options, Pattern, ReasonQuorum, Reconciler, Rollout, Schedule, SelfTestSummary,
+    Status, StatusReport, TaintCondition, UpdateStrategy,
+};
+use crate::settings;
 use crate::settings::Settings;
-use actix_web::{get, App, HttpResponse, HttpServer, Responder};
+use crate::webhook::Webhook;
+use actix_web::http::header;
+use actix_web::{delete, get, post, web, App, HttpRequest, HttpResponse, HttpServer, Responder};
 use k8s_openapi::api::core::v1::Taint;
 use kube::Client;
 use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io;
+use std::str::FromStr;
+use std::sync::Arc;
+use thiserror::Error;
+use tracing::Instrument;
+
+// The environment variable that the deployment sets to the name of the node that hosts Tainter.
+const SELF_NODE_NAME_ENV: &str = "NODE_NAME";
+
+#[derive(Error, Debug)]
+pub enum NewTainterError {
+    #[error("error opening decision log file {0}: {1}")]
+    OpenDecisionLog(String, io::Error),
+}
 
 pub struct Tainter {
     host: String,
     port: u16,
-    reconciler: Reconciler,
+    workers: usize,
+    // If false, Tainter runs as a pure controller without serving any endpoints.
+    server_enabled: bool,
+    // If true, the server also serves the admin endpoints, e.g. to add taints by hand.
+    admin_endpoints: bool,
+    // Authorizes the callers of the admin endpoints.
+    authorizer: Arc<Authorizer>,
+    // Shared between the watch loop and the server's /plan endpoint.
+    reconciler: Arc<Reconciler>,
+    // The reconcilers of the profiles by profile name.
+    profiles: Arc<Profiles>,
+    metrics: Metrics,
+}
+
+struct Profiles(BTreeMap<String, Arc<Reconciler>>);
+
+// The options that the reconcilers of all profiles share, which are built once so that the rate
+// limit applies to all of Tainter's updates and the decision log file is only opened once.
+struct SharedOptions {
+    rate_limiter: Option<Arc<RateLimiter>>,
+    webhook: Option<Arc<Webhook>>,
+    decision_log: Option<Arc<DecisionLog>>,
+}
+
+impl SharedOptions {
+    fn new(settings: &Settings) -> Result<Self, NewTainterError> {
+        let decision_log = settings
+            .decision_log
+            .as_ref()
+            .map(|decision_log| match &decision_log.path {
+                Some(path) => DecisionLog::file(path)
+                    .map_err(|error| NewTainterError::OpenDecisionLog(path.clone(), error)),
+                None => Ok(DecisionLog::stdout()),
+            })
+            .transpose()?;
+
+        Ok(SharedOptions {
+            rate_limiter: settings
+                .client
+                .rate_limit
+                .as_ref()
+                .map(|rate_limit| Arc::new(RateLimiter::new(rate_limit.qps, rate_limit.burst))),
+            webhook: settings.webhook.as_ref().map(|webhook| {
+                Arc::new(Webhook::new(
+                    webhook.url.clone(),
+                    webhook.timeout,
+                    webhook.max_retries,
+                    webhook.retry_delay,
+                ))
+            }),
+            decision_log: decision_log.map(Arc::new),
+        })
+    }
+}
+
+// The JSON body of endpoints that report a status rather than data, e.g. /health, and of all error
+// responses. Errors carry a machine-readable code alongside a human-readable message.
+#[derive(Serialize)]
+struct StatusBody {
+    status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<ErrorBody>,
+}
+
+#[derive(Serialize)]
+struct ErrorBody {
+    code: &'static str,
+    message: String,
+}
+
+impl StatusBody {
+    fn status(status: &'static str) -> Self {
+        StatusBody {
+            status,
+            error: None,
+        }
+    }
+
+    fn error(code: &'static str, message: impl Into<String>) -> Self {
+        StatusBody {
+            status: "error",
+            error: Some(ErrorBody {
+                code,
+                message: message.into(),
+            }),
+        }
+    }
 }
 
 #[get("/health")]
 async fn health() -> impl Responder {
-    HttpResponse::Ok().body("healthy")
+    HttpResponse::Ok().json(StatusBody::status("healthy"))
 }
 
-impl Tainter {
-    pub fn new(settings: Settings, client: Client) -> Self {
-        let matchers = Self::matchers(&settings);
+#[derive(Serialize)]
+struct Version {
+    version: &'static str,
+    git_sha: &'static str,
+    build_timestamp: &'static str,
+}
+
+#[get("/version")]
+async fn version() -> impl Responder {
+    HttpResponse::Ok().json(Version {
+        version: env!("CARGO_PKG_VERSION"),
+        git_sha: env!("GIT_SHA"),
+        build_timestamp: env!("BUILD_TIMESTAMP"),
+    })
+}
+
+#[get("/metrics")]
+async fn gather_metrics(metrics: web::Data<Metrics>) -> impl Responder {
+    HttpResponse::Ok()
+        .content_type("text/plain; version=0.0.4")
+        .body(metrics.gather())
+}
+
+pub struct TainterBuilder {
+    client: Client,
+    host: String,
+    port: u16,
+    workers: usize,
+    server_enabled: bool,
+    admin_endpoints: bool,
+    matchers: Vec<Configuration>,
+    options: Options,
+    profiles: Vec<(String, Vec<Configuration>, Options)>,
+    clock: Arc<dyn Clock>,
+}
+
+impl TainterBuilder {
+    // The server listens on 0.0.0.0:8080 with a single worker thread unless configured otherwise.
+    pub fn new(client: Client) -> Self {
+        TainterBuilder {
+            client,
+            host: String::from("0.0.0.0"),
+            port: 8080,
+            workers: 1,
+            server_enabled: true,
+            admin_endpoints: false,
+            matchers: vec![],
+            options: Options::default(),
+            profiles: vec![],
+            clock: Arc::new(UtcClock),
+        }
+    }
+
+    pub fn host(mut self, host: impl Into<String>) -> Self {
+        self.host = host.into();
+        self
+    }
+
+    pub fn port(mut self, port: u16) -> Self {
+        self.port = port;
+        self
+    }
+
+    pub fn workers(mut self, workers: usize) -> Self {
+        self.workers = workers;
+        self
+    }
+
+    pub fn server_enabled(mut self, server_enabled: bool) -> Self {
+        self.server_enabled = server_enabled;
+        self
+    }
+
+    pub fn admin_endpoints(mut self, admin_endpoints: bool) -> Self {
+        self.admin_endpoints = admin_endpoints;
+        self
+    }
+
+    pub fn matchers(mut self, matchers: Vec<Configuration>) -> Self {
+        self.matchers = matchers;
+        self
+    }
+
+    pub fn options(mut self, options: Options) -> Self {
+        self.options = options;
+        self
+    }
+
+    // Adds a profile, which reconciles the nodes that options.node_selector selects with its own
+    // matchers, independently of the reconciler and the other profiles.
+    pub fn profile(
+        mut self,
+        name: impl Into<String>,
+        matchers: Vec<Configuration>,
+        options: Options,
+    ) -> Self {
+        self.profiles.push((name.into(), matchers, options));
+        self
+    }
+
+    pub fn clock(mut self, clock: Box<dyn Clock>) -> Self {
+        self.clock = Arc::from(clock);
+        self
+    }
+
+    pub fn build(self) -> Tainter {
+        let metrics = Metrics::new();
+        let authorizer = Authorizer::new(self.client.clone());
 
-        let reconciler = Reconciler::new(client, matchers);
+        let profiles = self
+            .profiles
+            .into_iter()
+            .map(|(name, matchers, options)| {
+                let options = Options {
+                    profile: Some(name.clone()),
+                    ..options
+                };
+                let reconciler = Reconciler::new(
+                    self.client.clone(),
+                    matchers,
+                    options,
+                    metrics.clone(),
+                    Box::new(self.clock.clone()),
+                );
+                (name, Arc::new(reconciler))
+            })
+            .collect();
+
+        let reconciler = Reconciler::new(
+            self.client,
+            self.matchers,
+            self.options,
+            metrics.clone(),
+            Box::new(self.clock),
+        );
 
         Tainter {
-            host: settings.server.host,
-            port: settings.server.port,
-            reconciler,
+            host: self.host,
+            port: self.port,
+            workers: self.workers,
+            server_enabled: self.server_enabled,
+            admin_endpoints: self.admin_endpoints,
+            authorizer: Arc::new(authorizer),
+            reconciler: Arc::new(reconciler),
+            profiles: Arc::new(Profiles(profiles)),
+            metrics,
         }
     }
+}
 
-    fn matchers(settings: &Settings) -> Vec<Configuration> {
-        settings.reconciler.matchers.iter().map(|matcher| {
-            let taint = Taint{
-                effect: matcher.taint.effect.to_string(),
-                key: matcher.taint.key.clone(),
-                time_added: None,
-                value: Some(matcher.taint.value.clone()),
+// Reports the taints that Tainter would add to and remove from each node given the current
+// configuration, without changing any nodes.
+#[get("/plan")]
+async fn plan(reconciler: web::Data<Reconciler>) -> impl Responder {
+    plan_response(&reconciler).await
+}
+
+async fn plan_response(reconciler: &Reconciler) -> HttpResponse {
+    match reconciler.plan().await {
+        Ok(plans) => HttpResponse::Ok().json(plans),
+        Err(error) => {
+            tracing::error!(error = error.to_string(), "Error planning changes to nodes");
+            HttpResponse::InternalServerError().json(StatusBody::error(
+                "list_nodes_failed",
+                "error listing nodes",
+            ))
+        }
+    }
+}
+
+// Reports whether the loaded configuration is valid, how many matchers and conditions it has, and
+// warnings about configuration that is likely unintended.
+#[get("/healthz/config")]
+async fn config(reconciler: web::Data<Reconciler>) -> impl Responder {
+    HttpResponse::Ok().json(reconciler.config_report())
+}
+
+// Pauses reconciliation, e.g. during cluster maintenance. Tainter keeps evaluating nodes but does
+// not update them until reconciliation is resumed. Pausing and resuming decide whether Tainter
+// updates nodes, so the caller must be allowed to update nodes themselves.
+#[post("/pause")]
+async fn pause(
+    reconciler: web::Data<Reconciler>,
+    authorizer: web::Data<Authorizer>,
+    request: HttpRequest,
+) -> impl Responder {
+    if let Err(response) = authorize(&authorizer, &request, None).await {
+        return response;
+    }
+
+    reconciler.pause();
+    HttpResponse::Ok().json(StatusBody::status("paused"))
+}
+
+#[post("/resume")]
+async fn resume(
+    reconciler: web::Data<Reconciler>,
+    authorizer: web::Data<Authorizer>,
+    request: HttpRequest,
+) -> impl Responder {
+    if let Err(response) = authorize(&authorizer, &request, None).await {
+        return response;
+    }
+
+    reconciler.resume();
+    HttpResponse::Ok().json(StatusBody::status("running"))
+}
+
+// The runtime state of the top-level reconciler along with the state of each profile.
+#[derive(Serialize)]
+struct TainterStatus {
+    #[serde(flatten)]
+    reconciler: StatusReport,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    profiles: BTreeMap<String, StatusReport>,
+}
+
+// Summarises the runtime state of reconciliation, e.g. whether it is paused, when it last
+// reconciled and how many nodes Tainter tainted.
+#[get("/status")]
+async fn report_status(
+    reconciler: web::Data<Reconciler>,
+    profiles: web::Data<Profiles>,
+) -> impl Responder {
+    HttpResponse::Ok().json(TainterStatus {
+        reconciler: reconciler.status_report(),
+        profiles: profiles
+            .0
+            .iter()
+            .map(|(name, reconciler)| (name.clone(), reconciler.status_report()))
+            .collect(),
+    })
+}
+
+fn profile_not_found(name: &str) -> HttpResponse {
+    HttpResponse::NotFound().json(StatusBody::error(
+        "profile_not_found",
+        format!("no profile named {name}"),
+    ))
+}
+
+// Pauses the reconciliation of a single profile.
+#[post("/profiles/{name}/pause")]
+async fn pause_profile(
+    profiles: web::Data<Profiles>,
+    authorizer: web::Data<Authorizer>,
+    request: HttpRequest,
+    name: web::Path<String>,
+) -> impl Responder {
+    if let Err(response) = authorize(&authorizer, &request, None).await {
+        return response;
+    }
+
+    let Some(reconciler) = profiles.0.get(name.as_str()) else {
+        return profile_not_found(&name);
+    };
+    reconciler.pause();
+    HttpResponse::Ok().json(StatusBody::status("paused"))
+}
+
+#[post("/profiles/{name}/resume")]
+async fn resume_profile(
+    profiles: web::Data<Profiles>,
+    authorizer: web::Data<Authorizer>,
+    request: HttpRequest,
+    name: web::Path<String>,
+) -> impl Responder {
+    if let Err(response) = authorize(&authorizer, &request, None).await {
+        return response;
+    }
+
+    let Some(reconciler) = profiles.0.get(name.as_str()) else {
+        return profile_not_found(&name);
+    };
+    reconciler.resume();
+    HttpResponse::Ok().json(StatusBody::status("running"))
+}
+
+// Reports whether the reconciliation of a single profile is paused.
+#[get("/profiles/{name}/status")]
+async fn profile_status(profiles: web::Data<Profiles>, name: web::Path<String>) -> impl Responder {
+    let Some(reconciler) = profiles.0.get(name.as_str()) else {
+        return profile_not_found(&name);
+    };
+    let status = if reconciler.is_paused() {
+        "paused"
+    } else {
+        "running"
+    };
+    HttpResponse::Ok().json(StatusBody::status(status))
+}
+
+// Reports the taints that a single profile would add to and remove from each of its nodes.
+#[get("/profiles/{name}/plan")]
+async fn plan_profile(profiles: web::Data<Profiles>, name: web::Path<String>) -> impl Responder {
+    let Some(reconciler) = profiles.0.get(name.as_str()) else {
+        return profile_not_found(&name);
+    };
+    plan_response(reconciler).await
+}
+
+// Explains why each matcher of a single profile does or doesn't apply to a node.
+#[get("/profiles/{name}/explain/{node}")]
+async fn explain_profile(
+    profiles: web::Data<Profiles>,
+    path: web::Path<(String, String)>,
+) -> impl Responder {
+    let (name, node_name) = path.into_inner();
+    let Some(reconciler) = profiles.0.get(name.as_str()) else {
+        return profile_not_found(&name);
+    };
+    explain_response(reconciler, &node_name).await
+}
+
+// The JSON body of requests that add an ad-hoc taint.
+#[derive(Deserialize)]
+struct AdHocTaint {
+    key: String,
+    value: Option<String>,
+    effect: String,
+}
+
+// Authorizes the caller of an admin endpoint by the bearer token of the request. Returns the
+// response to send instead if the caller may not update the node, or nodes in general if node_name
+// is not set.
+async fn authorize(
+    authorizer: &Authorizer,
+    request: &HttpRequest,
+    node_name: Option<&str>,
+) -> Result<(), HttpResponse> {
+    let token = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    match authorizer.authorize(token, node_name).await {
+        Ok(Decision::Allowed) => Ok(()),
+        Ok(Decision::Unauthenticated) => Err(HttpResponse::Unauthorized().json(StatusBody::error(
+            "unauthenticated",
+            "a valid bearer token is required",
+        ))),
+        Ok(Decision::Forbidden(user)) => {
+            tracing::warn!(
+                user,
+                "Denying admin request of user who may not update nodes"
+            );
+            Err(HttpResponse::Forbidden().json(StatusBody::error(
+                "forbidden",
+                format!("user {user} may not update nodes"),
+            )))
+        }
+        Err(error) => {
+            tracing::error!(error = error.to_string(), "Error authorizing admin request");
+            Err(HttpResponse::InternalServerError().json(StatusBody::error(
+                "authorization_failed",
+                "error authorizing request",
+            )))
+        }
+    }
+}
+
+fn node_error(name: &str, error: kube::Error) -> HttpResponse {
+    match error {
+        kube::Error::Api(response) if response.code == 404 => HttpResponse::NotFound().json(
+            StatusBody::error("node_not_found", format!("no node named {name}")),
+        ),
+        kube::Error::Api(response) if response.code == 409 => HttpResponse::Conflict().json(
+            StatusBody::error("conflict", format!("node {name} was modified concurrently")),
+        ),
+        _ => HttpResponse::InternalServerError().json(StatusBody::error(
+            "update_node_failed",
+            format!("error updating node {name}"),
+        )),
+    }
+}
+
+// Adds a taint to a node regardless of the matchers, e.g. to take a node out of service by hand.
+// Tainter writes the taint with its own service account, so the caller must be allowed to update
+// the node themselves.
+#[post("/nodes/{name}/taints")]
+async fn add_taint(
+    reconciler: web::Data<Reconciler>,
+    authorizer: web::Data<Authorizer>,
+    request: HttpRequest,
+    name: web::Path<String>,
+    taint: web::Json<AdHocTaint>,
+) -> impl Responder {
+    if let Err(response) = authorize(&authorizer, &request, Some(&name)).await {
+        return response;
+    }
+
+    let taint = taint.into_inner();
+    if !["NoSchedule", "PreferNoSchedule", "NoExecute"].contains(&taint.effect.as_str()) {
+        return HttpResponse::BadRequest().json(StatusBody::error(
+            "invalid_taint",
+            format!("invalid taint effect {}", taint.effect),
+        ));
+    }
+    if !is_valid_taint_key(&taint.key) {
+        return HttpResponse::BadRequest().json(StatusBody::error(
+            "invalid_taint",
+            format!("invalid taint key {}", taint.key),
+        ));
+    }
+
+    let taint = Taint {
+        effect: taint.effect,
+        key: taint.key,
+        time_added: None,
+        value: taint.value,
+    };
+    match reconciler.add_taint(&name, taint).await {
+        Ok(AdHocChange::Added) => HttpResponse::Ok().json(StatusBody::status("added")),
+        Ok(AdHocChange::Unchanged) => HttpResponse::Ok().json(StatusBody::status("unchanged")),
+        Ok(AdHocChange::Unmanaged) => HttpResponse::Conflict().json(StatusBody::error(
+            "taint_not_managed",
+            format!(
+                "node {name} has a taint with the same key and effect that Tainter does not manage"
+            ),
+        )),
+        Err(error) => node_error(&name, error),
+    }
+}
+
+// Removes the taints with the key from a node regardless of the matchers.
+#[delete("/nodes/{name}/taints/{key}")]
+async fn remove_taint(
+    reconciler: web::Data<Reconciler>,
+    authorizer: web::Data<Authorizer>,
+    request: HttpRequest,
+    path: web::Path<(String, String)>,
+) -> impl Responder {
+    let (name, key) = path.into_inner();
+    if let Err(response) = authorize(&authorizer, &request, Some(&name)).await {
+        return response;
+    }
+
+    match reconciler.remove_taint(&name, &key).await {
+        Ok(true) => HttpResponse::Ok().json(StatusBody::status("removed")),
+        Ok(false) => HttpResponse::Ok().json(StatusBody::status("unchanged")),
+        Err(error) => node_error(&name, error),
+    }
+}
+
+// Explains why each matcher does or doesn't apply to a node, e.g. which of its conditions no node
+// condition matches.
+#[get("/explain/{name}")]
+async fn explain(reconciler: web::Data<Reconciler>, name: web::Path<String>) -> impl Responder {
+    explain_response(&reconciler, &name).await
+}
+
+async fn explain_response(reconciler: &Reconciler, name: &str) -> HttpResponse {
+    match reconciler.explain(name).await {
+        Ok(explanation) => HttpResponse::Ok().json(explanation),
+        Err(kube::Error::Api(response)) if response.code == 404 => HttpResponse::NotFound().json(
+            StatusBody::error("node_not_found", format!("no node named {name}")),
+        ),
+        Err(error) => {
+            tracing::error!(error = error.to_string(), "Error getting node to explain");
+            HttpResponse::InternalServerError().json(StatusBody::error(
+                "get_node_failed",
+                format!("error getting node {name}"),
+            ))
+        }
+    }
+}
+
+impl Tainter {
+    pub fn new(settings: Settings, client: Client) -> Result<Self, NewTainterError> {
+        let matchers = Self::matchers(&settings.reconciler.matchers);
+        let shared = SharedOptions::new(&settings)?;
+
+        let shadow_matchers = settings
+            .shadow_reconciler
+            .as_ref()
+            .map(|shadow_reconciler| Self::matchers(&shadow_reconciler.matchers))
+            .unwrap_or_default();
+
+        let options = Options {
+            shadow_matchers,
+            ..Self::options(&settings, &settings.reconciler, &shared)
+        };
+
+        let mut builder = Self::builder(client)
+            .host(settings.server.host.clone())
+            .port(settings.server.port)
+            .workers(settings.server.workers)
+            .server_enabled(settings.server.enabled)
+            .admin_endpoints(settings.server.admin_endpoints)
+            .matchers(matchers)
+            .options(options);
+        for profile in settings.profiles.iter().filter(|profile| profile.enabled) {
+            let options = Options {
+                node_selector: profile.node_selector.clone(),
+                ..Self::options(&settings, &profile.reconciler, &shared)
             };
+            builder = builder.profile(
+                profile.name.clone(),
+                Self::matchers(&profile.reconciler.matchers),
+                options,
+            );
+        }
+
+        Ok(builder.build())
+    }
 
-            let conditions: Vec<Condition> = matcher.conditions.iter().map(|cond| {
-                Condition{
-                    type_: Regex::new(cond.type_.as_str()).expect("regular expression should have been validated as part of initializing Settings"),
-                    status: Regex::new(cond.status.as_str()).expect("regular expression should have been validated as part of initializing Settings"),
+    fn options(
+        settings: &Settings,
+        reconciler: &settings::Reconciler,
+        shared: &SharedOptions,
+    ) -> Options {
+        Options {
+            max_nodes_per_sweep: reconciler.max_nodes_per_sweep,
+            sweep_deadline: reconciler.sweep_deadline,
+            min_matching_matchers: reconciler.min_matching_matchers,
+            cascade_max_depth: reconciler.cascade_max_depth,
+            systemic_guard_percentage: reconciler.systemic_guard_percentage,
+            skip_cordoned_nodes: reconciler.skip_cordoned_nodes,
+            require_node_ready: reconciler.require_node_ready,
+            protect_control_plane: reconciler.protect_control_plane,
+            self_node_name: reconciler
+                .protect_self
+                .then(|| Self::self_node_name(std::env::var(SELF_NODE_NAME_ENV).ok()))
+                .flatten(),
+            error_retry_delay: reconciler.error_retry_delay,
+            stale_condition_tolerance: reconciler.stale_condition_tolerance.map(|tolerance| {
+                chrono::Duration::from_std(tolerance)
+                    .expect("stale_condition_tolerance should be within range")
+            }),
+            log_aggregation: settings
+                .log
+                .aggregation
+                .as_ref()
+                .map(|aggregation| LogAggregation {
+                    threshold: aggregation.threshold,
+                    window: aggregation.window,
+                }),
+            startup_jitter: reconciler.startup_jitter,
+            relist_settle: reconciler.relist_settle,
+            publish_condition: reconciler.publish_condition,
+            prune_orphaned_taints: reconciler.prune_orphaned_taints,
+            prune_interval: reconciler.prune_interval,
+            group_by: reconciler.group_by.clone(),
+            effect_downgrade: match reconciler.effect_downgrade {
+                settings::EffectDowngrade::Forbid => EffectDowngrade::Forbid,
+                settings::EffectDowngrade::Allow => EffectDowngrade::Allow,
+            },
+            update_strategy: match reconciler.update_strategy {
+                settings::UpdateStrategy::Replace => UpdateStrategy::Replace,
+                settings::UpdateStrategy::JsonPatch => UpdateStrategy::JsonPatch,
+            },
+            short_circuit: reconciler.short_circuit,
+            last_action_annotation: reconciler.last_action_annotation.clone(),
+            min_nodes_before_acting: reconciler.min_nodes_before_acting,
+            node_write_cooldown: reconciler.node_write_cooldown.map(|cooldown| {
+                chrono::Duration::from_std(cooldown)
+                    .expect("node_write_cooldown should be within range")
+            }),
+            apply_strategy: match reconciler.apply_strategy {
+                settings::ApplyStrategy::Batch => ApplyStrategy::Batch,
+                settings::ApplyStrategy::PerMatcher => ApplyStrategy::PerMatcher,
+            },
+            disruption_budgets: reconciler
+                .disruption_budgets
+                .iter()
+                .map(Self::disruption_budget)
+                .collect(),
+            rollout: reconciler.rollout.as_ref().map(|rollout| Rollout {
+                initial_percentage: rollout.initial_percentage,
+                ramp_duration: chrono::Duration::from_std(rollout.ramp_duration)
+                    .expect("ramp_duration should be within range"),
+            }),
+            rate_limiter: shared.rate_limiter.clone(),
+            webhook: shared.webhook.clone(),
+            decision_log: shared.decision_log.clone(),
+            ..Default::default()
+        }
+    }
+
+    // Returns a builder to construct a Tainter programmatically rather than from settings.
+    pub fn builder(client: Client) -> TainterBuilder {
+        TainterBuilder::new(client)
+    }
+
+    fn matchers(matchers: &[settings::Matcher]) -> Vec<Configuration> {
+        matchers
+            .iter()
+            .map(|matcher| {
+                let taints = matcher.all_taints().map(Self::taint).collect();
+
+                let conditions = matcher.conditions.iter().map(Self::condition).collect();
+
+                let effect_mapping = matcher
+                    .effect_mapping
+                    .iter()
+                    .map(|mapping| (mapping.severity.clone(), mapping.effect.to_string()))
+                    .collect();
+
+                let mode = match matcher.mode {
+                    settings::MatcherMode::Taint => Mode::Taint,
+                    settings::MatcherMode::Report => Mode::Report,
+                };
+
+                Configuration {
+                    name: matcher.name.clone(),
+                    enabled: matcher.enabled,
+                    mode,
+                    conditions,
+                    taint_conditions: matcher
+                        .taint_conditions
+                        .iter()
+                        .map(|taint_condition| TaintCondition {
+                            key: Self::regex(&taint_condition.key),
+                            effect: taint_condition.effect.as_deref().map(Self::regex),
+                            value: taint_condition.value.as_deref().map(Self::regex),
+                        })
+                        .collect(),
+                    taints,
+                    effect_mapping,
+                    weight: matcher.weight,
+                    missing_label_policy: match matcher.missing_label_policy {
+                        settings::MissingLabelPolicy::Skip => MissingLabelPolicy::Skip,
+                        settings::MissingLabelPolicy::Fallback => MissingLabelPolicy::Fallback(
+                            matcher.missing_label_fallback.clone().unwrap_or_default(),
+                        ),
+                    },
+                    lifecycle: match matcher.lifecycle {
+                        settings::Lifecycle::AddOnly => Lifecycle::AddOnly,
+                        settings::Lifecycle::Managed => Lifecycle::Managed,
+                    },
+                    capacity_requires: matcher
+                        .capacity_requires
+                        .iter()
+                        .map(|(resource, quantity)| {
+                            let quantity = quantity::parse(quantity).expect(
+                                "quantity should have been validated as part of initializing Settings",
+                            );
+                            (resource.clone(), quantity)
+                        })
+                        .collect(),
+                    graceful_evict: matcher.graceful_evict,
+                    schedule: matcher.schedule.as_ref().map(|schedule| Schedule {
+                        cron: cron::Schedule::from_str(&schedule.cron)
+                            .expect(
+                                "cron expression should have been validated as part of initializing Settings",
+                            ),
+                        duration: chrono::Duration::from_std(schedule.duration)
+                            .expect("schedule duration should be within range"),
+                    }),
+                    reason_quorum: matcher.reason_quorum.as_ref().map(|reason_quorum| {
+                        ReasonQuorum {
+                            reason: Self::regex(&reason_quorum.reason),
+                            count: reason_quorum.count,
+                        }
+                    }),
+                    required_labels: matcher.required_labels.clone(),
+                    priority: matcher.priority,
+                    node_age_min: matcher.node_age_min.map(|node_age_min| {
+                        chrono::Duration::from_std(node_age_min)
+                            .expect("node_age_min should be within range")
+                    }),
+                    node_age_max: matcher.node_age_max.map(|node_age_max| {
+                        chrono::Duration::from_std(node_age_max)
+                            .expect("node_age_max should be within range")
+                    }),
+                    field_manager: matcher.field_manager.clone(),
+                    on_transition_only: matcher.on_transition_only,
+                    ..Default::default()
                 }
-            }).collect();
+            })
+            .collect()
+    }
+
+    // The name of the node that hosts Tainter, which the downward API exposes as an environment
+    // variable.
+    fn self_node_name(node_name: Option<String>) -> Option<String> {
+        let node_name = node_name.filter(|node_name| !node_name.is_empty());
+        if node_name.is_none() {
+            tracing::warn!(
+                env = SELF_NODE_NAME_ENV,
+                "protect_self is enabled, but the name of the node that hosts Tainter is not set"
+            );
+        }
+
+        node_name
+    }
+
+    fn disruption_budget(budget: &settings::DisruptionBudget) -> DisruptionBudget {
+        let max_tainted = match (budget.max_tainted, budget.max_tainted_percentage) {
+            (Some(count), _) => MaxTainted::Count(count),
+            (None, Some(percentage)) => MaxTainted::Percentage(percentage),
+            (None, None) => unreachable!(
+                "max_tainted should have been validated as part of initializing Settings"
+            ),
+        };
+
+        DisruptionBudget {
+            selector: budget.labels(),
+            max_tainted,
+        }
+    }
+
+    fn taint(taint: &settings::Taint) -> Taint {
+        Taint {
+            effect: taint.effect.to_string(),
+            key: taint.key.clone(),
+            time_added: None,
+            value: Some(taint.value.clone()),
+        }
+    }
 
-            Configuration{
-                conditions,
-                taint,
+    fn condition(condition: &settings::Condition) -> Condition {
+        let pattern = |pattern: &str| {
+            // A pattern without regex metacharacters only matches itself.
+            if condition.exact_literals && regex::escape(pattern) == pattern {
+                Pattern::Literal(pattern.to_string())
+            } else if condition.full_match {
+                Pattern::Regex(Self::regex(format!("^(?:{})$", pattern).as_str()))
+            } else {
+                Pattern::Regex(Self::regex(pattern))
             }
-        }).collect()
+        };
+
+        let type_ = if condition.types.is_empty() {
+            pattern(condition.type_.as_str())
+        } else {
+            // The types are combined into a single pattern that matches any of them, each in the
+            // way that it would match on its own.
+            let alternatives: Vec<String> = condition
+                .types
+                .iter()
+                .map(|type_| match pattern(type_) {
+                    Pattern::Literal(literal) => format!("^{}$", regex::escape(&literal)),
+                    Pattern::Regex(regex) => format!("(?:{})", regex.as_str()),
+                })
+                .collect();
+            Pattern::Regex(Self::regex(alternatives.join("|").as_str()))
+        };
+
+        Condition {
+            type_,
+            status: match (
+                &condition.status,
+                &condition.status_not,
+                &condition.status_in,
+                &condition.polarity,
+            ) {
+                (Some(status), _, _, _) => Status::Pattern(pattern(status)),
+                (None, Some(status_not), _, _) => Status::NotPattern(pattern(status_not)),
+                (None, None, Some(statuses), _) => Status::In(statuses.clone()),
+                (None, None, None, Some(polarity)) => Status::In(polarity.statuses()),
+                (None, None, None, None) => unreachable!(
+                    "status should have been validated as part of initializing Settings"
+                ),
+            },
+            reason: condition.reason.as_deref().map(Self::regex),
+            message: condition.message.as_deref().map(Self::regex),
+            max_age: condition.max_age.map(|max_age| {
+                chrono::Duration::from_std(max_age).expect("max_age should be within range")
+            }),
+            heartbeat: condition.max_heartbeat_age.map(|max_heartbeat_age| {
+                let max_heartbeat_age = chrono::Duration::from_std(max_heartbeat_age)
+                    .expect("max_heartbeat_age should be within range");
+                if condition.match_stale_heartbeat {
+                    Heartbeat::Stale(max_heartbeat_age)
+                } else {
+                    Heartbeat::Fresh(max_heartbeat_age)
+                }
+            }),
+            normalize: match condition.normalize {
+                settings::Normalize::Off => Normalize::Off,
+                settings::Normalize::Trim => Normalize::Trim,
+                settings::Normalize::TrimLowercase => Normalize::TrimLowercase,
+            },
+        }
+    }
+
+    fn regex(pattern: &str) -> Regex {
+        Regex::new(pattern).expect(
+            "regular expression should have been validated as part of initializing Settings",
+        )
+    }
+
+    // Lists nodes once and summarises how many are eligible for each matcher without starting the
+    // reconciler or the server.
+    pub async fn self_test(&self) -> Result<SelfTestSummary, kube::Error> {
+        self.reconciler.self_test().await
     }
 
     pub async fn start(self) -> std::io::Result<()> {
         tracing::info!("Starting Tainter");
 
+        let metrics_data = web::Data::new(self.metrics.clone());
+        let reconciler_data = web::Data::from(self.reconciler.clone());
+        let profiles_data = web::Data::from(self.profiles.clone());
+        let authorizer_data = web::Data::from(self.authorizer.clone());
+        let admin_endpoints = self.admin_endpoints;
+
+        for (name, reconciler) in &self.profiles.0 {
+            let reconciler = reconciler.clone();
+            // The logs of each profile are nested in a profile span to tell the profiles apart.
+            let span = tracing::info_span!("profile", name = name.as_str());
+            tokio::spawn(
+                async move {
+                    tracing::info!("Starting profile reconciler");
+                    reconciler.start().await;
+                }
+                .instrument(span),
+            );
+        }
+
+        if !self.server_enabled {
+            tracing::info!("Starting reconciler without server");
+            // Without the server, which stops on SIGINT and SIGTERM, Tainter has to handle the
+            // signals itself.
+            tokio::select! {
+                _ = self.reconciler.start() => {}
+                result = shutdown_signal() => {
+                    result?;
+                    tracing::info!("Received shutdown signal, stopping Tainter");
+                }
+            }
+            return Ok(());
+        }
+
         tokio::spawn(async move {
             tracing::info!("Starting reconciler");
             self.reconciler.start().await;
         });
 
-        tracing::info!("Starting server");
-        HttpServer::new(|| App::new().service(health))
-            .bind((self.host.as_str(), self.port))?
-            .run()
-            .await
+        tracing::info!(admin_endpoints, "Starting server");
+        HttpServer::new(move || {
+            App::new()
+                .app_data(metrics_data.clone())
+                .app_data(reconciler_data.clone())
+                .app_data(profiles_data.clone())
+                .service(health)
+                .service(version)
+                .service(gather_metrics)
+                .service(plan)
+                .service(config)
+                .service(report_status)
+                .service(profile_status)
+                .service(plan_profile)
+                .service(explain_profile)
+                .service(explain)
+                .configure(|service_config| {
+                    if admin_endpoints {
+                        service_config
+                            .app_data(authorizer_data.clone())
+                            .service(pause)
+                            .service(resume)
+                            .service(pause_profile)
+                            .service(resume_profile)
+                            .service(add_taint)
+                            .service(remove_taint);
+                    }
+                })
+        })
+        .workers(self.workers)
+        .bind((self.host.as_str(), self.port))?
+        .run()
+        .await
+    }
+}
+
+// Completes once the process receives SIGINT or, on Unix, SIGTERM, which Kubernetes sends to stop a
+// pod.
+async fn shutdown_signal() -> std::io::Result<()> {
+    use actix_web::rt::signal;
+
+    #[cfg(unix)]
+    {
+        let mut terminate = signal::unix::signal(signal::unix::SignalKind::terminate())?;
+        tokio::select! {
+            result = signal::ctrl_c() => result,
+            _ = terminate.recv() => Ok(()),
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        signal::ctrl_c().await
     }
 }
 
 #[cfg(test)]
 mod tests {
     use actix_web::{test, App};
+    use http::{Request, Response};
+    use k8s_openapi::serde_json;
+    use kube::client::Body;
+    use std::fs;
+    use test_case::test_case;
+    use tower_test::mock::Handle;
 
     use super::*;
 
+    #[test_case(false, "OutOfMemoryKiller", true ; "matches substring by default")]
+    #[test_case(true, "OutOfMemoryKiller", false ; "does not match substring with full_match")]
+    #[test_case(true, "OutOfMemory", true ; "matches entire string with full_match")]
+    #[test_case(true, "Node", true ; "matches alternation with full_match")]
+    #[actix_web::test]
+    async fn test_condition_full_match(full_match: bool, value: &str, expected: bool) {
+        let condition = Tainter::condition(&settings::Condition {
+            type_: "OutOfMemory|Node".to_string(),
+            types: vec![],
+            status: Some("OutOfMemory|Node".to_string()),
+            status_not: None,
+            status_in: None,
+            polarity: None,
+            reason: None,
+            message: None,
+            max_age: None,
+            max_heartbeat_age: None,
+            match_stale_heartbeat: false,
+            full_match,
+            exact_literals: false,
+            normalize: settings::Normalize::Off,
+        });
+
+        assert_eq!(expected, condition.type_.is_match(value));
+        let Status::Pattern(status) = condition.status else {
+            panic!("status should be a pattern");
+        };
+        assert_eq!(expected, status.is_match(value));
+    }
+
+    #[test_case(false, "Ready", "NotReady", true ; "matches substring of literal by default")]
+    #[test_case(true, "Ready", "NotReady", false ; "does not match substring of literal with exact_literals")]
+    #[test_case(true, "Ready", "Ready", true ; "matches literal exactly with exact_literals")]
+    #[test_case(true, "Ready|Degraded", "NotReady", true ; "matches substring of pattern with metacharacters with exact_literals")]
+    #[test_case(true, "Ready|Degraded", "Degraded", true ; "matches alternation with exact_literals")]
+    #[actix_web::test]
+    async fn test_condition_exact_literals(
+        exact_literals: bool,
+        pattern: &str,
+        value: &str,
+        expected: bool,
+    ) {
+        let condition = Tainter::condition(&settings::Condition {
+            type_: pattern.to_string(),
+            types: vec![],
+            status: Some(pattern.to_string()),
+            status_not: None,
+            status_in: None,
+            polarity: None,
+            reason: None,
+            message: None,
+            max_age: None,
+            max_heartbeat_age: None,
+            match_stale_heartbeat: false,
+            full_match: false,
+            exact_literals,
+            normalize: settings::Normalize::Off,
+        });
+
+        let literal = exact_literals && !pattern.contains('|');
+        assert_eq!(literal, matches!(condition.type_, Pattern::Literal(_)));
+        assert_eq!(expected, condition.type_.is_match(value));
+        let Status::Pattern(status) = condition.status else {
+            panic!("status should be a pattern");
+        };
+        assert_eq!(literal, matches!(status, Pattern::Literal(_)));
+        assert_eq!(expected, status.is_match(value));
+    }
+
+    #[test_case(Some("aks-zeus1-41950716-vmss000082"), Some("aks-zeus1-41950716-vmss000082") ; "set")]
+    #[test_case(Some(""), None ; "empty")]
+    #[test_case(None, None ; "unset")]
+    fn test_self_node_name(node_name: Option<&str>, expected: Option<&str>) {
+        assert_eq!(
+            expected.map(str::to_string),
+            Tainter::self_node_name(node_name.map(str::to_string))
+        );
+    }
+
+    #[test_case(false, false, "MemoryPressure", true ; "matches first type")]
+    #[test_case(false, false, "DiskPressure", true ; "matches second type")]
+    #[test_case(false, false, "PIDPressure", false ; "does not match other type")]
+    #[test_case(false, false, "NoDiskPressure", true ; "matches substring of type")]
+    #[test_case(true, false, "NoDiskPressure", false ; "does not match substring of type with full_match")]
+    #[test_case(false, true, "NoDiskPressure", false ; "does not match substring of literal type with exact_literals")]
+    #[test_case(false, true, "OutOfMemory", true ; "matches type with metacharacters with exact_literals")]
+    #[actix_web::test]
+    async fn test_condition_types(
+        full_match: bool,
+        exact_literals: bool,
+        value: &str,
+        expected: bool,
+    ) {
+        let condition = Tainter::condition(&settings::Condition {
+            type_: "".to_string(),
+            types: vec![
+                "MemoryPressure".to_string(),
+                "DiskPressure".to_string(),
+                "OutOf(Memory|Disk)".to_string(),
+            ],
+            status: Some("True".to_string()),
+            status_not: None,
+            status_in: None,
+            polarity: None,
+            reason: None,
+            message: None,
+            max_age: None,
+            max_heartbeat_age: None,
+            match_stale_heartbeat: false,
+            full_match,
+            exact_literals,
+            normalize: settings::Normalize::Off,
+        });
+
+        assert_eq!(expected, condition.type_.is_match(value));
+    }
+
+    #[test_case("Ready", settings::Polarity::Negative, vec!["False", "Unknown"] ; "ready is negative")]
+    #[test_case("MemoryPressure", settings::Polarity::Positive, vec!["True"] ; "memory pressure is positive")]
+    #[actix_web::test]
+    async fn test_condition_polarity(
+        type_: &str,
+        polarity: settings::Polarity,
+        expected: Vec<&str>,
+    ) {
+        let condition = Tainter::condition(&settings::Condition {
+            type_: type_.to_string(),
+            types: vec![],
+            status: None,
+            status_not: None,
+            status_in: None,
+            polarity: Some(polarity),
+            reason: None,
+            message: None,
+            max_age: None,
+            max_heartbeat_age: None,
+            match_stale_heartbeat: false,
+            full_match: false,
+            exact_literals: false,
+            normalize: settings::Normalize::Off,
+        });
+
+        let Status::In(statuses) = condition.status else {
+            panic!("status should be a set of statuses");
+        };
+        assert_eq!(expected, statuses);
+    }
+
+    #[test_case(true, "False", true ; "matches other status")]
+    #[test_case(true, "True", false ; "does not match status")]
+    #[test_case(true, "TrueEnough", true ; "matches status containing pattern with full_match")]
+    #[test_case(false, "TrueEnough", false ; "does not match status containing pattern")]
+    #[actix_web::test]
+    async fn test_condition_status_not(full_match: bool, value: &str, expected: bool) {
+        let condition = Tainter::condition(&settings::Condition {
+            type_: "Ready".to_string(),
+            types: vec![],
+            status: None,
+            status_not: Some("True".to_string()),
+            status_in: None,
+            polarity: None,
+            reason: None,
+            message: None,
+            max_age: None,
+            max_heartbeat_age: None,
+            match_stale_heartbeat: false,
+            full_match,
+            exact_literals: false,
+            normalize: settings::Normalize::Off,
+        });
+
+        let Status::NotPattern(status_not) = condition.status else {
+            panic!("status should be an inverted pattern");
+        };
+        assert_eq!(expected, !status_not.is_match(value));
+    }
+
     #[actix_web::test]
     async fn test_health_endpoint() {
         let app = test::init_service(App::new().service(health)).await;
 
         let req = test::TestRequest::default().uri("/health").to_request();
 
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+        assert_eq!(
+            resp.headers().get("content-type").unwrap(),
+            "application/json"
+        );
+        let body = test::read_body(resp).await;
+        assert_eq!(body, actix_web::web::Bytes::from(r#"{"status":"healthy"}"#));
+    }
+
+    #[actix_web::test]
+    async fn test_version_endpoint() {
+        let app = test::init_service(App::new().service(version)).await;
+
+        let req = test::TestRequest::default().uri("/version").to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+        assert_eq!(
+            resp.headers().get("content-type").unwrap(),
+            "application/json"
+        );
+        let body = test::read_body(resp).await;
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert!(body.contains(&format!(r#""version":"{}""#, env!("CARGO_PKG_VERSION"))));
+        assert!(body.contains(r#""git_sha":"#));
+        assert!(body.contains(r#""build_timestamp":"#));
+    }
+
+    #[actix_web::test]
+    async fn test_plan_endpoint() {
+        let (mock_service, mut handle) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+        let client = Client::new(mock_service, "default");
+        let matchers = vec![Configuration {
+            taints: vec![Taint {
+                effect: "NoSchedule".to_string(),
+                key: "not-ready".to_string(),
+                time_added: None,
+                value: None,
+            }],
+            conditions: vec![Tainter::condition(&settings::Condition {
+                type_: "Ready".to_string(),
+                types: vec![],
+                status: Some("False".to_string()),
+                status_not: None,
+                status_in: None,
+                polarity: None,
+                reason: None,
+                message: None,
+                max_age: None,
+                max_heartbeat_age: None,
+                match_stale_heartbeat: false,
+                full_match: false,
+                exact_literals: false,
+                normalize: settings::Normalize::Off,
+            })],
+            ..Default::default()
+        }];
+        let reconciler = Reconciler::new(
+            client,
+            matchers,
+            Options::default(),
+            Metrics::new(),
+            Box::new(UtcClock),
+        );
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(reconciler))
+                .service(plan),
+        )
+        .await;
+
+        tokio::spawn(async move {
+            let (request, response) = handle.next_request().await.expect("list nodes not called");
+            assert_eq!(request.method(), http::Method::GET);
+            assert_eq!(request.uri().path(), "/api/v1/nodes");
+            let body =
+                fs::read_to_string("src/reconciler/testfiles/list-nodes-single-eligible.json")
+                    .unwrap();
+            response.send_response(
+                Response::builder()
+                    .body(Body::from(body.into_bytes()))
+                    .unwrap(),
+            );
+        });
+
+        let req = test::TestRequest::default().uri("/plan").to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+        let body = test::read_body(resp).await;
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert_eq!(
+            r#"[{"node":"aks-zeus1-41950716-vmss000082","add":[{"effect":"NoSchedule","key":"not-ready"}],"remove":[]}]"#,
+            body
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_plan_endpoint_returns_json_error_if_list_nodes_fails() {
+        let (mock_service, mut handle) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+        let client = Client::new(mock_service, "default");
+        let reconciler = Reconciler::new(
+            client,
+            vec![],
+            Options::default(),
+            Metrics::new(),
+            Box::new(UtcClock),
+        );
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(reconciler))
+                .service(plan),
+        )
+        .await;
+
+        tokio::spawn(async move {
+            let (_, response) = handle.next_request().await.expect("list nodes not called");
+            response.send_error("API server is not ready");
+        });
+
+        let req = test::TestRequest::default().uri("/plan").to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(
+            resp.status(),
+            actix_web::http::StatusCode::INTERNAL_SERVER_ERROR
+        );
+        assert_eq!(
+            resp.headers().get("content-type").unwrap(),
+            "application/json"
+        );
+        let body = test::read_body(resp).await;
+        assert_eq!(
+            body,
+            actix_web::web::Bytes::from(
+                r#"{"status":"error","error":{"code":"list_nodes_failed","message":"error listing nodes"}}"#
+            )
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_pause_and_resume_endpoints() {
+        let (mock_service, mut handle) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+        let client = Client::new(mock_service, "default");
+        let reconciler = Reconciler::new(
+            client.clone(),
+            vec![],
+            Options::default(),
+            Metrics::new(),
+            Box::new(UtcClock),
+        );
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(reconciler))
+                .app_data(web::Data::new(Profiles(BTreeMap::new())))
+                .app_data(web::Data::new(Authorizer::new(client)))
+                .service(pause)
+                .service(resume)
+                .service(report_status),
+        )
+        .await;
+
+        // Only pausing and resuming are authorized.
+        tokio::spawn(async move {
+            for _ in 0..2 {
+                review(&mut handle, true).await;
+            }
+        });
+
+        let requests = [
+            (test::TestRequest::get().uri("/status"), "running"),
+            (test::TestRequest::post().uri("/pause"), "paused"),
+            (test::TestRequest::get().uri("/status"), "paused"),
+            (test::TestRequest::post().uri("/resume"), "running"),
+            (test::TestRequest::get().uri("/status"), "running"),
+        ];
+        for (req, expected) in requests {
+            let req = req.insert_header(("authorization", "Bearer token"));
+            let resp = test::call_service(&app, req.to_request()).await;
+            assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+            let body: serde_json::Value = test::read_body_json(resp).await;
+            assert_eq!(expected, body["status"]);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_new_returns_error_if_decision_log_file_cannot_be_opened() {
+        let (mock_service, _) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+        let client = Client::new(mock_service, "default");
+        let mut settings = Settings::new("src/settings/testfiles/decision_log.toml").unwrap();
+        settings.decision_log.as_mut().unwrap().path =
+            Some("src/settings/testfiles/missing/decisions.ndjson".to_string());
+
+        let res = Tainter::new(settings, client);
+
+        assert!(res.err().unwrap().to_string().starts_with(
+            "error opening decision log file src/settings/testfiles/missing/decisions.ndjson"
+        ));
+    }
+
+    #[actix_web::test]
+    async fn test_status_endpoint() {
+        let (mock_service, _) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+        let client = Client::new(mock_service, "default");
+        let settings = Settings::new("src/settings/testfiles/multiple_taints.toml").unwrap();
+        let tainter = Tainter::new(settings, client).unwrap();
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::from(tainter.reconciler))
+                .app_data(web::Data::from(tainter.profiles))
+                .service(report_status),
+        )
+        .await;
+
+        let resp =
+            test::call_service(&app, test::TestRequest::get().uri("/status").to_request()).await;
+
+        assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        // Nodes have not been listed yet.
+        assert_eq!(false, body["ready"]);
+        assert_eq!(false, body["paused"]);
+        assert_eq!("running", body["status"]);
+        assert_eq!(1, body["matcher_count"]);
+        assert_eq!(0, body["tainted_nodes"]);
+        assert_eq!(0, body["watch_errors"]);
+        assert!(body["last_reconcile"].is_null());
+        assert!(body["uptime_seconds"].as_i64().unwrap() >= 0);
+        // Without profiles, the status only reports the top-level reconciler.
+        assert!(body.get("profiles").is_none());
+    }
+
+    #[actix_web::test]
+    async fn test_status_endpoint_reports_profiles() {
+        let (mock_service, _) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+        let client = Client::new(mock_service, "default");
+        let settings = Settings::new("src/settings/testfiles/profiles.toml").unwrap();
+        let tainter = Tainter::new(settings, client).unwrap();
+        tainter.profiles.0["network"].pause();
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::from(tainter.reconciler))
+                .app_data(web::Data::from(tainter.profiles))
+                .service(report_status),
+        )
+        .await;
+
+        let resp =
+            test::call_service(&app, test::TestRequest::get().uri("/status").to_request()).await;
+
+        assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!("running", body["status"]);
+        assert_eq!("running", body["profiles"]["hardware"]["status"]);
+        assert_eq!("paused", body["profiles"]["network"]["status"]);
+        assert_eq!(1, body["profiles"]["network"]["matcher_count"]);
+    }
+
+    fn node_response() -> Response<Body> {
+        let body = fs::read_to_string("src/reconciler/testfiles/node-put-success.json").unwrap();
+        Response::builder()
+            .body(Body::from(body.into_bytes()))
+            .unwrap()
+    }
+
+    async fn taints_from_body(request: Request<Body>) -> Vec<Taint> {
+        let bytes = request.into_body().collect_bytes().await.unwrap();
+        let node: k8s_openapi::api::core::v1::Node = serde_json::from_slice(&bytes).unwrap();
+        node.spec.unwrap().taints.unwrap()
+    }
+
+    fn ad_hoc_reconciler(client: Client) -> Reconciler {
+        Reconciler::new(
+            client,
+            vec![],
+            Options::default(),
+            Metrics::new(),
+            Box::new(UtcClock),
+        )
+    }
+
+    fn json_response(body: serde_json::Value) -> Response<Body> {
+        Response::builder()
+            .body(Body::from(serde_json::to_vec(&body).unwrap()))
+            .unwrap()
+    }
+
+    // Responds to the token review and the subject access review of an admin request.
+    async fn review(handle: &mut Handle<Request<Body>, Response<Body>>, allowed: bool) {
+        let (request, response) = handle.next_request().await.expect("token not reviewed");
+        assert_eq!(
+            request.uri().path(),
+            "/apis/authentication.k8s.io/v1/tokenreviews"
+        );
+        response.send_response(json_response(serde_json::json!({
+            "apiVersion": "authentication.k8s.io/v1",
+            "kind": "TokenReview",
+            "spec": {},
+            "status": {"authenticated": true, "user": {"username": "jane"}}
+        })));
+
+        let (request, response) = handle.next_request().await.expect("access not reviewed");
+        assert_eq!(
+            request.uri().path(),
+            "/apis/authorization.k8s.io/v1/subjectaccessreviews"
+        );
+        response.send_response(json_response(serde_json::json!({
+            "apiVersion": "authorization.k8s.io/v1",
+            "kind": "SubjectAccessReview",
+            "spec": {},
+            "status": {"allowed": allowed}
+        })));
+    }
+
+    #[actix_web::test]
+    async fn test_add_taint_endpoint() {
+        let (mock_service, mut handle) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+        let client = Client::new(mock_service, "default");
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(ad_hoc_reconciler(client.clone())))
+                .app_data(web::Data::new(Authorizer::new(client)))
+                .service(add_taint),
+        )
+        .await;
+
+        let spawned = tokio::spawn(async move {
+            review(&mut handle, true).await;
+            let (request, response) = handle.next_request().await.expect("get node not called");
+            assert_eq!(request.method(), http::Method::GET);
+            assert_eq!(
+                request.uri().path(),
+                "/api/v1/nodes/aks-zeus1-41950716-vmss000082"
+            );
+            response.send_response(node_response());
+
+            let (request, response) = handle.next_request().await.expect("put node not called");
+            assert_eq!(request.method(), http::Method::PUT);
+            assert_eq!(
+                request.uri().path(),
+                "/api/v1/nodes/aks-zeus1-41950716-vmss000082"
+            );
+            assert_eq!(request.uri().query(), Some("&fieldManager=tainter"));
+            let taints = taints_from_body(request).await;
+            assert_eq!(3, taints.len());
+            assert_eq!(
+                Taint {
+                    effect: "NoSchedule".to_string(),
+                    key: "maintenance".to_string(),
+                    time_added: None,
+                    value: Some("true".to_string()),
+                },
+                taints[2]
+            );
+            response.send_response(node_response());
+        });
+
+        let req = test::TestRequest::post()
+            .uri("/nodes/aks-zeus1-41950716-vmss000082/taints")
+            .insert_header(("authorization", "Bearer token"))
+            .set_json(
+                serde_json::json!({"key": "maintenance", "value": "true", "effect": "NoSchedule"}),
+            )
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!("added", body["status"]);
+        spawned.await.unwrap();
+    }
+
+    #[test_case(r#"{"key": "maintenance", "effect": "NoEvict"}"#, "invalid taint effect NoEvict" ; "invalid effect")]
+    #[test_case(r#"{"key": "-maintenance", "effect": "NoSchedule"}"#, "invalid taint key -maintenance" ; "invalid key")]
+    #[actix_web::test]
+    async fn test_add_taint_endpoint_rejects_invalid_taint(body: &str, message: &str) {
+        let (mock_service, mut handle) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+        let client = Client::new(mock_service, "default");
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(ad_hoc_reconciler(client.clone())))
+                .app_data(web::Data::new(Authorizer::new(client)))
+                .service(add_taint),
+        )
+        .await;
+
+        tokio::spawn(async move {
+            review(&mut handle, true).await;
+        });
+        let req = test::TestRequest::post()
+            .uri("/nodes/aks-zeus1-41950716-vmss000082/taints")
+            .insert_header(("authorization", "Bearer token"))
+            .insert_header(("content-type", "application/json"))
+            .set_payload(body.to_string())
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), actix_web::http::StatusCode::BAD_REQUEST);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!("invalid_taint", body["error"]["code"]);
+        assert_eq!(message, body["error"]["message"]);
+    }
+
+    #[actix_web::test]
+    async fn test_remove_taint_endpoint() {
+        let (mock_service, mut handle) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+        let client = Client::new(mock_service, "default");
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(ad_hoc_reconciler(client.clone())))
+                .app_data(web::Data::new(Authorizer::new(client)))
+                .service(remove_taint),
+        )
+        .await;
+
+        let spawned = tokio::spawn(async move {
+            review(&mut handle, true).await;
+            let (request, response) = handle.next_request().await.expect("get node not called");
+            assert_eq!(request.method(), http::Method::GET);
+            response.send_response(node_response());
+
+            let (request, response) = handle.next_request().await.expect("put node not called");
+            assert_eq!(request.method(), http::Method::PUT);
+            let taints = taints_from_body(request).await;
+            assert_eq!(
+                vec!["kubernetes.azure.com/scalesetpriority"],
+                taints
+                    .iter()
+                    .map(|taint| taint.key.as_str())
+                    .collect::<Vec<_>>()
+            );
+            response.send_response(node_response());
+        });
+
+        let req = test::TestRequest::delete()
+            .uri("/nodes/aks-zeus1-41950716-vmss000082/taints/node.kubernetes.io%2Fout-of-service")
+            .insert_header(("authorization", "Bearer token"))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!("removed", body["status"]);
+        spawned.await.unwrap();
+    }
+
+    #[actix_web::test]
+    async fn test_remove_taint_endpoint_returns_not_found_for_unknown_node() {
+        let (mock_service, mut handle) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+        let client = Client::new(mock_service, "default");
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(ad_hoc_reconciler(client.clone())))
+                .app_data(web::Data::new(Authorizer::new(client)))
+                .service(remove_taint),
+        )
+        .await;
+
+        tokio::spawn(async move {
+            review(&mut handle, true).await;
+            let (_, response) = handle.next_request().await.expect("get node not called");
+            let body = r#"{"kind":"Status","apiVersion":"v1","status":"Failure","message":"nodes \"unknown\" not found","reason":"NotFound","code":404}"#;
+            response.send_response(
+                Response::builder()
+                    .status(404)
+                    .body(Body::from(body.as_bytes().to_vec()))
+                    .unwrap(),
+            );
+        });
+
+        let req = test::TestRequest::delete()
+            .uri("/nodes/unknown/taints/maintenance")
+            .insert_header(("authorization", "Bearer token"))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), actix_web::http::StatusCode::NOT_FOUND);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!("node_not_found", body["error"]["code"]);
+    }
+
+    #[actix_web::test]
+    async fn test_add_taint_endpoint_keeps_taint_that_tainter_does_not_manage() {
+        let (mock_service, mut handle) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+        let client = Client::new(mock_service, "default");
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(ad_hoc_reconciler(client.clone())))
+                .app_data(web::Data::new(Authorizer::new(client)))
+                .service(add_taint),
+        )
+        .await;
+
+        tokio::spawn(async move {
+            review(&mut handle, true).await;
+            let (_, response) = handle.next_request().await.expect("get node not called");
+            response.send_response(node_response());
+        });
+
+        // The node has the taint with another value, which the cluster autoscaler owns.
+        let req = test::TestRequest::post()
+            .uri("/nodes/aks-zeus1-41950716-vmss000082/taints")
+            .insert_header(("authorization", "Bearer token"))
+            .set_json(serde_json::json!({
+                "key": "kubernetes.azure.com/scalesetpriority",
+                "value": "regular",
+                "effect": "NoSchedule"
+            }))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), actix_web::http::StatusCode::CONFLICT);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!("taint_not_managed", body["error"]["code"]);
+    }
+
+    #[test_case(None, actix_web::http::StatusCode::UNAUTHORIZED, "unauthenticated" ; "without token")]
+    #[test_case(Some("Bearer token"), actix_web::http::StatusCode::FORBIDDEN, "forbidden" ; "without permission")]
+    #[actix_web::test]
+    async fn test_admin_endpoints_reject_unauthorized_callers(
+        authorization: Option<&str>,
+        expected_status: actix_web::http::StatusCode,
+        expected_code: &str,
+    ) {
+        let (mock_service, mut handle) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+        let client = Client::new(mock_service, "default");
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(ad_hoc_reconciler(client.clone())))
+                .app_data(web::Data::new(Authorizer::new(client)))
+                .service(add_taint),
+        )
+        .await;
+
+        let authenticated = authorization.is_some();
+        let spawned = tokio::spawn(async move {
+            if authenticated {
+                review(&mut handle, false).await;
+            }
+            // The node is never fetched, let alone updated.
+            assert!(handle.next_request().await.is_none());
+        });
+
+        let mut req = test::TestRequest::post()
+            .uri("/nodes/aks-zeus1-41950716-vmss000082/taints")
+            .set_json(serde_json::json!({"key": "maintenance", "effect": "NoExecute"}));
+        if let Some(authorization) = authorization {
+            req = req.insert_header(("authorization", authorization));
+        }
+
+        let resp = test::call_service(&app, req.to_request()).await;
+        assert_eq!(resp.status(), expected_status);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(expected_code, body["error"]["code"]);
+        drop(app);
+        spawned.await.unwrap();
+    }
+
+    #[actix_web::test]
+    async fn test_profiles_plan_their_own_taints_independently() {
+        let (mock_service, mut handle) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+        let client = Client::new(mock_service, "default");
+        let settings = Settings::new("src/settings/testfiles/profiles.toml").unwrap();
+        let tainter = Tainter::new(settings, client).unwrap();
+
+        // Disabled profiles are never started.
+        assert_eq!(
+            vec!["hardware", "network"],
+            tainter.profiles.0.keys().collect::<Vec<_>>()
+        );
+
+        tokio::spawn(async move {
+            for selector in ["agentpool%3Dzeus1", "agentpool%3Dzeus2"] {
+                let (request, response) =
+                    handle.next_request().await.expect("list nodes not called");
+                assert_eq!(request.uri().path(), "/api/v1/nodes");
+                assert!(request
+                    .uri()
+                    .query()
+                    .unwrap()
+                    .contains(&format!("labelSelector={selector}")));
+                let body =
+                    fs::read_to_string("src/reconciler/testfiles/list-nodes-single-eligible.json")
+                        .unwrap();
+                response.send_response(
+                    Response::builder()
+                        .body(Body::from(body.into_bytes()))
+                        .unwrap(),
+                );
+            }
+        });
+
+        for (profile, key) in [("hardware", "hardware"), ("network", "network")] {
+            let plans = tainter.profiles.0[profile].plan().await.unwrap();
+            assert_eq!(1, plans.len());
+            let keys: Vec<&str> = plans[0]
+                .add
+                .iter()
+                .map(|taint| taint.key.as_str())
+                .collect();
+            assert_eq!(vec![key], keys);
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_profile_plan_endpoint() {
+        let (mock_service, mut handle) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+        let client = Client::new(mock_service, "default");
+        let settings = Settings::new("src/settings/testfiles/profiles.toml").unwrap();
+        let tainter = Tainter::new(settings, client).unwrap();
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::from(tainter.profiles))
+                .service(plan_profile)
+                .service(explain_profile),
+        )
+        .await;
+
+        tokio::spawn(async move {
+            let (request, response) = handle.next_request().await.expect("list nodes not called");
+            assert!(request
+                .uri()
+                .query()
+                .unwrap()
+                .contains("labelSelector=agentpool%3Dzeus2"));
+            let body =
+                fs::read_to_string("src/reconciler/testfiles/list-nodes-single-eligible.json")
+                    .unwrap();
+            response.send_response(
+                Response::builder()
+                    .body(Body::from(body.into_bytes()))
+                    .unwrap(),
+            );
+        });
+
+        let resp = test::call_service(
+            &app,
+            test::TestRequest::get()
+                .uri("/profiles/network/plan")
+                .to_request(),
+        )
+        .await;
+        assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!("network", body[0]["add"][0]["key"]);
+
+        let resp = test::call_service(
+            &app,
+            test::TestRequest::get()
+                .uri("/profiles/storage/explain/aks-zeus1-41950716-vmss000082")
+                .to_request(),
+        )
+        .await;
+        assert_eq!(resp.status(), actix_web::http::StatusCode::NOT_FOUND);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!("profile_not_found", body["error"]["code"]);
+    }
+
+    #[actix_web::test]
+    async fn test_profile_pause_and_resume_endpoints() {
+        let (mock_service, mut handle) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+        let client = Client::new(mock_service, "default");
+        let tainter = Tainter::builder(client)
+            .profile("hardware", vec![], Options::default())
+            .profile("network", vec![], Options::default())
+            .build();
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::from(tainter.profiles.clone()))
+                .app_data(web::Data::from(tainter.authorizer.clone()))
+                .service(pause_profile)
+                .service(resume_profile)
+                .service(profile_status),
+        )
+        .await;
+
+        tokio::spawn(async move {
+            for _ in 0..3 {
+                review(&mut handle, true).await;
+            }
+        });
+
+        let requests = [
+            (
+                test::TestRequest::post().uri("/profiles/hardware/pause"),
+                actix_web::http::StatusCode::OK,
+                r#"{"status":"paused"}"#,
+            ),
+            (
+                test::TestRequest::get().uri("/profiles/hardware/status"),
+                actix_web::http::StatusCode::OK,
+                r#"{"status":"paused"}"#,
+            ),
+            (
+                test::TestRequest::get().uri("/profiles/network/status"),
+                actix_web::http::StatusCode::OK,
+                r#"{"status":"running"}"#,
+            ),
+            (
+                test::TestRequest::post().uri("/profiles/hardware/resume"),
+                actix_web::http::StatusCode::OK,
+                r#"{"status":"running"}"#,
+            ),
+            (
+                test::TestRequest::post().uri("/profiles/storage/pause"),
+                actix_web::http::StatusCode::NOT_FOUND,
+                r#"{"status":"error","error":{"code":"profile_not_found","message":"no profile named storage"}}"#,
+            ),
+        ];
+        for (req, expected_status, expected_body) in requests {
+            let req = req.insert_header(("authorization", "Bearer token"));
+            let resp = test::call_service(&app, req.to_request()).await;
+            assert_eq!(resp.status(), expected_status);
+            let body = test::read_body(resp).await;
+            assert_eq!(body, actix_web::web::Bytes::from(expected_body));
+        }
+        assert!(!tainter.reconciler.is_paused());
+    }
+
+    #[test_case("/pause" ; "pause")]
+    #[test_case("/resume" ; "resume")]
+    #[test_case("/profiles/hardware/pause" ; "pause profile")]
+    #[test_case("/profiles/hardware/resume" ; "resume profile")]
+    #[actix_web::test]
+    async fn test_pause_endpoints_reject_unauthenticated_callers(uri: &str) {
+        let (mock_service, _) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+        let client = Client::new(mock_service, "default");
+        let tainter = Tainter::builder(client)
+            .profile("hardware", vec![], Options::default())
+            .build();
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::from(tainter.reconciler.clone()))
+                .app_data(web::Data::from(tainter.profiles.clone()))
+                .app_data(web::Data::from(tainter.authorizer.clone()))
+                .service(pause)
+                .service(resume)
+                .service(pause_profile)
+                .service(resume_profile),
+        )
+        .await;
+
+        let resp = test::call_service(&app, test::TestRequest::post().uri(uri).to_request()).await;
+        assert_eq!(resp.status(), actix_web::http::StatusCode::UNAUTHORIZED);
+        assert!(!tainter.reconciler.is_paused());
+        assert!(!tainter.profiles.0["hardware"].is_paused());
+    }
+
+    #[actix_web::test]
+    async fn test_config_endpoint() {
+        let (mock_service, _) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+        let client = Client::new(mock_service, "default");
+        let settings = Settings::new("src/settings/testfiles/multiple_taints.toml").unwrap();
+        let reconciler = Reconciler::new(
+            client,
+            Tainter::matchers(&settings.reconciler.matchers),
+            Options::default(),
+            Metrics::new(),
+            Box::new(UtcClock),
+        );
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(reconciler))
+                .service(config),
+        )
+        .await;
+
+        let req = test::TestRequest::default()
+            .uri("/healthz/config")
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+        let body = test::read_body(resp).await;
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert_eq!(
+            r#"{"valid":true,"matchers":1,"conditions":1,"errors":[],"warnings":[]}"#,
+            body
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_pressure_signals_taint_node_under_pressure() {
+        let (mock_service, mut handle) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+        let client = Client::new(mock_service, "default");
+        let settings = Settings::new("src/settings/testfiles/pressure_signals.toml").unwrap();
+        let reconciler = Reconciler::new(
+            client,
+            Tainter::matchers(&settings.reconciler.matchers),
+            Options::default(),
+            Metrics::new(),
+            Box::new(UtcClock),
+        );
+
+        tokio::spawn(async move {
+            let (_, response) = handle.next_request().await.expect("list nodes not called");
+            let body =
+                fs::read_to_string("src/reconciler/testfiles/list-nodes-memory-pressure.json")
+                    .unwrap();
+            response.send_response(
+                Response::builder()
+                    .body(Body::from(body.into_bytes()))
+                    .unwrap(),
+            );
+        });
+
+        let plans = reconciler.plan().await.unwrap();
+
+        assert_eq!(1, plans.len());
+        let node_plan = plans.first().unwrap();
+        assert_eq!("aks-demeter1-41950716-vmss000082", node_plan.node);
+        assert_eq!(
+            vec![Taint {
+                effect: "NoSchedule".to_string(),
+                key: "pressure".to_string(),
+                time_added: None,
+                value: Some("kubelet".to_string()),
+            }],
+            node_plan.add
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_metrics_endpoint() {
+        let metrics_data = web::Data::new(Metrics::new());
+        metrics_data.watch_restarts.inc();
+        let app =
+            test::init_service(App::new().app_data(metrics_data).service(gather_metrics)).await;
+
+        let req = test::TestRequest::default().uri("/metrics").to_request();
+
         let resp = test::call_service(&app, req).await;
         assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
         let body = test::read_body(resp).await;
-        assert_eq!(body, actix_web::web::Bytes::from("healthy"));
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert!(body.contains("tainter_watch_restarts_total 1"));
     }
 }
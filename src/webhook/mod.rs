@@ -0,0 +1,217 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use k8s_openapi::api::core::v1::Taint;
+use serde::Serialize;
+
+// Notifies an external webhook, e.g. of incident tooling, of the taints that Tainter adds to and
+// removes from nodes. Delivery is best effort: events are sent in the background so that they
+// never block reconciliation, and are dropped after the retries are exhausted.
+#[derive(Clone)]
+pub struct Webhook {
+    client: reqwest::Client,
+    url: String,
+    max_retries: u32,
+    retry_delay: Duration,
+}
+
+#[derive(Serialize, Debug, PartialEq, Clone, Copy)]
+pub enum Action {
+    Added,
+    Removed,
+}
+
+// The JSON payload that is posted to the webhook for each added or removed taint.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Event {
+    pub node: String,
+    pub action: Action,
+    pub taint: Taint,
+    // Why the taint was added or removed, e.g. the node conditions that triggered it.
+    pub reason: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl Webhook {
+    pub fn new(url: String, timeout: Duration, max_retries: u32, retry_delay: Duration) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .expect("webhook client should be created");
+
+        Webhook {
+            client,
+            url,
+            max_retries,
+            retry_delay,
+        }
+    }
+
+    // Delivers the events in order in a background task.
+    pub fn send(&self, events: Vec<Event>) {
+        if events.is_empty() {
+            return;
+        }
+
+        let webhook = self.clone();
+        tokio::spawn(async move {
+            for event in &events {
+                webhook.deliver(event).await;
+            }
+        });
+    }
+
+    async fn deliver(&self, event: &Event) {
+        for attempt in 0..=self.max_retries {
+            if attempt > 0 {
+                tokio::time::sleep(self.retry_delay).await;
+            }
+
+            let result = self
+                .client
+                .post(&self.url)
+                .json(event)
+                .send()
+                .await
+                .and_then(|response| response.error_for_status());
+            match result {
+                Ok(_) => {
+                    tracing::debug!(
+                        node = event.node,
+                        taint = event.taint.key,
+                        "Delivered event to webhook"
+                    );
+                    return;
+                }
+                Err(error) => tracing::warn!(
+                    error = error.to_string(),
+                    node = event.node,
+                    taint = event.taint.key,
+                    attempt = attempt + 1,
+                    "Error delivering event to webhook"
+                ),
+            }
+        }
+
+        tracing::error!(
+            node = event.node,
+            taint = event.taint.key,
+            "Dropping event after exhausting retries of delivering it to webhook"
+        );
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use k8s_openapi::serde_json;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+    use tokio::sync::mpsc;
+    use tracing_test::traced_test;
+
+    // Starts an HTTP server that responds to the requests in order with the given status codes,
+    // and then with 200, and forwards the JSON body of each request to the returned receiver.
+    pub(crate) async fn server(
+        statuses: Vec<u16>,
+    ) -> (String, mpsc::UnboundedReceiver<serde_json::Value>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/events", listener.local_addr().unwrap());
+        let (sender, receiver) = mpsc::unbounded_channel();
+
+        tokio::spawn(async move {
+            let mut statuses = statuses.into_iter();
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut request = Vec::new();
+                let mut buffer = [0; 1024];
+                // Reads until the headers and the body that their content-length announces.
+                let body = loop {
+                    let read = stream.read(&mut buffer).await.unwrap();
+                    request.extend_from_slice(&buffer[..read]);
+                    let text = String::from_utf8_lossy(&request).to_string();
+                    let Some((headers, body)) = text.split_once("\r\n\r\n") else {
+                        continue;
+                    };
+                    let length: usize = headers
+                        .lines()
+                        .find_map(|line| {
+                            line.to_lowercase()
+                                .strip_prefix("content-length: ")
+                                .map(|length| length.parse().unwrap())
+                        })
+                        .unwrap_or_default();
+                    if body.len() >= length {
+                        break body.to_string();
+                    }
+                };
+
+                let status = statuses.next().unwrap_or(200);
+                stream
+                    .write_all(
+                        format!(
+                            "HTTP/1.1 {} Status\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+                            status
+                        )
+                        .as_bytes(),
+                    )
+                    .await
+                    .unwrap();
+                sender.send(serde_json::from_str(&body).unwrap()).unwrap();
+            }
+        });
+
+        (url, receiver)
+    }
+
+    fn event(key: &str) -> Event {
+        Event {
+            node: "aks-hermes1-41950716-vmss000001".to_string(),
+            action: Action::Added,
+            taint: Taint {
+                effect: "NoSchedule".to_string(),
+                key: key.to_string(),
+                time_added: None,
+                value: Some("kaput".to_string()),
+            },
+            reason: "NetworkInterfaceCard=Kaput".to_string(),
+            timestamp: DateTime::parse_from_rfc3339("2024-05-12T11:21:10Z")
+                .unwrap()
+                .with_timezone(&Utc),
+        }
+    }
+
+    fn payload(event: Event) -> serde_json::Value {
+        serde_json::to_value(event).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_send_delivers_events_in_order() {
+        let (url, mut receiver) = server(vec![]).await;
+        let webhook = Webhook::new(url, Duration::from_secs(5), 0, Duration::ZERO);
+
+        webhook.send(vec![event("network"), event("disk")]);
+
+        assert_eq!(payload(event("network")), receiver.recv().await.unwrap());
+        assert_eq!(payload(event("disk")), receiver.recv().await.unwrap());
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_send_retries_failed_deliveries() {
+        let (url, mut receiver) = server(vec![500, 503, 500]).await;
+        let webhook = Webhook::new(url, Duration::from_secs(5), 2, Duration::from_millis(10));
+
+        webhook.send(vec![event("network"), event("disk")]);
+
+        // The first event fails on all three attempts and is dropped, whereas the second event
+        // succeeds on its first attempt.
+        for _ in 0..3 {
+            assert_eq!(payload(event("network")), receiver.recv().await.unwrap());
+        }
+        assert_eq!(payload(event("disk")), receiver.recv().await.unwrap());
+        assert!(logs_contain(
+            "Dropping event after exhausting retries of delivering it to webhook"
+        ));
+    }
+}
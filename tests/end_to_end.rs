@@ -164,8 +164,8 @@ fn assert_within_duration(
     assert!(
         within_duration,
         "{} is not within {} seconds of {}",
-        time.to_string(),
+        time,
         duration.num_seconds(),
-        target.to_string()
+        target
     )
 }
@@ -0,0 +1,137 @@
+use http::{Request, Response};
+use k8s_openapi::api::core::v1::Taint;
+use kube::client::Body;
+use kube::Client;
+use regex::Regex;
+use std::fs;
+use tainter::clock::UtcClock;
+use tainter::metrics::Metrics;
+use tainter::reconciler::{Normalize, Options, Pattern, Status};
+use tainter::{Condition, Configuration, Reconciler, Tainter};
+
+fn matchers() -> Vec<Configuration> {
+    vec![Configuration {
+        taints: vec![Taint {
+            effect: "NoSchedule".to_string(),
+            key: "not-ready".to_string(),
+            time_added: None,
+            value: None,
+        }],
+        conditions: vec![Condition {
+            type_: Pattern::Regex(Regex::new("Ready").unwrap()),
+            status: Status::In(vec!["False".to_string(), "Unknown".to_string()]),
+            reason: None,
+            message: None,
+            max_age: None,
+            heartbeat: None,
+            normalize: Normalize::Off,
+        }],
+        ..Default::default()
+    }]
+}
+
+#[tokio::test]
+async fn test_reconciler_configured_through_library_taints_node() {
+    let (mock_service, mut handle) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+    let client = Client::new(mock_service, "default");
+
+    let reconciler = Reconciler::new(
+        client,
+        matchers(),
+        Options::default(),
+        Metrics::new(),
+        Box::new(UtcClock),
+    );
+
+    tokio::spawn(async move {
+        reconciler.start().await;
+    });
+
+    let (request, response) = handle.next_request().await.expect("list nodes not called");
+    assert_eq!(request.method(), http::Method::GET);
+    assert_eq!(request.uri().to_string(), "/api/v1/nodes?&limit=500");
+    let body =
+        fs::read_to_string("src/reconciler/testfiles/list-nodes-single-eligible.json").unwrap();
+    response.send_response(
+        Response::builder()
+            .body(Body::from(body.into_bytes()))
+            .unwrap(),
+    );
+
+    let (request, _) = handle.next_request().await.expect("PUT node not called");
+    assert_eq!(request.method(), http::Method::PUT);
+    assert_eq!(
+        request.uri().to_string(),
+        "/api/v1/nodes/aks-zeus1-41950716-vmss000082?&fieldManager=tainter"
+    );
+}
+
+#[tokio::test]
+async fn test_tainter_builds_without_settings() {
+    let (mock_service, _) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+    let client = Client::new(mock_service, "default");
+
+    // Building must not require a configuration file or make any requests.
+    let _tainter = Tainter::builder(client)
+        .host("127.0.0.1")
+        .port(8081)
+        .matchers(matchers())
+        .options(Options {
+            skip_cordoned_nodes: true,
+            ..Default::default()
+        })
+        .clock(Box::new(UtcClock))
+        .build();
+}
+
+#[actix_web::test]
+async fn test_tainter_serves_health_with_single_worker() {
+    let (mock_service, _handle) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+    let client = Client::new(mock_service, "default");
+
+    let tainter = Tainter::builder(client)
+        .host("127.0.0.1")
+        .port(8082)
+        .workers(1)
+        .matchers(matchers())
+        .build();
+    actix_web::rt::spawn(tainter.start());
+
+    // The server may take a moment to bind.
+    let mut response = None;
+    for _ in 0..50 {
+        match reqwest::get("http://127.0.0.1:8082/health").await {
+            Ok(res) => {
+                response = Some(res);
+                break;
+            }
+            Err(_) => tokio::time::sleep(std::time::Duration::from_millis(100)).await,
+        }
+    }
+
+    let response = response.expect("server did not respond");
+    assert_eq!(http::StatusCode::OK, response.status());
+    assert_eq!(r#"{"status":"healthy"}"#, response.text().await.unwrap());
+}
+
+#[actix_web::test]
+async fn test_tainter_runs_reconciler_without_server() {
+    let (mock_service, mut handle) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+    let client = Client::new(mock_service, "default");
+
+    let tainter = Tainter::builder(client)
+        .host("127.0.0.1")
+        .port(8083)
+        .server_enabled(false)
+        .matchers(matchers())
+        .build();
+    actix_web::rt::spawn(tainter.start());
+
+    // The reconciler runs and lists nodes...
+    let (request, _) = handle.next_request().await.expect("list nodes not called");
+    assert_eq!(request.method(), http::Method::GET);
+    assert_eq!(request.uri().to_string(), "/api/v1/nodes?&limit=500");
+
+    // ...but nothing is bound to the server's port.
+    std::net::TcpListener::bind("127.0.0.1:8083").expect("server should not bind its port");
+}